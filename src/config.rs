@@ -0,0 +1,1279 @@
+use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use ethers::types::H160;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::monitors::{
+    self,
+    static_monitor::{self, CompareMode, StaticMonitor},
+    hyperliquid_monitor::{self, HyperliquidMonitor},
+    api_monitor::{AlertOperator, ApiMonitor},
+    xml_monitor::{self, XmlMonitor},
+    js_monitor::JsMonitor,
+    stream_monitor::{HyperliquidFillStream, StreamMonitor},
+    fetcher::{HttpMonitor, ResponseCache},
+    ChangeKind,
+    CircuitBreaker,
+    Monitor,
+};
+use crate::notifiers::server_chan::ServerChanNotifier;
+use crate::notifiers::NotifierKind;
+use crate::utils::{parse_timezone, shell_quote, ActiveSchedule};
+use crate::utils::events::EventLog;
+
+/// Monitoring task type
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum TaskType {
+    // Older configs stored the display name, keep accepting it
+    #[serde(alias = "Static Web")]
+    Static,
+    Hyperliquid,
+    #[serde(alias = "API Monitor")]
+    Api,
+    Xml,
+    JsRendered,
+}
+
+impl fmt::Display for TaskType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskType::Static => write!(f, "Static Web"),
+            TaskType::Hyperliquid => write!(f, "Hyperliquid"),
+            TaskType::Api => write!(f, "API Monitor"),
+            TaskType::Xml => write!(f, "XML XPath"),
+            TaskType::JsRendered => write!(f, "JS Rendered"),
+        }
+    }
+}
+
+impl TaskType {
+    /// Shortest interval monitors of this type run at, shorter ones are raised to it
+    pub fn min_interval_secs(&self) -> u64 {
+        match self {
+            TaskType::Static => monitors::MIN_INTERVAL_SECS_STATIC,
+            TaskType::Hyperliquid => monitors::MIN_INTERVAL_SECS_HYPERLIQUID,
+            TaskType::Api => monitors::MIN_INTERVAL_SECS_API,
+            TaskType::Xml => monitors::MIN_INTERVAL_SECS_XML,
+            TaskType::JsRendered => monitors::MIN_INTERVAL_SECS_JS,
+        }
+    }
+}
+
+impl FromStr for TaskType {
+    type Err = anyhow::Error;
+    
+    /// Parse a type name as typed on the command line, e.g. `static` or `api`
+    fn from_str(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "static" | "staticweb" => Ok(TaskType::Static),
+            "hyperliquid" => Ok(TaskType::Hyperliquid),
+            "api" | "apimonitor" => Ok(TaskType::Api),
+            "xml" | "xmlxpath" => Ok(TaskType::Xml),
+            "js" | "jsrendered" => Ok(TaskType::JsRendered),
+            _ => Err(anyhow!("Unknown task type {}, expected static, hyperliquid, api, xml or js", name.trim())),
+        }
+    }
+}
+
+/// Monitoring task configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskConfig {
+    /// Task name
+    pub name: String,
+    /// Task type
+    pub task_type: TaskType,
+    /// Target URL to monitor
+    pub url: String,
+    /// Selector (CSS for static and JS-rendered pages, one JSONPath per line for API, XPath for XML)
+    pub selector: String,
+    /// Wallet address (for Hyperliquid monitoring)
+    pub address: String,
+    /// Whether to monitor spot trading (for Hyperliquid monitoring)
+    pub monitor_spot: bool,
+    /// Whether to monitor contract trading (for Hyperliquid monitoring)
+    pub monitor_contract: bool,
+    /// Whether to monitor funding payments (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub monitor_funding: bool,
+    /// Whether to monitor spot token balances, catching deposits and withdrawals (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub monitor_spot_balance: bool,
+    /// Whether to monitor vault deposits, withdrawals and equity (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub monitor_vaults: bool,
+    /// Report fills from this many recent minutes when monitoring starts, 0 for none (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub backfill_minutes: u64,
+    /// Only report position changes whose size moved at least this percent, None for every change (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub position_change_threshold_pct: Option<f64>,
+    /// Only report vault equity moves of at least this percent, None for the default (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub vault_change_threshold_pct: Option<f64>,
+    /// Alert when a position's unrealized PnL moved at least this many USD, None for no PnL alerts (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub pnl_alert_threshold_usd: Option<f64>,
+    /// Receive fills over the WebSocket API as they happen instead of polling for them (for Hyperliquid monitoring)
+    #[serde(default)]
+    pub stream_fills: bool,
+    /// Explorer linked from notifications, `hyperdash`, `hypurrscan` or a URL containing `{address}` (for Hyperliquid monitoring)
+    #[serde(default = "default_explorer")]
+    pub explorer: String,
+    /// Monitoring interval (seconds)
+    pub interval_secs: u64,
+    /// Whether it's enabled
+    pub enabled: bool,
+    /// Notes for the task
+    pub notes: String,
+    /// Labels for grouping and filtering tasks, e.g. `prices`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Task-specific ServerChan key, falls back to the global keys when empty
+    #[serde(default)]
+    pub server_chan_key: Option<String>,
+    /// Names of the notifiers this task sends through, e.g. `serverchan`, empty for all configured ones
+    #[serde(default)]
+    pub notifiers: Vec<String>,
+    /// How the page content is compared between checks (for static web page monitoring)
+    #[serde(default)]
+    pub compare_mode: CompareMode,
+    /// Known-good content every check is compared with instead of the previous check, None to follow the content
+    /// (for static web page and API monitoring)
+    #[serde(default)]
+    pub baseline: Option<String>,
+    /// Load the page in headless Chrome before selecting (for static web page monitoring)
+    #[serde(default)]
+    pub render_js: bool,
+    /// Raw `Cookie` header sent with each request, for pages behind a login (static and API monitoring)
+    #[serde(default)]
+    pub cookies: Option<String>,
+    /// User-Agent sent with each request, None for a common browser one (static, API and XML monitoring)
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Pages larger than this many bytes fail the check instead of being read, None for 5 MB (for static web page monitoring)
+    #[serde(default)]
+    pub max_body_bytes: Option<usize>,
+    /// Regexes removed from the content before comparing (for static web page monitoring)
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// Regexes one of which must match the changed text for a notification (for static web page monitoring)
+    #[serde(default)]
+    pub require_patterns: Vec<String>,
+    /// Regex capturing a number to track instead of the content (for static web page monitoring)
+    #[serde(default)]
+    pub numeric_regex: Option<String>,
+    /// Only report a value once it starts meeting this condition, e.g. `["==", 0.0]`, None for every change (for API monitoring)
+    #[serde(default)]
+    pub alert_when: Option<(AlertOperator, f64)>,
+    /// Compare how many items the selectors match instead of their content (for API monitoring)
+    #[serde(default)]
+    pub array_length_mode: bool,
+    /// Alert once responses are persistently slower than this many milliseconds (static and API monitoring)
+    #[serde(default)]
+    pub slow_threshold_ms: Option<u64>,
+    /// Skip TLS certificate verification, for internal endpoints with self-signed certificates (static, API and XML monitoring)
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    /// Consecutive failed checks before a "task failing" notification is sent
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Alert once when nothing changed for this many seconds, for targets that should update regularly
+    #[serde(default)]
+    pub stale_after_secs: Option<u64>,
+    /// Local days and hours the task polls in, e.g. `mon-fri 09:30-16:00`, None to poll all the time
+    #[serde(default)]
+    pub active_schedule: Option<ActiveSchedule>,
+    /// Poll on wall-clock multiples of the interval (e.g. on the minute) instead of after each check, without jitter
+    #[serde(default)]
+    pub aligned: bool,
+    /// Stop the task automatically after this many seconds, None or zero runs forever
+    #[serde(default)]
+    pub max_runtime_secs: Option<u64>,
+    /// Send a "Started monitoring" notification on the first check, off to only record the initial state
+    #[serde(default = "default_notify_on_start")]
+    pub notify_on_start: bool,
+}
+
+/// Default number of failed checks in a row before a task is reported as failing
+pub fn default_failure_threshold() -> u32 {
+    3
+}
+
+/// Tasks announce that they started unless turned off
+fn default_notify_on_start() -> bool {
+    true
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            name: "New Task".to_string(),
+            task_type: TaskType::Static,
+            url: "https://example.com".to_string(),
+            selector: "".to_string(),
+            address: "".to_string(),
+            monitor_spot: true,
+            monitor_contract: false,
+            monitor_funding: false,
+            monitor_spot_balance: false,
+            monitor_vaults: false,
+            backfill_minutes: 0,
+            position_change_threshold_pct: None,
+            vault_change_threshold_pct: None,
+            pnl_alert_threshold_usd: None,
+            explorer: default_explorer(),
+            stream_fills: false,
+            interval_secs: 60,
+            enabled: true,
+            notes: String::new(),
+            tags: Vec::new(),
+            server_chan_key: None,
+            notifiers: Vec::new(),
+            compare_mode: CompareMode::RawBytes,
+            baseline: None,
+            render_js: false,
+            cookies: None,
+            user_agent: None,
+            max_body_bytes: None,
+            ignore_patterns: Vec::new(),
+            require_patterns: Vec::new(),
+            numeric_regex: None,
+            alert_when: None,
+            array_length_mode: false,
+            slow_threshold_ms: None,
+            accept_invalid_certs: false,
+            failure_threshold: default_failure_threshold(),
+            stale_after_secs: None,
+            active_schedule: None,
+            aligned: false,
+            max_runtime_secs: None,
+            notify_on_start: true,
+        }
+    }
+}
+
+impl TaskConfig {
+    /// Validate the task fields, returning a list of problems (empty when valid)
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push("Task name must not be empty".to_string());
+        }
+
+        if self.interval_secs == 0 {
+            errors.push("Interval must be greater than 0".to_string());
+        }
+        
+        if self.failure_threshold == 0 {
+            errors.push("Failure threshold must be at least 1".to_string());
+        }
+        
+        if self.stale_after_secs.is_some_and(|secs| secs <= self.interval_secs) {
+            errors.push("Staleness window must be longer than the interval".to_string());
+        }
+        
+        for name in &self.notifiers {
+            if NotifierKind::from_name(name).is_none() {
+                errors.push(format!("Unknown notifier {}", name.trim()));
+            }
+        }
+
+        match self.task_type {
+            TaskType::Static | TaskType::JsRendered => {
+                if let Err(e) = validate_url(&self.url) {
+                    errors.push(e);
+                }
+                let selector = self.selector.trim();
+                if !selector.is_empty() && scraper::Selector::parse(selector).is_err() {
+                    errors.push(format!("Invalid CSS selector: {}", selector));
+                }
+                if self.task_type == TaskType::Static && self.compare_mode == CompareMode::Selector && selector.is_empty() {
+                    errors.push("Comparing selected text needs a CSS selector".to_string());
+                }
+                let renders = self.task_type == TaskType::JsRendered || self.render_js;
+                if renders && self.accept_invalid_certs {
+                    errors.push(static_monitor::RENDER_JS_INVALID_CERTS.to_string());
+                }
+                let numeric_regex: Vec<String> = self.numeric_regex.iter().cloned().collect();
+                for patterns in [&self.ignore_patterns, &self.require_patterns, &numeric_regex] {
+                    if let Err(e) = static_monitor::compile_patterns(patterns) {
+                        errors.push(e.to_string());
+                    }
+                }
+            }
+            TaskType::Api => {
+                if let Err(e) = validate_url(&self.url) {
+                    errors.push(e);
+                }
+                for selector in self.selector.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    if let Err(e) = jsonpath_lib::Compiled::compile(selector) {
+                        errors.push(format!("Invalid JSONPath {}: {}", selector, e));
+                    }
+                }
+                if self.alert_when.is_some_and(|(_, value)| !value.is_finite()) {
+                    errors.push("Alert value must be a number".to_string());
+                }
+            }
+            TaskType::Xml => {
+                if let Err(e) = validate_url(&self.url) {
+                    errors.push(e);
+                }
+                if let Err(e) = xml_monitor::validate_xpath(&self.selector) {
+                    errors.push(format!("Invalid XPath: {}", e));
+                }
+            }
+            TaskType::Hyperliquid => {
+                let address = self.address.trim();
+                if !address.starts_with("0x") || address.len() != 42 || H160::from_str(address).is_err() {
+                    errors.push("Wallet address must be 0x followed by 40 hex characters".to_string());
+                }
+                if !self.monitor_spot && !self.monitor_contract && !self.monitor_funding && !self.monitor_spot_balance && !self.monitor_vaults {
+                    errors.push("Select at least one of Contract, Spot, Funding, Spot balances or Vaults".to_string());
+                }
+                if self.position_change_threshold_pct.is_some_and(|pct| !pct.is_finite() || pct < 0.0) {
+                    errors.push("Position change threshold must be a positive percentage".to_string());
+                }
+                if self.vault_change_threshold_pct.is_some_and(|pct| !pct.is_finite() || pct < 0.0) {
+                    errors.push("Vault change threshold must be a positive percentage".to_string());
+                }
+                if self.pnl_alert_threshold_usd.is_some_and(|usd| !usd.is_finite() || usd < 0.0) {
+                    errors.push("PnL alert threshold must be a positive amount".to_string());
+                }
+                if let Err(e) = hyperliquid_monitor::explorer_template(&self.explorer) {
+                    errors.push(e.to_string());
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Whether the task matches a list filter: `#tag` matches a tag exactly, other text any part of
+    /// the name, URL, address, notes or tags (case-insensitive)
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        if filter.is_empty() {
+            return true;
+        }
+        
+        if let Some(tag) = filter.strip_prefix('#') {
+            return self.tags.iter().any(|own| own.to_lowercase() == tag);
+        }
+        
+        [&self.name, &self.url, &self.address, &self.notes]
+            .into_iter()
+            .chain(&self.tags)
+            .any(|field| field.to_lowercase().contains(&filter))
+    }
+    
+    /// What the task watches: the wallet address for Hyperliquid, the URL otherwise
+    pub fn target(&self) -> &str {
+        match self.task_type {
+            TaskType::Hyperliquid => &self.address,
+            _ => &self.url,
+        }
+    }
+    
+    /// One task per distinct non-blank line, with this task's settings and the line as its target (the address
+    /// for Hyperliquid tasks, the URL otherwise); `{line}` in the name and notes is replaced by the line
+    pub fn expand_lines(&self, lines: &str) -> Vec<TaskConfig> {
+        let mut seen = std::collections::HashSet::new();
+        lines
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && seen.insert(*line))
+            .map(|line| {
+                let mut task = self.clone();
+                match task.task_type {
+                    TaskType::Hyperliquid => task.address = line.to_string(),
+                    _ => task.url = line.to_string(),
+                }
+                task.name = self.name.replace("{line}", line);
+                task.notes = self.notes.replace("{line}", line);
+                task
+            })
+            .collect()
+    }
+    
+    /// Warning when the interval is below the minimum for the task type or the safe floor for the target host
+    pub fn interval_warning(&self) -> Option<String> {
+        let min_interval_secs = self.task_type.min_interval_secs();
+        if self.interval_secs < min_interval_secs {
+            return Some(format!("Intervals under {}s are raised to {}s for {} tasks", min_interval_secs, min_interval_secs, self.task_type));
+        }
+        
+        let host = match self.task_type {
+            TaskType::Hyperliquid => "api.hyperliquid.xyz".to_string(),
+            _ => reqwest::Url::parse(self.url.trim()).ok()?.host_str()?.to_string(),
+        };
+        let floor = SAFE_INTERVAL_FLOORS
+            .iter()
+            .find(|(known, _)| host == *known || host.ends_with(&format!(".{}", known)))
+            .map(|(_, floor)| *floor)
+            .unwrap_or(DEFAULT_SAFE_INTERVAL_SECS);
+        
+        (self.interval_secs < floor).then(|| {
+            format!("Intervals under {}s may hit rate limits on {}", floor, host)
+        })
+    }
+    
+    /// Whether the first check is announced, both the task and the global setting must allow it
+    pub fn announces_start(&self, notification: &NotificationConfig) -> bool {
+        self.notify_on_start && notification.notify_on_start
+    }
+    
+    /// Create the monitor described by this task, failing on options `validate` reports
+    pub fn build_monitor(&self) -> Result<Box<dyn Monitor>> {
+        self.build_monitor_with_cache(None)
+    }
+    
+    /// Create the monitor described by this task, its HTTP requests going through the cache when one is given
+    pub fn build_monitor_with_cache(&self, cache: Option<&ResponseCache>) -> Result<Box<dyn Monitor>> {
+        let monitor: Box<dyn Monitor> = match self.task_type {
+            TaskType::Static => Box::new(
+                StaticMonitor::builder(&self.url, &self.selector)
+                    .interval(self.interval_secs)
+                    .notes(&self.notes)
+                    .compare_mode(self.compare_mode)
+                    .baseline(self.baseline.as_deref())
+                    .render_js(self.render_js)
+                    .cookies(self.cookies.clone())
+                    .user_agent(self.user_agent.as_deref())
+                    .accept_invalid_certs(self.accept_invalid_certs)
+                    .ignore_patterns(&self.ignore_patterns)
+                    .require_patterns(&self.require_patterns)
+                    .numeric_regex(self.numeric_regex.as_deref())
+                    .slow_threshold(self.slow_threshold_ms.map(Duration::from_millis))
+                    .max_body_bytes(self.max_body_bytes)
+                    .response_cache(cache)
+                    .build()?,
+            ),
+            TaskType::Api => Box::new(
+                ApiMonitor::builder(&self.url, &self.selector)
+                    .interval(self.interval_secs)
+                    .notes(&self.notes)
+                    .cookies(self.cookies.clone())
+                    .user_agent(self.user_agent.as_deref())
+                    .accept_invalid_certs(self.accept_invalid_certs)
+                    .slow_threshold(self.slow_threshold_ms.map(Duration::from_millis))
+                    .alert_when(self.alert_when)
+                    .array_length(self.array_length_mode)
+                    .baseline(self.baseline.as_deref())
+                    .response_cache(cache)
+                    .build(),
+            ),
+            TaskType::Xml => {
+                let mut monitor = XmlMonitor::new_with_notes(
+                    &self.url,
+                    &self.selector,
+                    self.interval_secs,
+                    &self.notes,
+                );
+                monitor.set_user_agent(self.user_agent.as_deref());
+                monitor.set_accept_invalid_certs(self.accept_invalid_certs);
+                if let Some(cache) = cache {
+                    monitor.set_response_cache(cache);
+                }
+                Box::new(monitor)
+            }
+            TaskType::JsRendered => {
+                Box::new(JsMonitor::new_with_notes(
+                    &self.url,
+                    &self.selector,
+                    self.interval_secs,
+                    &self.notes,
+                ))
+            }
+            // Spot fills come from the stream when it is on
+            TaskType::Hyperliquid => Box::new(
+                HyperliquidMonitor::builder(&self.address)
+                    .interval(self.interval_secs)
+                    .notes(&self.notes)
+                    .spot(self.monitor_spot && !self.stream_fills)
+                    .contract(self.monitor_contract)
+                    .monitor_funding(self.monitor_funding)
+                    .monitor_spot_balance(self.monitor_spot_balance)
+                    .monitor_vaults(self.monitor_vaults)
+                    .backfill_minutes(self.backfill_minutes)
+                    .position_change_threshold(self.position_change_threshold_pct.unwrap_or(0.0))
+                    .vault_change_threshold(self.vault_change_threshold_pct.unwrap_or(hyperliquid_monitor::DEFAULT_VAULT_CHANGE_PCT))
+                    .pnl_alert_threshold(self.pnl_alert_threshold_usd)
+                    .explorer(&self.explorer)
+                    .build()?,
+            ),
+        };
+        Ok(monitor)
+    }
+    
+    /// Create the push stream run alongside the polling monitor, None when the task doesn't stream.
+    /// The stream only takes over spot fills, perpetual trades are still reported by position polling
+    pub fn build_stream_monitor(&self) -> Option<Box<dyn StreamMonitor>> {
+        if self.task_type != TaskType::Hyperliquid || !self.stream_fills || !self.monitor_spot {
+            return None;
+        }
+        Some(Box::new(HyperliquidFillStream::new_with_notes(&self.address, true, false, &self.notes)))
+    }
+    
+    /// Equivalent `hyperliquid_monitor` command running this task alone, for moving it to a headless server;
+    /// options without a command-line flag (notes, notifiers, user agent, slow alert…) are left out,
+    /// and cookies are read from `$MONITOR_COOKIES` so the session never ends up in the clipboard
+    pub fn cli_command(&self) -> String {
+        let mut args: Vec<String> = Vec::new();
+        let mut arg = |flag: &str, value: &str| {
+            args.push(flag.to_string());
+            args.push(shell_quote(value));
+        };
+        // Cookies are a login session, left for the shell to fill in
+        let mut cookies_from_env = false;
+        
+        let subcommand = match self.task_type {
+            TaskType::Static => {
+                arg("--url", &self.url);
+                arg("--selector", &self.selector);
+                for pattern in &self.ignore_patterns {
+                    arg("--ignore", pattern);
+                }
+                for pattern in &self.require_patterns {
+                    arg("--require", pattern);
+                }
+                if let Some(pattern) = &self.numeric_regex {
+                    arg("--numeric-regex", pattern);
+                }
+                cookies_from_env = self.cookies.is_some();
+                if self.compare_mode != CompareMode::RawBytes {
+                    arg("--compare", &self.compare_mode.to_string());
+                }
+                "static"
+            }
+            TaskType::Api => {
+                arg("--url", &self.url);
+                for selector in self.selector.lines().map(str::trim).filter(|selector| !selector.is_empty()) {
+                    arg("--selector", selector);
+                }
+                if let Some((operator, value)) = self.alert_when {
+                    arg("--alert-when", &format!("{} {}", operator, value));
+                }
+                cookies_from_env = self.cookies.is_some();
+                "api"
+            }
+            TaskType::Xml => {
+                arg("--url", &self.url);
+                arg("--xpath", &self.selector);
+                "xml"
+            }
+            TaskType::JsRendered => {
+                arg("--url", &self.url);
+                if !self.selector.is_empty() {
+                    arg("--selector", &self.selector);
+                }
+                "js"
+            }
+            TaskType::Hyperliquid => {
+                arg("--address", &self.address);
+                arg("--spot", &self.monitor_spot.to_string());
+                arg("--contract", &self.monitor_contract.to_string());
+                if self.backfill_minutes > 0 {
+                    arg("--backfill-minutes", &self.backfill_minutes.to_string());
+                }
+                if let Some(percent) = self.position_change_threshold_pct.filter(|percent| *percent > 0.0) {
+                    arg("--position-threshold", &percent.to_string());
+                }
+                if let Some(percent) = self.vault_change_threshold_pct {
+                    arg("--vault-threshold", &percent.to_string());
+                }
+                if let Some(usd) = self.pnl_alert_threshold_usd {
+                    arg("--pnl-threshold", &usd.to_string());
+                }
+                if self.explorer != hyperliquid_monitor::DEFAULT_EXPLORER {
+                    arg("--explorer", &self.explorer);
+                }
+                "hyperliquid"
+            }
+        };
+        if cookies_from_env {
+            // Double quotes so the shell expands the variable, unlike the values quoted above
+            args.push("--cookies".to_string());
+            args.push(format!("\"${}\"", COOKIES_ENV_VAR));
+        }
+        
+        let mut command = format!("hyperliquid_monitor {} {} --interval {}", subcommand, args.join(" "), self.interval_secs);
+        let switches = [
+            (self.task_type == TaskType::Static && self.render_js, "--render-js"),
+            (self.task_type == TaskType::Api && self.array_length_mode, "--array-length"),
+            (self.task_type == TaskType::Hyperliquid && self.monitor_funding, "--funding"),
+            (self.task_type == TaskType::Hyperliquid && self.monitor_spot_balance, "--spot-balance"),
+            (self.task_type == TaskType::Hyperliquid && self.monitor_vaults, "--vaults"),
+            (self.task_type == TaskType::Hyperliquid && self.stream_fills, "--stream"),
+        ];
+        for (_, switch) in switches.iter().filter(|(on, _)| *on) {
+            command.push(' ');
+            command.push_str(switch);
+        }
+        command
+    }
+}
+
+/// Environment variable the copied CLI command reads the cookies from
+pub const COOKIES_ENV_VAR: &str = "MONITOR_COOKIES";
+
+/// Split comma-separated tags, dropping blanks and duplicates
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(|tag| tag.trim().trim_start_matches('#')).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Split one pattern per line, dropping blank lines
+pub fn parse_patterns(input: &str) -> Vec<String> {
+    input.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
+/// Polling faster than this risks rate limits on the given hosts
+const SAFE_INTERVAL_FLOORS: &[(&str, u64)] = &[
+    ("api.hyperliquid.xyz", 10),
+    ("api.coingecko.com", 60),
+    ("api.github.com", 60),
+];
+
+/// Floor for hosts without a known limit
+const DEFAULT_SAFE_INTERVAL_SECS: u64 = 5;
+
+/// Check that a URL parses and uses http(s)
+fn validate_url(url: &str) -> Result<(), String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("URL must not be empty".to_string());
+    }
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => Ok(()),
+        Ok(parsed) => Err(format!("Unsupported URL scheme: {}", parsed.scheme())),
+        Err(e) => Err(format!("Invalid URL: {}", e)),
+    }
+}
+
+/// Notification configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Whether to enable ServerChan notifications
+    pub enabled: bool,
+    /// ServerChan keys
+    pub server_chan_keys: Vec<String>,
+    /// Quiet hours as (start, end) local hours, non-critical notifications are held until they end
+    #[serde(default)]
+    pub quiet_hours: Option<(u8, u8)>,
+    /// ServerChan delivery channels, e.g. `9|66`
+    #[serde(default)]
+    pub channel: Option<String>,
+    /// ServerChan message tags
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Log notifications instead of sending them
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Send content to ServerChan as written instead of escaping it for its Markdown rendering
+    #[serde(default)]
+    pub plain_text: bool,
+    /// Send non-critical changes as one digest per task every this many seconds, None to send each one
+    #[serde(default)]
+    pub digest_interval_secs: Option<u64>,
+    /// Send "Started monitoring" notifications, off keeps restarts silent for every task
+    #[serde(default = "default_notify_on_start")]
+    pub notify_on_start: bool,
+    /// Prefix, e.g. an emoji, put before the title of each kind of notification; kinds left out get none
+    #[serde(default = "default_title_prefixes")]
+    pub title_prefixes: BTreeMap<ChangeKind, String>,
+    /// Whether muting also silences errors and outages, which otherwise still go out
+    #[serde(default)]
+    pub mute_critical: bool,
+}
+
+fn default_title_prefixes() -> BTreeMap<ChangeKind, String> {
+    [
+        (ChangeKind::Started, "🟢"),
+        (ChangeKind::Updated, "🔔"),
+        (ChangeKind::Trade, "💱"),
+        (ChangeKind::Position, "📊"),
+        (ChangeKind::Funding, "💸"),
+        (ChangeKind::Balance, "💰"),
+        (ChangeKind::Snapshot, "📸"),
+        (ChangeKind::Down, "🔴"),
+        (ChangeKind::Recovered, "✅"),
+        (ChangeKind::ThresholdCrossed, "📈"),
+        (ChangeKind::Stale, "⏳"),
+        (ChangeKind::Stopped, "⏹️"),
+        (ChangeKind::Error, "🔴"),
+    ]
+    .into_iter()
+    .map(|(kind, prefix)| (kind, prefix.to_string()))
+    .collect()
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_chan_keys: vec![String::new()],
+            quiet_hours: None,
+            channel: None,
+            tags: None,
+            dry_run: false,
+            plain_text: false,
+            digest_interval_secs: None,
+            notify_on_start: true,
+            title_prefixes: default_title_prefixes(),
+            mute_critical: false,
+        }
+    }
+}
+
+impl NotificationConfig {
+    /// Digest window, None when every notification is sent on its own
+    pub fn digest_interval(&self) -> Option<Duration> {
+        self.digest_interval_secs.filter(|secs| *secs > 0).map(Duration::from_secs)
+    }
+
+    /// Apply the configured channel, tags, dry-run mode and content escaping to a notifier
+    pub fn apply_to(&self, notifier: &mut ServerChanNotifier) {
+        notifier.set_channel(self.channel.clone());
+        notifier.set_tags(self.tags.clone());
+        notifier.set_dry_run(self.dry_run);
+        notifier.set_plain_text(self.plain_text);
+    }
+}
+
+/// GUI color theme
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Theme {
+    /// Follow the operating system theme
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::System => write!(f, "System"),
+            Theme::Light => write!(f, "Light"),
+            Theme::Dark => write!(f, "Dark"),
+        }
+    }
+}
+
+/// GUI window geometry in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: f32,
+    pub height: f32,
+    /// Outer top left corner, None when the platform doesn't report it
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// Current configuration file layout, bump with a new step in `Configs::migrate` when it changes
+pub const CONFIG_VERSION: u32 = 3;
+
+/// Application configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Configs {
+    /// Layout version of the file, 0 for files written before versioning
+    #[serde(default)]
+    pub version: u32,
+    /// Notification configuration
+    pub notification: NotificationConfig,
+    /// Monitoring task list
+    pub tasks: Vec<TaskConfig>,
+    /// GUI color theme
+    #[serde(default)]
+    pub theme: Theme,
+    /// Interval for newly created tasks (seconds)
+    #[serde(default = "default_interval_secs")]
+    pub default_interval_secs: u64,
+    /// Explorer linked from the notifications of newly created Hyperliquid tasks
+    #[serde(default = "default_explorer")]
+    pub default_explorer: String,
+    /// Randomize every poll interval by up to this percentage, zero polls on the exact interval
+    #[serde(default)]
+    pub jitter_percent: u8,
+    /// Most checks running at the same time across all tasks, the others wait for a free slot
+    #[serde(default = "default_max_concurrent_checks")]
+    pub max_concurrent_checks: usize,
+    /// Seconds a response is reused by the other tasks requesting the same URL, zero fetches for every task
+    #[serde(default)]
+    pub response_cache_secs: u64,
+    /// Pause all polling once too many checks fail across tasks, None to never pause
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// File receiving a JSON line per task event (checks, changes, notifications, errors), None to keep no event log
+    #[serde(default)]
+    pub event_log: Option<String>,
+    /// GUI window geometry from the last session
+    #[serde(default)]
+    pub window: Option<WindowGeometry>,
+    /// IANA timezone for times shown in notifications, e.g. `Asia/Shanghai`, None for the local timezone
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+/// Global kill-switch pausing every task after a burst of failed checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Failed checks across all tasks that open the circuit
+    pub max_errors: u32,
+    /// Window the failures are counted in (seconds)
+    pub window_secs: u64,
+    /// How long polling stays paused once open (seconds)
+    pub cooldown_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self { max_errors: 50, window_secs: 300, cooldown_secs: 900 }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    60
+}
+
+fn default_explorer() -> String {
+    hyperliquid_monitor::DEFAULT_EXPLORER.to_string()
+}
+
+fn default_max_concurrent_checks() -> usize {
+    16
+}
+
+impl Default for Configs {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            notification: NotificationConfig::default(),
+            tasks: Vec::new(),
+            theme: Theme::default(),
+            default_interval_secs: default_interval_secs(),
+            default_explorer: default_explorer(),
+            jitter_percent: 0,
+            max_concurrent_checks: default_max_concurrent_checks(),
+            response_cache_secs: 0,
+            circuit_breaker: None,
+            event_log: None,
+            window: None,
+            timezone: None,
+        }
+    }
+}
+
+impl Configs {
+    /// Circuit breaker shared by all tasks, never opening when none is configured
+    pub fn circuit_breaker(&self) -> CircuitBreaker {
+        match self.circuit_breaker {
+            Some(breaker) => CircuitBreaker::new(
+                breaker.max_errors,
+                Duration::from_secs(breaker.window_secs),
+                Duration::from_secs(breaker.cooldown_secs),
+            ),
+            None => CircuitBreaker::default(),
+        }
+    }
+    
+    /// Structured event log, None when no path is set
+    pub fn event_log(&self) -> Option<EventLog> {
+        self.event_log.as_deref().map(str::trim).filter(|path| !path.is_empty()).map(EventLog::new)
+    }
+    
+    /// Cache shared by the tasks' requests, None when caching is off
+    pub fn response_cache(&self) -> Option<ResponseCache> {
+        (self.response_cache_secs > 0).then(|| ResponseCache::new(Duration::from_secs(self.response_cache_secs)))
+    }
+    
+    /// Load configuration from a JSON file, upgrading older layouts and writing them back when the file is writable
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let config_file = path.as_ref();
+        let (config, migrated) = Self::read_migrated(config_file)?;
+        if migrated {
+            match config.save(config_file) {
+                Ok(()) => info!("Upgraded configuration file to version {}", CONFIG_VERSION),
+                Err(e) => warn!("Upgraded configuration to version {} but could not write it back to {}: {}", CONFIG_VERSION, config_file.display(), e),
+            }
+        }
+        Ok(config)
+    }
+    
+    /// Load configuration from a JSON file, upgrading older layouts in memory only; the file is never written
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::read_migrated(path.as_ref()).map(|(config, _)| config)
+    }
+    
+    /// Parse a configuration file and upgrade it to the current layout, also returning whether it was upgraded
+    fn read_migrated(config_file: &Path) -> Result<(Self, bool)> {
+        if !config_file.exists() {
+            return Err(anyhow!("Configuration file does not exist"));
+        }
+        let config_str = fs::read_to_string(config_file)?;
+        let mut raw: Value = serde_json::from_str(&config_str)
+            .map_err(|e| anyhow!("Invalid configuration file {}: {}", config_file.display(), e))?;
+        let migrated = Self::migrate(&mut raw);
+        let config: Configs = serde_json::from_value(raw)
+            .map_err(|e| anyhow!("Invalid configuration file {}: {}", config_file.display(), e))?;
+        Ok((config, migrated))
+    }
+    
+    /// Copy a configuration file next to itself as `<name>.bak`, e.g. before replacing an unreadable one
+    pub fn back_up<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+        let path = path.as_ref();
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        fs::copy(path, &backup)?;
+        Ok(backup)
+    }
+    
+    /// Upgrade a raw configuration to the current layout, returns whether anything changed
+    fn migrate(raw: &mut Value) -> bool {
+        let version = raw["version"].as_u64().unwrap_or(0);
+        if version > u64::from(CONFIG_VERSION) {
+            warn!(
+                "Configuration file version {} is newer than this build supports ({}), unknown settings are ignored",
+                version, CONFIG_VERSION
+            );
+            return false;
+        }
+        if version == u64::from(CONFIG_VERSION) {
+            return false;
+        }
+        
+        // 0 -> 1: task types were stored by their display name
+        if version < 1 {
+            if let Some(tasks) = raw["tasks"].as_array_mut() {
+                for task in tasks {
+                    let task_type = match task["task_type"].as_str() {
+                        Some("Static Web") => "Static",
+                        Some("API Monitor") => "Api",
+                        _ => continue,
+                    };
+                    task["task_type"] = Value::from(task_type);
+                }
+            }
+        }
+        
+        // 1 -> 2: hash-only tasks became a compare mode
+        if version < 2 {
+            if let Some(tasks) = raw["tasks"].as_array_mut().map(|tasks| tasks.iter_mut().filter_map(Value::as_object_mut)) {
+                for task in tasks {
+                    if task.remove("hash_only").and_then(|hash_only| hash_only.as_bool()) == Some(true) {
+                        task.insert("compare_mode".to_string(), Value::from(CompareMode::Hash.to_string()));
+                    }
+                }
+            }
+        }
+        
+        // 2 -> 3: tasks stopping got their own kind, saved prefixes lack it
+        if version < 3 {
+            if let Some(prefixes) = raw.get_mut("notification").and_then(|notification| notification.get_mut("title_prefixes")).and_then(Value::as_object_mut) {
+                prefixes.entry("Stopped").or_insert_with(|| Value::from(default_title_prefixes()[&ChangeKind::Stopped].as_str()));
+            }
+        }
+        
+        raw["version"] = Value::from(CONFIG_VERSION);
+        true
+    }
+
+    /// The configured display timezone, None for the local timezone
+    pub fn display_timezone(&self) -> Result<Option<Tz>> {
+        self.timezone
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .map(parse_timezone)
+            .transpose()
+    }
+
+    /// A new task using the configured default interval and explorer
+    pub fn new_task(&self) -> TaskConfig {
+        TaskConfig {
+            interval_secs: self.default_interval_secs,
+            explorer: self.default_explorer.clone(),
+            ..TaskConfig::default()
+        }
+    }
+    
+    /// Save configuration to a JSON file, through a temporary file so an interrupted save never leaves it half written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let config_str = serde_json::to_string_pretty(self)?;
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, config_str)?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+    
+    /// Index of the task with this name, an error when none or several tasks have it
+    pub fn find_task(&self, name: &str) -> Result<usize> {
+        let matches: Vec<usize> = self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.name == name)
+            .map(|(index, _)| index)
+            .collect();
+        match matches.as_slice() {
+            [index] => Ok(*index),
+            [] => Err(anyhow!("No task named {}", name)),
+            _ => Err(anyhow!("Several tasks are named {} (indexes {:?}), remove by index instead", name, matches)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_task(task_type: TaskType) -> TaskConfig {
+        TaskConfig {
+            name: format!("{} task", task_type),
+            task_type,
+            url: "https://example.com/data".to_string(),
+            selector: match task_type {
+                TaskType::Api => "$.data.price".to_string(),
+                TaskType::Xml => "/rss/channel/item[1]/title".to_string(),
+                _ => String::new(),
+            },
+            address: "0xc64cc00b46101bd40aa1c3121195e85c0b0918d8".to_string(),
+            ..TaskConfig::default()
+        }
+    }
+
+    #[test]
+    fn task_types_round_trip_through_save_load_and_start() {
+        let path = std::env::temp_dir().join(format!("monitor-config-{}.json", std::process::id()));
+        let configs = Configs {
+            tasks: vec![
+                sample_task(TaskType::Static),
+                sample_task(TaskType::Api),
+                sample_task(TaskType::Hyperliquid),
+                sample_task(TaskType::Xml),
+                sample_task(TaskType::JsRendered),
+            ],
+            ..Configs::default()
+        };
+
+        configs.save(&path).unwrap();
+        let loaded = Configs::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let expected = [
+            (TaskType::Static, "Static webpage monitor for"),
+            (TaskType::Api, "API monitor for"),
+            (TaskType::Hyperliquid, "Hyperliquid monitor for"),
+            (TaskType::Xml, "XML monitor for"),
+            (TaskType::JsRendered, "JS-rendered page monitor for"),
+        ];
+        assert_eq!(loaded.tasks.len(), expected.len());
+        for (task, (task_type, name_prefix)) in loaded.tasks.iter().zip(expected) {
+            assert_eq!(task.task_type, task_type);
+            assert!(task.validate().is_empty(), "{:?}", task.validate());
+            assert!(task.build_monitor().unwrap().get_name().starts_with(name_prefix));
+        }
+    }
+
+    #[test]
+    fn unversioned_configs_are_migrated_and_written_back() {
+        let path = std::env::temp_dir().join(format!("monitor-config-legacy-{}.json", std::process::id()));
+        fs::write(&path, r#"{
+            "notification": { "enabled": true, "server_chan_keys": ["SCT123"] },
+            "tasks": [{
+                "name": "Announcements", "task_type": "Static Web", "url": "https://example.com",
+                "selector": ".news", "address": "", "monitor_spot": true, "monitor_contract": false,
+                "interval_secs": 60, "enabled": true, "notes": "", "hash_only": true
+            }]
+        }"#).unwrap();
+
+        let legacy = fs::read_to_string(&path).unwrap();
+        assert_eq!(Configs::read(&path).unwrap().tasks[0].compare_mode, CompareMode::Hash);
+        assert_eq!(fs::read_to_string(&path).unwrap(), legacy, "reading must not write the file");
+
+        // A file that cannot be written back still loads
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        fs::create_dir(&temp).unwrap();
+        assert_eq!(Configs::load(&path).unwrap().version, CONFIG_VERSION);
+        fs::remove_dir(&temp).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), legacy);
+
+        let loaded = Configs::load(&path).unwrap();
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.version, CONFIG_VERSION);
+        assert_eq!(loaded.tasks[0].task_type, TaskType::Static);
+        assert!(loaded.tasks[0].announces_start(&loaded.notification));
+        assert_eq!(written["version"], CONFIG_VERSION);
+        assert_eq!(written["tasks"][0]["task_type"], "Static");
+        assert_eq!(loaded.tasks[0].compare_mode, CompareMode::Hash);
+        assert_eq!(written["tasks"][0]["compare_mode"], "hash");
+    }
+
+    #[test]
+    fn parse_errors_report_the_line() {
+        let path = std::env::temp_dir().join(format!("monitor-config-corrupt-{}.json", std::process::id()));
+        fs::write(&path, "{\n  \"tasks\": [,]\n}").unwrap();
+
+        let error = Configs::load(&path).unwrap_err().to_string();
+        let backup = Configs::back_up(&path).unwrap();
+        let backed_up = fs::read_to_string(&backup).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&backup).unwrap();
+
+        assert!(error.contains("line 2"), "{}", error);
+        assert!(backed_up.contains("[,]"));
+    }
+
+    #[test]
+    fn saved_title_prefixes_gain_the_stopped_kind() {
+        let mut raw = serde_json::json!({
+            "version": 2,
+            "notification": { "title_prefixes": { "Updated": "" } },
+            "tasks": []
+        });
+        assert!(Configs::migrate(&mut raw));
+        assert_eq!(raw["notification"]["title_prefixes"], serde_json::json!({ "Updated": "", "Stopped": "⏹️" }));
+
+        // A file without notification settings keeps its defaults
+        let mut raw = serde_json::json!({ "version": 2, "tasks": [] });
+        assert!(Configs::migrate(&mut raw));
+        assert!(raw.get("notification").is_none());
+    }
+
+    #[test]
+    fn newer_configs_load_without_migrating() {
+        let mut raw = serde_json::json!({ "version": CONFIG_VERSION + 1, "tasks": [] });
+        assert!(!Configs::migrate(&mut raw));
+        assert_eq!(raw["version"], CONFIG_VERSION + 1);
+    }
+
+    #[test]
+    fn tasks_filter_by_tag_or_text() {
+        let task = TaskConfig {
+            name: "BTC price".to_string(),
+            tags: parse_tags(" prices, #Crypto,, prices "),
+            ..sample_task(TaskType::Api)
+        };
+
+        assert_eq!(task.tags, vec!["prices", "Crypto"]);
+        assert!(task.matches_filter(""));
+        assert!(task.matches_filter("#crypto"));
+        assert!(!task.matches_filter("#price"));
+        assert!(task.matches_filter("btc"));
+        assert!(task.matches_filter("pric"));
+        assert!(!task.matches_filter("wallets"));
+    }
+
+    #[test]
+    fn bulk_lines_become_one_task_each() {
+        let template = TaskConfig {
+            name: "Wallet {line}".to_string(),
+            notes: "{line}".to_string(),
+            ..sample_task(TaskType::Hyperliquid)
+        };
+        let tasks = template.expand_lines(" 0xaaa\n\n0xbbb\n0xaaa\n");
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].address, "0xbbb");
+        assert_eq!(tasks[1].name, "Wallet 0xbbb");
+        assert_eq!(tasks[1].notes, "0xbbb");
+        assert_eq!(tasks[0].interval_secs, template.interval_secs);
+
+        let pages = TaskConfig { name: "{line}".to_string(), ..sample_task(TaskType::Static) }.expand_lines("https://a.example/");
+        assert_eq!(pages[0].url, "https://a.example/");
+        assert_eq!(pages[0].name, "https://a.example/");
+    }
+
+    #[test]
+    fn legacy_display_names_are_still_accepted() {
+        for (legacy, task_type) in [
+            ("Static Web", TaskType::Static),
+            ("API Monitor", TaskType::Api),
+            ("Hyperliquid", TaskType::Hyperliquid),
+        ] {
+            let parsed: TaskType = serde_json::from_str(&format!("\"{}\"", legacy)).unwrap();
+            assert_eq!(parsed, task_type);
+        }
+    }
+
+    #[test]
+    fn tasks_are_found_by_unique_name_and_types_parse_from_cli_names() {
+        let mut configs = Configs {
+            tasks: vec![sample_task(TaskType::Static), sample_task(TaskType::Api), sample_task(TaskType::Api)],
+            ..Configs::default()
+        };
+        configs.tasks[0].name = "News".to_string();
+
+        assert_eq!(configs.find_task("News").unwrap(), 0);
+        assert!(configs.find_task("Missing").is_err());
+        assert!(configs.find_task(&configs.tasks[1].name).unwrap_err().to_string().contains("[1, 2]"));
+
+        assert_eq!("js-rendered".parse::<TaskType>().unwrap(), TaskType::JsRendered);
+        assert_eq!(" API ".parse::<TaskType>().unwrap(), TaskType::Api);
+        assert!("ftp".parse::<TaskType>().is_err());
+    }
+
+    #[test]
+    fn tasks_translate_to_cli_commands() {
+        let mut api = sample_task(TaskType::Api);
+        api.selector = "$.data.price\n$.data.volume".to_string();
+        api.alert_when = Some((AlertOperator::Gte, 100.0));
+        api.interval_secs = 30;
+        assert_eq!(
+            api.cli_command(),
+            "hyperliquid_monitor api --url https://example.com/data --selector '$.data.price' --selector '$.data.volume' --alert-when '>= 100' --interval 30"
+        );
+
+        let mut page = sample_task(TaskType::Static);
+        page.selector = "div.price".to_string();
+        page.ignore_patterns = vec!["it's \\d+".to_string()];
+        page.render_js = true;
+        assert!(page.cli_command().contains(r#"--ignore 'it'\''s \d+'"#), "{}", page.cli_command());
+        assert!(page.cli_command().ends_with("--render-js"));
+
+        api.cookies = Some("session=s3cr3t".to_string());
+        page.cookies = api.cookies.clone();
+        for command in [api.cli_command(), page.cli_command()] {
+            assert!(command.contains(r#"--cookies "$MONITOR_COOKIES""#), "{}", command);
+            assert!(!command.contains("s3cr3t"), "{}", command);
+        }
+
+        let mut wallet = sample_task(TaskType::Hyperliquid);
+        wallet.monitor_contract = false;
+        wallet.stream_fills = true;
+        assert!(wallet.cli_command().contains("--spot true --contract false"), "{}", wallet.cli_command());
+        assert!(wallet.cli_command().ends_with("--stream"));
+    }
+
+    #[test]
+    fn streams_leave_perpetual_trades_to_position_polling() {
+        let mut wallet = sample_task(TaskType::Hyperliquid);
+        wallet.stream_fills = true;
+        let stream = wallet.build_stream_monitor().unwrap();
+        assert_eq!(stream.id(), HyperliquidFillStream::new(&wallet.address, true, false).id());
+
+        wallet.monitor_spot = false;
+        assert!(wallet.build_stream_monitor().is_none());
+    }
+}
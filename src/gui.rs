@@ -1,40 +1,62 @@
 use eframe::{egui, Frame, CreationContext};
 use egui::{Color32, RichText, Ui, Vec2};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 use tokio::task::JoinHandle;
 use anyhow::Result;
 use dotenv::dotenv;
-use log::{debug, info};
+use log::{debug, info, Level};
 use std::collections::VecDeque;
-use std::fs;
-use std::path::Path;
-use std::collections::HashMap;
 use std::fmt;
 
-mod monitors;
-mod notifiers;
-mod utils;
+use hyperliquid_monitor::{config, monitors, notifiers, utils};
+mod preview;
 
-use monitors::{
-    static_monitor::StaticMonitor,
-    hyperliquid_monitor::HyperliquidMonitor,
-    api_monitor::ApiMonitor,
-    Monitor, Change
-};
-use notifiers::server_chan::ServerChanNotifier;
-use notifiers::Notifier;
+use config::{parse_patterns, parse_tags, CircuitBreakerConfig, Configs, TaskConfig, TaskType, Theme, WindowGeometry};
+use monitors::{CheckLimiter, CircuitBreaker, Monitor, Change, ChangeKind, CIRCUIT_BREAKER_ID, DEFAULT_USER_AGENT, SLOW_CHECKS_BEFORE_ALERT};
+use monitors::api_monitor::AlertOperator;
+use monitors::fetcher::ResponseCache;
+use monitors::hyperliquid_monitor::{HyperliquidMonitor, DEFAULT_VAULT_CHANGE_PCT};
+use monitors::static_monitor::CompareMode;
+use monitors::stream_monitor::{reconnect_backoff, StreamMonitor};
+use notifiers::server_chan::ServerChanError;
+use notifiers::{build_notifiers, build_task_notifiers, CompositeNotifier, Notifier, NotifierKind};
+use notifiers::history::{HistoryEntry, NotificationHistory, HISTORY_PATH};
+use notifiers::pending::{PendingNotifications, PENDING_PATH};
+use notifiers::queue::{HeldNotifications, Mute, NotificationQueue, NotificationSender, DRAIN_TIMEOUT, QUEUE_CAPACITY};
+use notifiers::{FailureTracker, StaleTracker};
+use utils::{format_timestamp, ActiveSchedule, Schedule, TaskLogger};
+use utils::events::{check_outcome, EventKind, EventLog};
+
+/// Configuration file, shared with the CLI
+const CONFIG_PATH: &str = "config.json";
+
+/// Configuration changes are written at most this often
+const CONFIG_SAVE_DEBOUNCE: Duration = Duration::from_secs(2);
 
 /// Maximum number of log entries
 const MAX_LOGS: usize = 100;
 
+/// Quick-pick intervals in the task form
+const INTERVAL_PRESETS: [(&str, u64); 5] = [("30s", 30), ("1m", 60), ("5m", 300), ("15m", 900), ("1h", 3600)];
+
+/// Choices of the "Mute for" menu, None mutes until unmuted by hand
+const MUTE_PRESETS: [(&str, Option<u64>); 3] = [("15 minutes", Some(15 * 60)), ("1 hour", Some(3600)), ("Until I unmute", None)];
+
+/// Status color of a task whose checks are failing but not yet reported
+const DEGRADED_COLOR: Color32 = Color32::from_rgb(255, 140, 0);
+
 /// Monitoring task status
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TaskStatus {
     Idle,
     Running,
+    /// Running, but recent checks failed and have not reached the failure threshold yet
+    Degraded,
+    /// Running, waiting outside its active schedule
+    Scheduled,
     Error,
 }
 
@@ -43,156 +65,125 @@ impl fmt::Display for TaskStatus {
         match self {
             TaskStatus::Idle => write!(f, "Idle"),
             TaskStatus::Running => write!(f, "Running"),
+            TaskStatus::Degraded => write!(f, "Degraded"),
+            TaskStatus::Scheduled => write!(f, "Scheduled"),
             TaskStatus::Error => write!(f, "Error"),
         }
     }
 }
 
-/// Monitoring task type
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub enum TaskType {
-    Static,
-    Hyperliquid,
-    Api,
-}
-
-impl fmt::Display for TaskType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            TaskType::Static => write!(f, "Static Web"),
-            TaskType::Hyperliquid => write!(f, "Hyperliquid"),
-            TaskType::Api => write!(f, "API Monitor"),
-        }
-    }
-}
-
-impl TaskType {
-    fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "Static Web" => Some(TaskType::Static),
-            "Hyperliquid" => Some(TaskType::Hyperliquid),
-            "API Monitor" => Some(TaskType::Api),
-            _ => None,
-        }
-    }
-}
-
-/// Monitoring task configuration
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct TaskConfig {
-    /// Task name
-    pub name: String,
-    /// Task type
-    pub task_type: String,
-    /// Target URL to monitor
-    pub url: String,
-    /// Selector (for static web page monitoring and API)
-    pub selector: String,
-    /// Wallet address (for Hyperliquid monitoring)
-    pub address: String,
-    /// Whether to monitor spot trading (for Hyperliquid monitoring)
-    pub monitor_spot: bool,
-    /// Whether to monitor contract trading (for Hyperliquid monitoring)
-    pub monitor_contract: bool,
-    /// Monitoring interval (seconds)
-    pub interval_secs: u64,
-    /// Whether it's enabled
-    pub enabled: bool,
-    /// Notes for the task
-    pub notes: String,
-}
-
-impl Default for TaskConfig {
-    fn default() -> Self {
-        Self {
-            name: "New Task".to_string(),
-            task_type: "Static Web".to_string(),
-            url: "https://example.com".to_string(),
-            selector: "".to_string(),
-            address: "".to_string(),
-            monitor_spot: true,
-            monitor_contract: false,
-            interval_secs: 60,
-            enabled: true,
-            notes: String::new(),
-        }
-    }
-}
-
-/// Notification configuration
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct NotificationConfig {
-    /// Whether to enable ServerChan notifications
-    pub enabled: bool,
-    /// ServerChan keys
-    pub server_chan_keys: Vec<String>,
-}
-
-impl Default for NotificationConfig {
-    fn default() -> Self {
-        Self {
-            enabled: false,
-            server_chan_keys: vec![String::new()],
-        }
-    }
-}
-
-/// Application configuration
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct Configs {
-    /// Notification configuration
-    pub notification: NotificationConfig,
-    /// Monitoring task list
-    pub tasks: Vec<TaskConfig>,
-}
-
-impl Default for Configs {
-    fn default() -> Self {
-        Self {
-            notification: NotificationConfig::default(),
-            tasks: Vec::new(),
-        }
-    }
-}
-
 /// Monitoring application state
 struct MonitorApp {
     /// Application configuration
     configs: Configs,
     /// Task configuration being edited
     editing_task: TaskConfig,
+    /// Tags of the edited task as typed, comma-separated
+    editing_tags: String,
+    /// Ignore regexes of the edited task as typed, one per line
+    editing_ignore_patterns: String,
+    /// Require regexes of the edited task as typed, one per line
+    editing_require_patterns: String,
+    /// Active schedule of the edited task as typed, e.g. `mon-fri 09:30-16:00`
+    editing_schedule: String,
+    /// Task list filter, text or `#tag`
+    task_filter: String,
     /// Whether to show the add task dialog
     show_add_task_dialog: bool,
     /// Whether to show the edit task dialog
     show_edit_task_dialog: bool,
+    /// Shared settings of the tasks created by the bulk add dialog, None while it is closed
+    bulk_task: Option<TaskConfig>,
+    /// URLs or addresses typed in the bulk add dialog, one per line
+    bulk_lines: String,
+    /// Change whose details are shown in a popup, with its log line
+    viewing_change: Option<(String, Change)>,
+    /// Notification history loaded for the history window, None while it is closed
+    history: Option<Vec<HistoryEntry>>,
+    /// Task the history window shows, empty for all tasks
+    history_task: String,
     /// Current editing task index
     editing_task_index: Option<usize>,
+    /// Show secrets in plain text instead of masking them
+    show_secrets: bool,
+    /// ServerChan tags as typed, `|`-separated
+    tags_input: String,
+    /// Task waiting for delete confirmation
+    pending_delete: Option<usize>,
+    /// Most recently deleted task, for undo
+    last_deleted: Option<TaskConfig>,
     /// Current task statuses
     task_statuses: Vec<TaskStatus>,
+    /// Changes detected per task since the user last acknowledged them
+    unread_changes: Vec<u32>,
+    /// Connectivity check result from the last start of each task
+    reachability: Vec<Option<Result<(), String>>>,
+    /// Time taken by the last response of each task, for monitors that measure it
+    response_times: Vec<Option<Duration>>,
+    /// Signals waking each running task for an immediate check
+    check_signals: Vec<Arc<Notify>>,
+    /// Slots shared by the running tasks' checks, resized in place when the limit changes so running tasks keep them
+    check_limiter: CheckLimiter,
+    /// Pauses every task after too many failures across tasks
+    circuit_breaker: CircuitBreaker,
+    /// Responses shared by tasks requesting the same URL, None when caching is off
+    response_cache: Option<ResponseCache>,
+    /// Structured log of task events, None when the configuration sets no path
+    event_log: Option<EventLog>,
+    /// Result of the last selector test in the task form
+    selector_test: Option<Result<String, String>>,
+    /// Whether a selector test is in flight
+    selector_test_running: bool,
+    /// Response body preview in the task form
+    response_preview: Option<Result<String, String>>,
+    /// Whether a response preview is in flight
+    response_preview_running: bool,
+    /// Why fetching the current content as the baseline failed
+    baseline_error: Option<String>,
+    /// Whether the current content is being fetched as the baseline
+    baseline_running: bool,
     /// Runtime
     runtime: Runtime,
     /// Task handles
     task_handles: Vec<Option<JoinHandle<()>>>,
     /// Log records
-    logs: VecDeque<(String, Color32)>,
+    logs: VecDeque<LogEntry>,
     /// Notification service
-    notifier: Option<Arc<ServerChanNotifier>>,
+    notifier: Option<Arc<dyn Notifier>>,
     /// Configuration file path
     config_path: String,
-    /// Task states
-    task_states: HashMap<String, TaskStatus>,
-    /// Notification sender
-    notification_sender: Option<tokio::sync::mpsc::UnboundedSender<(String, String)>>,
-    /// Notification handle
+    /// Why the configuration file could not be loaded, shown until the app restarts
+    config_warning: Option<String>,
+    /// When the configuration first changed since it was last written, None when saved
+    config_dirty_since: Option<Instant>,
+    /// Sender handed to monitoring tasks for reporting back to the UI
+    message_sender: mpsc::UnboundedSender<Message>,
+    /// Receiver drained by the UI on every frame
+    message_receiver: mpsc::UnboundedReceiver<Message>,
+    /// Queue the monitoring tasks push notifications into
+    notification_sender: NotificationQueue,
+    /// Worker sending queued notifications
     notification_handle: Option<JoinHandle<()>>,
-    /// Selected task
-    selected_task: Option<String>,
-    /// New task
-    new_task: TaskConfig,
-    /// Edit task
-    edit_task: TaskConfig,
-    /// Notifier logs
-    notifier_logs: Vec<String>,
+}
+
+/// Line in the log panel
+struct LogEntry {
+    /// Timestamped text
+    text: String,
+    /// Text color
+    color: Color32,
+    /// Change the line reports, for copying its details
+    change: Option<Change>,
+}
+
+impl LogEntry {
+    /// Text copied by the line's copy button, with the full details of a change
+    fn copy_text(&self) -> String {
+        match &self.change {
+            Some(change) => format!("{}\n\n{}", self.text, change.details),
+            None => self.text.clone(),
+        }
+    }
 }
 
 /// Message type
@@ -200,99 +191,227 @@ enum Message {
     Log(String, Color32),
     TaskStatusChanged(usize, TaskStatus),
     ChangeDetected(usize, Change),
+    /// Result of the connectivity check run when a task starts
+    Reachability(usize, Result<(), String>),
+    /// Time taken by the last response of a task
+    ResponseTimed(usize, Duration),
+    /// Result of testing the selector in the task form
+    SelectorTested(Result<String, String>),
+    /// Response body fetched for the preview in the task form
+    ResponsePreviewed(Result<String, String>),
+    /// Content fetched to become the baseline of the task in the form
+    BaselineFetched(Result<String, String>),
+    /// Current state of a Hyperliquid wallet, fetched on request
+    SnapshotTaken(usize, Result<Change, String>),
 }
 
 impl MonitorApp {
     /// Create a new monitoring application
-    fn new(_cc: &CreationContext) -> Self {
+    fn new(cc: &CreationContext) -> Self {
         // Initialize environment variables
         dotenv().ok();
         
         // Configuration file path
-        let config_path = "config.json".to_string();
+        let config_path = CONFIG_PATH.to_string();
         
-        // Try to load saved configuration
-        let config = Self::load_config(&config_path).unwrap_or_default();
+        // Try to load saved configuration, keeping a copy of a file that can't be read instead of silently replacing it
+        let (config, config_warning) = match Configs::load(&config_path) {
+            Ok(config) => (config, None),
+            Err(_) if !std::path::Path::new(&config_path).exists() => (Configs::default(), None),
+            Err(e) => {
+                let warning = match Configs::back_up(&config_path) {
+                    Ok(backup) => format!("{}. Starting with an empty configuration, the old file was copied to {}", e, backup.display()),
+                    Err(backup_error) => format!("{}. Starting with an empty configuration, backing up the old file failed: {}", e, backup_error),
+                };
+                log::error!("{}", warning);
+                (Configs::default(), Some(warning))
+            }
+        };
+        utils::set_display_timezone(config.display_timezone().unwrap_or_else(|e| {
+            log::error!("{}, using the local timezone", e);
+            None
+        }));
         
         // Create Tokio runtime
         let runtime = Runtime::new().expect("Failed to create Tokio runtime");
         
         // Initialize state
         let task_statuses = vec![TaskStatus::Idle; config.tasks.len()];
+        let check_limiter = CheckLimiter::new(config.max_concurrent_checks);
+        let response_cache = config.response_cache();
+        let circuit_breaker = config.circuit_breaker();
+        let event_log = config.event_log();
+        let unread_changes = vec![0; config.tasks.len()];
+        let reachability = vec![None; config.tasks.len()];
+        let response_times = vec![None; config.tasks.len()];
+        let check_signals = (0..config.tasks.len()).map(|_| Arc::new(Notify::new())).collect();
         let mut task_handles = Vec::with_capacity(config.tasks.len());
         for _ in 0..config.tasks.len() {
             task_handles.push(None);
         }
         
         // Initialize notification service
-        let notifier = if !config.notification.server_chan_keys.is_empty() {
-            Some(Arc::new(ServerChanNotifier::new_with_keys(&config.notification.server_chan_keys)))
-        } else {
-            // Try to load ServerChan key from environment variables
-            match std::env::var("SERVER_CHAN_KEY") {
-                Ok(key) if !key.is_empty() => Some(Arc::new(ServerChanNotifier::new_with_keys(&vec![key]))),
-                _ => None,
-            }
+        let notifier = combined_notifier(build_notifiers(&config.notification));
+        
+        // Channel for monitoring tasks to report back to the UI
+        let (message_sender, message_receiver) = mpsc::unbounded_channel();
+        
+        // Notifications are sent in the background so a slow notifier never delays a check
+        let notification_sender = NotificationQueue::new(QUEUE_CAPACITY);
+        notification_sender.set_digest_interval(config.notification.digest_interval());
+        notification_sender.set_history(Some(NotificationHistory::new(HISTORY_PATH)));
+        notification_sender.set_pending(Some(PendingNotifications::new(PENDING_PATH)));
+        notification_sender.set_title_prefixes(config.notification.title_prefixes.clone());
+        notification_sender.set_mute_critical(config.notification.mute_critical);
+        let failure_tx = message_sender.clone();
+        let notification_handle = {
+            let _guard = runtime.enter();
+            notification_sender.spawn(move |notification, e| {
+                let message = match e.downcast_ref::<ServerChanError>() {
+                    Some(ServerChanError::InvalidKey(_)) => format!("Notification not delivered, check your ServerChan key: {}", e),
+                    _ => format!("Failed to send notification \"{}\": {}", notification.title, e),
+                };
+                let _ = failure_tx.send(Message::Log(notification.logger.log(Level::Error, &message), Color32::RED));
+            })
         };
         
         let mut app = Self {
+            tags_input: config.notification.tags.as_ref().map(|tags| tags.join("|")).unwrap_or_default(),
             configs: config,
             editing_task: TaskConfig::default(),
+            editing_tags: String::new(),
+            editing_ignore_patterns: String::new(),
+            editing_schedule: String::new(),
+            editing_require_patterns: String::new(),
+            task_filter: String::new(),
             show_add_task_dialog: false,
             show_edit_task_dialog: false,
+            bulk_task: None,
+            bulk_lines: String::new(),
+            viewing_change: None,
+            history: None,
+            history_task: String::new(),
             editing_task_index: None,
+            pending_delete: None,
+            last_deleted: None,
+            show_secrets: false,
             task_statuses,
+            unread_changes,
+            reachability,
+            response_times,
+            check_signals,
+            check_limiter,
+            response_cache,
+            circuit_breaker,
+            event_log,
+            selector_test: None,
+            selector_test_running: false,
+            response_preview: None,
+            response_preview_running: false,
+            baseline_error: None,
+            baseline_running: false,
             runtime,
             task_handles,
             logs: VecDeque::with_capacity(MAX_LOGS),
             notifier,
             config_path,
-            task_states: HashMap::new(),
-            notification_sender: None,
-            notification_handle: None,
-            selected_task: None,
-            new_task: TaskConfig::default(),
-            edit_task: TaskConfig::default(),
-            notifier_logs: Vec::new(),
+            config_warning,
+            config_dirty_since: None,
+            message_sender,
+            message_receiver,
+            notification_sender,
+            notification_handle: Some(notification_handle),
         };
         
+        // Apply the saved theme before the first frame
+        app.apply_theme(&cc.egui_ctx, cc.integration_info.system_theme);
+        
         // Add welcome logs
         app.add_log("Hyperliquid Monitoring System Started", Color32::GREEN);
         app.add_log("Version: 0.1.0", Color32::WHITE);
+        if let Some(warning) = app.config_warning.clone() {
+            app.add_log(&warning, Color32::RED);
+        }
         
         app
     }
     
-    /// Load configuration
-    fn load_config(path: &str) -> Result<Configs> {
-        let config_file = Path::new(path);
-        if config_file.exists() {
-            let config_str = fs::read_to_string(config_file)?;
-            let config: Configs = serde_json::from_str(&config_str)?;
-            
-            Ok(config)
-        } else {
-            Err(anyhow::anyhow!("Configuration file does not exist"))
+    /// Save configuration
+    fn save_config(&mut self) -> Result<()> {
+        self.config_dirty_since = None;
+        self.configs.save(&self.config_path)
+    }
+    
+    /// Note that the configuration changed, it is written by `flush_config` once changes settle
+    fn mark_config_dirty(&mut self) {
+        self.config_dirty_since.get_or_insert_with(Instant::now);
+    }
+    
+    /// Write pending configuration changes once they are `CONFIG_SAVE_DEBOUNCE` old
+    fn flush_config(&mut self) {
+        if self.config_dirty_since.is_none_or(|since| since.elapsed() < CONFIG_SAVE_DEBOUNCE) {
+            return;
+        }
+        
+        match self.save_config() {
+            Ok(()) => self.add_log("Configuration saved", Color32::GRAY),
+            Err(e) => self.add_log(&format!("Failed to save configuration: {}", e), Color32::RED),
         }
     }
     
-    /// Save configuration
-    fn save_config(&self) -> Result<()> {
-        let config_str = serde_json::to_string_pretty(&self.configs)?;
-        fs::write(&self.config_path, config_str)?;
-        Ok(())
+    /// Apply the configured theme, resolving System with the OS theme when known
+    fn apply_theme(&self, ctx: &egui::Context, system_theme: Option<eframe::Theme>) {
+        let dark = match self.configs.theme {
+            Theme::Light => false,
+            Theme::Dark => true,
+            Theme::System => system_theme != Some(eframe::Theme::Light),
+        };
+        
+        ctx.set_visuals(if dark { egui::Visuals::dark() } else { egui::Visuals::light() });
+    }
+    
+    /// Record the current window geometry, keeping the restored size while maximized
+    fn track_window(&mut self, info: &eframe::WindowInfo) {
+        if info.minimized || info.fullscreen {
+            return;
+        }
+        
+        if info.maximized {
+            if let Some(window) = &mut self.configs.window {
+                window.maximized = true;
+            }
+            return;
+        }
+        
+        self.configs.window = Some(WindowGeometry {
+            width: info.size.x,
+            height: info.size.y,
+            x: info.position.map(|pos| pos.x),
+            y: info.position.map(|pos| pos.y),
+            maximized: false,
+        });
     }
     
     /// Add log
     fn add_log(&mut self, message: &str, color: Color32) {
+        self.push_log(message, color, None);
+    }
+    
+    /// Add a log line for a detected change, keeping the change for its details
+    fn add_change_log(&mut self, message: &str, change: Change) {
+        self.push_log(message, Color32::GOLD, Some(change));
+    }
+    
+    /// Append a timestamped log line, dropping the oldest past the limit
+    fn push_log(&mut self, message: &str, color: Color32, change: Option<Change>) {
         let timestamp = chrono::Local::now().format("[%H:%M:%S]").to_string();
-        let log_message = format!("{} {}", timestamp, message);
+        let text = format!("{} {}", timestamp, message);
         
         if self.logs.len() >= MAX_LOGS {
             self.logs.pop_front();
         }
         
-        self.logs.push_back((log_message, color));
+        self.logs.push_back(LogEntry { text, color, change });
     }
     
     /// Start monitoring task
@@ -309,93 +428,211 @@ impl MonitorApp {
         
         let task_config = self.configs.tasks[task_index].clone();
         
+        // Create monitor based on task type, with the push stream run alongside it if any
+        let monitor = match task_config.build_monitor_with_cache(self.response_cache.as_ref()) {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                self.task_statuses[task_index] = TaskStatus::Error;
+                self.add_log(&format!("Task #{} '{}' not started: {}", task_index + 1, task_config.name, e), Color32::RED);
+                return;
+            }
+        };
+        let stream = task_config.build_stream_monitor();
+        
         self.task_statuses[task_index] = TaskStatus::Running;
+        self.reachability[task_index] = None;
+        self.response_times[task_index] = None;
         
-        // Create notification service
-        let notifier = self.notifier.clone();
+        // Fresh signal, so a click from before the restart doesn't skip the first sleep
+        self.check_signals[task_index] = Arc::new(Notify::new());
+        let check_now = self.check_signals[task_index].clone();
         
-        // Create channel for sending messages
-        let (tx, mut rx) = mpsc::channel::<Message>(32);
-        let tx_clone = tx.clone();
+        // Create notification service from the task's own key or notifier selection, else share the global one
+        let own_key = task_config.server_chan_key.as_deref().is_some_and(|key| !key.trim().is_empty());
+        let notifier = if own_key || (!task_config.notifiers.is_empty() && self.notifier.is_some()) {
+            combined_notifier(build_task_notifiers(&self.configs.notification, &task_config))
+        } else {
+            self.notifier.clone()
+        };
+        
+        // Sender for reporting back to the UI
+        let tx = self.message_sender.clone();
         
         // Record task name for later use
         let task_name = task_config.name.clone();
         
-        // Create monitor based on task type
-        let monitor: Box<dyn Monitor> = match task_config.task_type.as_str() {
-            "Static Web" => {
-                Box::new(StaticMonitor::new_with_notes(
-                    &task_config.url,
-                    &task_config.selector,
-                    task_config.interval_secs,
-                    &task_config.notes,
-                ))
-            }
-            "API Monitor" => {
-                Box::new(ApiMonitor::new_with_notes(
-                    task_config.url,
-                    task_config.selector,
-                    task_config.interval_secs,
-                    &task_config.notes,
-                ))
-            }
-            "Hyperliquid" => {
-                Box::new(HyperliquidMonitor::new_with_notes(
-                    &task_config.address,
-                    task_config.interval_secs,
-                    task_config.monitor_spot,
-                    task_config.monitor_contract,
-                    &task_config.notes,
-                ))
-            }
-            _ => {
-                self.add_log(&format!("Unknown task type: {}", task_config.task_type), Color32::RED);
-                return;
-            }
+        // Zero means run forever, same as no limit
+        let max_runtime = task_config.max_runtime_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        
+        let options = RunOptions {
+            max_runtime,
+            quiet_hours: self.configs.notification.quiet_hours,
+            failure_threshold: task_config.failure_threshold,
+            stale_after: task_config.stale_after_secs.map(Duration::from_secs),
+            schedule: Schedule::new(task_config.aligned, self.configs.jitter_percent),
+            notify_on_start: task_config.announces_start(&self.configs.notification),
+            limiter: self.check_limiter.clone(),
+            active_schedule: task_config.active_schedule.clone(),
+            breaker: self.circuit_breaker.clone(),
+            breaker_notifications: self.notifier.clone()
+                .map(|notifier| self.notification_sender.sender(CIRCUIT_BREAKER_ID, notifier, TaskLogger::new("Circuit breaker"))),
         };
+        let mut logger = TaskLogger::new(&task_name);
+        logger.set_events(self.event_log.clone(), &monitor.id());
         
-        // Create monitoring task
+        let notifications = notifier.map(|notifier| self.notification_sender.sender(&monitor.id(), notifier, logger.clone()));
+        
+        // Create monitoring task, the stream stops with it
         let handle = self.runtime.spawn(async move {
-            run_monitor_task(task_index, monitor, notifier, tx).await;
+            let streaming = {
+                let (notifications, options, logger, tx) = (notifications.clone(), options.clone(), logger.clone(), tx.clone());
+                async move {
+                    match stream {
+                        Some(stream) => run_stream_task(task_index, stream, notifications, options, logger, tx).await,
+                        None => std::future::pending().await,
+                    }
+                }
+            };
+            tokio::select! {
+                _ = run_monitor_task(task_index, monitor, notifications, options, check_now, logger, tx) => {}
+                _ = streaming => {}
+            }
         });
         
         self.task_handles[task_index] = Some(handle);
         
         // Add log - Using the previously saved task_name instead of the moved task_config
         self.add_log(&format!("Started task #{}: {}", task_index + 1, task_name), Color32::GREEN);
+
+    }
+    
+    /// Run a single ad-hoc check of a stopped task and log the result
+    fn check_once(&mut self, task_index: usize) {
+        let Some(task_config) = self.configs.tasks.get(task_index) else {
+            return;
+        };
+        
+        let mut monitor = match task_config.build_monitor() {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                self.add_log(&format!("Task #{} '{}' not checked: {}", task_index + 1, task_config.name, e), Color32::RED);
+                return;
+            }
+        };
+        let logger = TaskLogger::new(&task_config.name);
+        let tx = self.message_sender.clone();
+        self.add_log(&format!("Checking task #{} once: {}", task_index + 1, task_config.name), Color32::LIGHT_BLUE);
         
-        // Create message receiving task
         self.runtime.spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                match msg {
-                    Message::Log(message, _color) => {
-                        // Handle log message
-                        debug!("{}", message);
-                        // Cannot directly modify application state, send back to main thread
-                    },
-                    Message::TaskStatusChanged(idx, status) => {
-                        // Handle task status change
-                        let _ = tx_clone.send(Message::Log(
-                            format!("Task #{} status changed to: {:?}", idx + 1, status),
-                            match status {
-                                TaskStatus::Running => Color32::GREEN,
-                                TaskStatus::Idle => Color32::YELLOW,
-                                TaskStatus::Error => Color32::RED,
-                            }
-                        )).await;
-                    },
-                    Message::ChangeDetected(idx, change) => {
-                        // Handle detected change
-                        let _ = tx_clone.send(Message::Log(
-                            format!("Task #{} detected change: {}", idx + 1, change.message),
-                            Color32::GOLD
-                        )).await;
-                    },
+            match monitor.check().await {
+                Ok(Some(change)) if change.kind == ChangeKind::Error => {
+                    send_log(&tx, &logger, Level::Error, &format!("Check failed: {}\n{}", change.message, change.details), Color32::RED);
                 }
+                Ok(Some(change)) => {
+                    send_log(&tx, &logger, Level::Info, &format!("Check result: {}\n{}", change.message, change.details), Color32::LIGHT_GREEN);
+                }
+                Ok(None) => send_log(&tx, &logger, Level::Info, "Check result: no content reported", Color32::GRAY),
+                Err(e) => send_log(&tx, &logger, Level::Error, &format!("Check failed: {}", e), Color32::RED),
+            }
+        });
+    }
+    
+    /// Fetch the current state of a Hyperliquid task's wallet and show it in the change details window
+    fn take_snapshot(&mut self, task_index: usize) {
+        let Some(task_config) = self.configs.tasks.get(task_index) else {
+            return;
+        };
+        
+        let monitor = HyperliquidMonitor::builder(&task_config.address).notes(&task_config.notes).build();
+        let monitor = match monitor {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                self.add_log(&format!("Snapshot of task #{} failed: {}", task_index + 1, e), Color32::RED);
+                return;
             }
+        };
+        let tx = self.message_sender.clone();
+        self.add_log(&format!("Taking a snapshot of task #{}: {}", task_index + 1, task_config.name), Color32::LIGHT_BLUE);
+        
+        self.runtime.spawn(async move {
+            let result = monitor.snapshot().await.map_err(|e| e.to_string());
+            let _ = tx.send(Message::SnapshotTaken(task_index, result));
         });
     }
     
+    /// Handle messages reported by monitoring tasks
+    fn process_messages(&mut self) {
+        while let Ok(msg) = self.message_receiver.try_recv() {
+            match msg {
+                Message::Log(message, color) => {
+                    self.add_log(&message, color);
+                },
+                Message::TaskStatusChanged(idx, status) => {
+                    if idx >= self.task_statuses.len() {
+                        continue;
+                    }
+                    
+                    self.task_statuses[idx] = status;
+                    if status == TaskStatus::Idle {
+                        // The task finished on its own
+                        self.task_handles[idx] = None;
+                    }
+                    
+                    self.add_log(
+                        &format!("Task #{} status changed to: {:?}", idx + 1, status),
+                        match status {
+                            TaskStatus::Running => Color32::GREEN,
+                            TaskStatus::Idle => Color32::YELLOW,
+                            TaskStatus::Degraded => DEGRADED_COLOR,
+                            TaskStatus::Scheduled => Color32::LIGHT_BLUE,
+                            TaskStatus::Error => Color32::RED,
+                        }
+                    );
+                },
+                Message::SelectorTested(result) => {
+                    self.selector_test = Some(result);
+                    self.selector_test_running = false;
+                },
+                Message::ResponsePreviewed(result) => {
+                    self.response_preview = Some(result);
+                    self.response_preview_running = false;
+                },
+                Message::BaselineFetched(result) => {
+                    match result {
+                        Ok(content) => {
+                            self.editing_task.baseline = Some(content);
+                            self.baseline_error = None;
+                        }
+                        Err(e) => self.baseline_error = Some(e),
+                    }
+                    self.baseline_running = false;
+                },
+                Message::Reachability(idx, result) => {
+                    if let Some(reachability) = self.reachability.get_mut(idx) {
+                        *reachability = Some(result);
+                    }
+                },
+                Message::ResponseTimed(idx, elapsed) => {
+                    if let Some(response_time) = self.response_times.get_mut(idx) {
+                        *response_time = Some(elapsed);
+                    }
+                },
+                Message::SnapshotTaken(idx, result) => match result {
+                    Ok(change) => self.viewing_change = Some((change.message.clone(), change)),
+                    Err(e) => self.add_log(&format!("Task #{} snapshot failed: {}", idx + 1, e), Color32::RED),
+                },
+                Message::ChangeDetected(idx, change) => {
+                    if let Some(unread) = self.unread_changes.get_mut(idx) {
+                        *unread += 1;
+                    }
+                    self.add_change_log(&format!("Task #{} detected change: {}", idx + 1, change.message), change);
+                },
+            }
+        }
+    }
+    
     /// Stop monitoring task
     fn stop_task(&mut self, task_index: usize) {
         if task_index >= self.configs.tasks.len() {
@@ -421,23 +658,29 @@ impl MonitorApp {
     
     /// Add new task
     fn add_task(&mut self) {
-        self.configs.tasks.push(self.editing_task.clone());
-        self.task_statuses.push(TaskStatus::Idle);
-        self.task_handles.push(None);
-        
-        // Add log
-        self.add_log(&format!("Added new task: {}", self.editing_task.name), Color32::LIGHT_BLUE);
-        
-        // Save configuration
-        if let Err(e) = self.save_config() {
-            self.add_log(&format!("Failed to save configuration: {}", e), Color32::RED);
-        }
+        self.push_task(self.editing_task.clone());
         
         // Reset edit state
         self.editing_task = TaskConfig::default();
         self.show_add_task_dialog = false;
     }
     
+    /// Append a task with its UI state and save the configuration
+    fn push_task(&mut self, task: TaskConfig) {
+        self.add_log(&format!("Added new task: {}", task.name), Color32::LIGHT_BLUE);
+        
+        self.configs.tasks.push(task);
+        self.task_statuses.push(TaskStatus::Idle);
+        self.unread_changes.push(0);
+        self.reachability.push(None);
+        self.response_times.push(None);
+        self.check_signals.push(Arc::new(Notify::new()));
+        self.task_handles.push(None);
+        
+        // Save configuration
+        self.mark_config_dirty();
+    }
+    
     /// Update task
     fn update_task(&mut self) {
         if let Some(_idx) = self.editing_task_index {
@@ -452,9 +695,7 @@ impl MonitorApp {
                 self.add_log(&format!("Updated task #{}: {}", _idx + 1, self.editing_task.name), Color32::LIGHT_BLUE);
                 
                 // Save configuration
-                if let Err(e) = self.save_config() {
-                    self.add_log(&format!("Failed to save configuration: {}", e), Color32::RED);
-                }
+                self.mark_config_dirty();
             }
         }
         
@@ -464,9 +705,31 @@ impl MonitorApp {
         self.editing_task_index = None;
     }
     
+    /// Restore the most recently deleted task at the end of the list, so running tasks keep the index
+    /// their status updates are sent with
+    fn undo_delete(&mut self) {
+        let Some(task) = self.last_deleted.take() else {
+            return;
+        };
+        
+        self.add_log(&format!("Restored task: {}", task.name), Color32::LIGHT_BLUE);
+        self.configs.tasks.push(task);
+        self.task_statuses.push(TaskStatus::Idle);
+        self.unread_changes.push(0);
+        self.reachability.push(None);
+        self.response_times.push(None);
+        self.check_signals.push(Arc::new(Notify::new()));
+        self.task_handles.push(None);
+        
+        self.mark_config_dirty();
+    }
+    
     /// Delete task
     fn delete_task(&mut self, task_index: usize) -> bool {
         if task_index < self.configs.tasks.len() {
+            // Keep a copy for undo
+            self.last_deleted = Some(self.configs.tasks[task_index].clone());
+            
             // If task is running, stop it first
             self.stop_task(task_index);
             
@@ -476,12 +739,14 @@ impl MonitorApp {
             // Delete task
             self.configs.tasks.remove(task_index);
             self.task_statuses.remove(task_index);
+            self.unread_changes.remove(task_index);
+            self.reachability.remove(task_index);
+            self.response_times.remove(task_index);
+            self.check_signals.remove(task_index);
             self.task_handles.remove(task_index);
             
             // Save configuration
-            if let Err(e) = self.save_config() {
-                self.add_log(&format!("Failed to save configuration: {}", e), Color32::RED);
-            }
+            self.mark_config_dirty();
             
             return true;
         }
@@ -491,19 +756,11 @@ impl MonitorApp {
     /// Update notification settings
     fn update_notification_config(&mut self) {
         if self.configs.notification.enabled {
-            // Filter out empty keys
-            let valid_keys: Vec<String> = self.configs.notification.server_chan_keys
-                .iter()
-                .filter(|key| !key.trim().is_empty())
-                .cloned()
-                .collect();
-                
-            if !valid_keys.is_empty() {
-                self.notifier = Some(Arc::new(ServerChanNotifier::new_with_keys(&valid_keys)));
-                debug!("Notification service initialized with {} keys", valid_keys.len());
+            self.notifier = combined_notifier(build_notifiers(&self.configs.notification));
+            if self.notifier.is_some() {
+                debug!("Notification service initialized");
             } else {
-                self.notifier = None;
-                debug!("No valid ServerChan keys found, notification service disabled");
+                debug!("No notifiers configured, notification service disabled");
             }
         } else {
             self.notifier = None;
@@ -511,9 +768,7 @@ impl MonitorApp {
         }
         
         // Save configuration
-        if let Err(e) = self.save_config() {
-            self.add_log(&format!("Failed to save configuration: {}", e), Color32::RED);
-        }
+        self.mark_config_dirty();
     }
     
     /// Draw main UI
@@ -522,9 +777,75 @@ impl MonitorApp {
         ui.horizontal(|ui| {
             let add_btn = ui.add_sized([120.0, 30.0], egui::Button::new("Add Task"));
             if add_btn.clicked() {
-                self.editing_task = TaskConfig::default();
+                self.editing_task = self.configs.new_task();
+                self.editing_tags = String::new();
+                self.editing_ignore_patterns = String::new();
+                self.editing_schedule = String::new();
+                self.editing_require_patterns = String::new();
                 self.editing_task_index = None;
                 self.show_add_task_dialog = true;
+                self.selector_test = None;
+                self.response_preview = None;
+                self.baseline_error = None;
+            }
+            
+            ui.add_space(10.0);
+            
+            let bulk_btn = ui.add_sized([120.0, 30.0], egui::Button::new("Bulk Add"))
+                .on_hover_text("Create one task per URL or address");
+            if bulk_btn.clicked() {
+                self.bulk_task = Some(TaskConfig {
+                    name: "{line}".to_string(),
+                    notes: "{line}".to_string(),
+                    ..self.configs.new_task()
+                });
+                self.bulk_lines = String::new();
+            }
+            
+            ui.add_space(10.0);
+            
+            if let Some(task) = &self.last_deleted {
+                let undo_btn = ui.add_sized([120.0, 30.0], egui::Button::new("Undo Delete"))
+                    .on_hover_text(format!("Restore '{}'", task.name));
+                if undo_btn.clicked() {
+                    self.undo_delete();
+                }
+                
+                ui.add_space(10.0);
+            }
+            
+            // Mute every notification for a while without stopping tasks
+            if self.notification_sender.mute().is_some() {
+                if ui.add_sized([120.0, 30.0], egui::Button::new("🔔 Unmute")).clicked() {
+                    self.notification_sender.set_mute(None);
+                    self.add_log("Notifications unmuted", Color32::GREEN);
+                }
+            } else {
+                let mut selected = None;
+                egui::ComboBox::from_id_source("mute_for")
+                    .selected_text("🔇 Mute for…")
+                    .show_ui(ui, |ui| {
+                        for (label, secs) in MUTE_PRESETS {
+                            if ui.selectable_label(false, label).clicked() {
+                                selected = Some((label, secs));
+                            }
+                        }
+                    });
+                if let Some((label, secs)) = selected {
+                    let mute = match secs {
+                        Some(secs) => Mute::Until(tokio::time::Instant::now() + Duration::from_secs(secs)),
+                        None => Mute::Indefinitely,
+                    };
+                    self.notification_sender.set_mute(Some(mute));
+                    self.add_log(&format!("Notifications muted: {}", label.to_lowercase()), Color32::YELLOW);
+                }
+            }
+            if ui.checkbox(&mut self.configs.notification.mute_critical, "Mute errors too")
+                .on_hover_text("Off lets errors and outages through while muted")
+                .changed()
+            {
+                self.notification_sender.set_mute_critical(self.configs.notification.mute_critical);
+                self.mark_config_dirty();
             }
             
             ui.add_space(10.0);
@@ -537,6 +858,101 @@ impl MonitorApp {
                     self.add_log("Configuration saved", Color32::GREEN);
                 }
             }
+            
+            ui.add_space(10.0);
+            
+            let previous_theme = self.configs.theme;
+            egui::ComboBox::from_label("Theme")
+                .selected_text(self.configs.theme.to_string())
+                .show_ui(ui, |ui| {
+                    for theme in [Theme::System, Theme::Light, Theme::Dark] {
+                        ui.selectable_value(&mut self.configs.theme, theme, theme.to_string());
+                    }
+                });
+            if self.configs.theme != previous_theme {
+                self.mark_config_dirty();
+            }
+            
+            ui.add_space(10.0);
+            
+            ui.label("Default interval:");
+            let interval_response = ui.add(egui::DragValue::new(&mut self.configs.default_interval_secs)
+                .clamp_range(1..=3600)
+                .suffix(" sec"));
+            
+            ui.label("Jitter:").on_hover_text("Randomize every poll interval so tasks don't fire together");
+            let jitter_response = ui.add(egui::DragValue::new(&mut self.configs.jitter_percent)
+                .clamp_range(0..=50)
+                .prefix("±")
+                .suffix("%"));
+            
+            ui.label("Concurrent checks:")
+                .on_hover_text("Most checks running at the same time, the others wait for a free slot");
+            let concurrency_response = ui.add(egui::DragValue::new(&mut self.configs.max_concurrent_checks)
+                .clamp_range(1..=256));
+            if concurrency_response.changed() {
+                self.check_limiter.resize(self.configs.max_concurrent_checks);
+            }
+            
+            ui.label("Response cache:")
+                .on_hover_text("Reuse a response for this long across tasks requesting the same URL, 0 to fetch for every task; applies to tasks started afterwards");
+            let cache_response = ui.add(egui::DragValue::new(&mut self.configs.response_cache_secs)
+                .clamp_range(0..=3600)
+                .suffix(" sec"));
+            if cache_response.changed() {
+                self.response_cache = self.configs.response_cache();
+            }
+            
+            if interval_response.changed() || jitter_response.changed() || concurrency_response.changed() || cache_response.changed() {
+                self.mark_config_dirty();
+            }
+            
+            ui.label("Timezone:").on_hover_text("IANA name used for times in notifications, empty for the local timezone");
+            let mut timezone = self.configs.timezone.clone().unwrap_or_default();
+            let timezone_response = ui.add(egui::TextEdit::singleline(&mut timezone)
+                .hint_text("Local, e.g. Asia/Shanghai")
+                .desired_width(140.0));
+            if timezone_response.changed() {
+                self.configs.timezone = Some(timezone.trim().to_string()).filter(|name| !name.is_empty());
+                if let Ok(display_timezone) = self.configs.display_timezone() {
+                    utils::set_display_timezone(display_timezone);
+                    self.mark_config_dirty();
+                }
+            }
+            if self.configs.display_timezone().is_err() {
+                ui.label(RichText::new("Unknown timezone").color(Color32::RED));
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            let mut enabled = self.configs.circuit_breaker.is_some();
+            let mut changed = ui.checkbox(&mut enabled, "Circuit breaker")
+                .on_hover_text("Pause every task once too many checks fail across tasks, then resume; applies to tasks started afterwards")
+                .changed();
+            if changed {
+                self.configs.circuit_breaker = enabled.then(CircuitBreakerConfig::default);
+            }
+            if let Some(breaker) = self.configs.circuit_breaker.as_mut() {
+                ui.label("Pause after");
+                changed |= ui.add(egui::DragValue::new(&mut breaker.max_errors)
+                    .clamp_range(1..=10_000)
+                    .suffix(" failed checks"))
+                    .changed();
+                ui.label("within");
+                changed |= ui.add(egui::DragValue::new(&mut breaker.window_secs)
+                    .clamp_range(1..=86_400)
+                    .suffix(" sec"))
+                    .changed();
+                ui.label("for");
+                changed |= ui.add(egui::DragValue::new(&mut breaker.cooldown_secs)
+                    .clamp_range(1..=86_400)
+                    .suffix(" sec"))
+                    .changed();
+            }
+            if changed {
+                self.circuit_breaker = self.configs.circuit_breaker();
+                self.mark_config_dirty();
+            }
         });
         
         ui.add_space(10.0);
@@ -545,28 +961,129 @@ impl MonitorApp {
         
         // Notification settings
         ui.heading("Notification Settings");
-        ui.checkbox(&mut self.configs.notification.enabled, "Enable ServerChan Notifications");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.configs.notification.enabled, "Enable ServerChan Notifications");
+            let dry_run = ui.checkbox(&mut self.configs.notification.dry_run, "Dry run")
+                .on_hover_text("Log notifications instead of sending them; applies to tasks started afterwards");
+            if dry_run.changed() {
+                self.update_notification_config();
+            }
+        });
         
         if self.configs.notification.enabled {
-            ui.label("ServerChan Keys (one per line):");
-            let mut keys_text = self.configs.notification.server_chan_keys.join("\n");
-            if ui.text_edit_multiline(&mut keys_text).changed() {
-                // Split by lines and filter out empty lines
-                let keys: Vec<String> = keys_text
-                    .lines()
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
+            ui.horizontal(|ui| {
+                ui.label("ServerChan Keys:");
+                ui.checkbox(&mut self.show_secrets, "Show");
+            });
+            
+            // One masked field per key
+            let mut keys_changed = false;
+            let mut remove_index = None;
+            for (i, key) in self.configs.notification.server_chan_keys.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::TextEdit::singleline(key)
+                        .password(!self.show_secrets)
+                        .hint_text("SendKey"));
+                    keys_changed |= response.changed();
+                    if ui.small_button("✖").on_hover_text("Remove key").clicked() {
+                        remove_index = Some(i);
+                    }
+                });
+            }
+            if ui.small_button("Add Key").clicked() {
+                self.configs.notification.server_chan_keys.push(String::new());
+            }
+            
+            if let Some(index) = remove_index {
+                self.configs.notification.server_chan_keys.remove(index);
                 
-                // If no keys were entered, keep at least one empty string
-                if keys.is_empty() {
-                    self.configs.notification.server_chan_keys = vec![String::new()];
-                } else {
-                    self.configs.notification.server_chan_keys = keys;
+                // Keep at least one (empty) field to type into
+                if self.configs.notification.server_chan_keys.is_empty() {
+                    self.configs.notification.server_chan_keys.push(String::new());
+                }
+                keys_changed = true;
+            }
+            
+            // Optional routing, e.g. critical alerts to a push channel
+            ui.horizontal(|ui| {
+                ui.label("Channel:");
+                let mut channel = self.configs.notification.channel.clone().unwrap_or_default();
+                if ui.add(egui::TextEdit::singleline(&mut channel).hint_text("e.g. 9|66").desired_width(100.0)).changed() {
+                    self.configs.notification.channel = Some(channel).filter(|channel| !channel.trim().is_empty());
+                    keys_changed = true;
+                }
+                
+                ui.label("Tags:");
+                if ui.add(egui::TextEdit::singleline(&mut self.tags_input).hint_text("tag1|tag2").desired_width(150.0)).changed() {
+                    let tags: Vec<String> = self.tags_input
+                        .split('|')
+                        .map(|tag| tag.trim().to_string())
+                        .filter(|tag| !tag.is_empty())
+                        .collect();
+                    self.configs.notification.tags = (!tags.is_empty()).then_some(tags);
+                    keys_changed = true;
                 }
                 
+                keys_changed |= ui.checkbox(&mut self.configs.notification.plain_text, "Plain text")
+                    .on_hover_text("Send content as written; by default $, @, * and the like are escaped so WeChat shows them literally")
+                    .changed();
+            });
+            
+            if keys_changed {
                 self.update_notification_config();
             }
+            
+            // Quiet hours, applied to tasks when they are (re)started
+            let mut quiet_enabled = self.configs.notification.quiet_hours.is_some();
+            let (mut quiet_start, mut quiet_end) = self.configs.notification.quiet_hours.unwrap_or((23, 7));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut quiet_enabled, "Quiet hours from");
+                ui.add_enabled(quiet_enabled, egui::DragValue::new(&mut quiet_start).clamp_range(0..=23).suffix(":00"));
+                ui.label("to");
+                ui.add_enabled(quiet_enabled, egui::DragValue::new(&mut quiet_end).clamp_range(0..=23).suffix(":00"));
+            });
+            let quiet_hours = quiet_enabled.then_some((quiet_start, quiet_end));
+            if quiet_hours != self.configs.notification.quiet_hours {
+                self.configs.notification.quiet_hours = quiet_hours;
+                self.mark_config_dirty();
+            }
+            
+            // Start notifications, applied to tasks when they are (re)started
+            if ui.checkbox(&mut self.configs.notification.notify_on_start, "Notify when tasks start")
+                .on_hover_text("Off keeps restarts silent for every task, whatever the task's own setting")
+                .changed()
+            {
+                self.mark_config_dirty();
+            }
+            
+            // Digest of non-critical changes, applied to the queue right away
+            let mut digest_enabled = self.configs.notification.digest_interval_secs.is_some();
+            let mut digest_minutes = self.configs.notification.digest_interval_secs.map_or(60, |secs| (secs / 60).max(1));
+            let digest_changed = ui.horizontal(|ui| {
+                ui.checkbox(&mut digest_enabled, "Send changes as a digest every").changed()
+                    | ui.add_enabled(digest_enabled, egui::DragValue::new(&mut digest_minutes).clamp_range(1..=1440).suffix(" min")).changed()
+            }).inner;
+            if digest_changed {
+                self.configs.notification.digest_interval_secs = digest_enabled.then_some(digest_minutes * 60);
+                self.notification_sender.set_digest_interval(self.configs.notification.digest_interval());
+                self.mark_config_dirty();
+            }
+            
+            // Title prefixes per kind of notification, applied to the queue right away
+            let mut prefixes_changed = false;
+            ui.collapsing("Title prefixes", |ui| {
+                egui::Grid::new("title_prefixes_grid").num_columns(2).show(ui, |ui| {
+                    for (kind, prefix) in self.configs.notification.title_prefixes.iter_mut() {
+                        ui.label(format!("{:?}", kind));
+                        prefixes_changed |= ui.add(egui::TextEdit::singleline(prefix).desired_width(60.0)).changed();
+                        ui.end_row();
+                    }
+                });
+            });
+            if prefixes_changed {
+                self.notification_sender.set_title_prefixes(self.configs.notification.title_prefixes.clone());
+                self.mark_config_dirty();
+            }
         }
         
         ui.add_space(10.0);
@@ -579,7 +1096,16 @@ impl MonitorApp {
         let logs_height = available_height * 0.4; // 40% of available height
         
         // Task list
-        ui.heading("Task List");
+        ui.horizontal(|ui| {
+            ui.heading("Task List");
+            ui.add_space(10.0);
+            ui.add(egui::TextEdit::singleline(&mut self.task_filter)
+                .hint_text("Filter by name, URL or #tag")
+                .desired_width(220.0));
+            if !self.task_filter.is_empty() && ui.small_button("✖").on_hover_text("Clear filter").clicked() {
+                self.task_filter.clear();
+            }
+        });
         ui.add_space(5.0);
         
         let task_count = self.configs.tasks.len();
@@ -602,12 +1128,17 @@ impl MonitorApp {
         ui.add_space(10.0);
         
         // Log area
-        ui.heading("Logs");
+        ui.horizontal(|ui| {
+            ui.heading("Logs");
+            if ui.button("History").on_hover_text("Notifications sent so far").clicked() {
+                self.history = Some(NotificationHistory::new(HISTORY_PATH).load());
+            }
+        });
         ui.add_space(5.0);
         
         // Create a combined log text for copying
         let log_text = self.logs.iter()
-            .map(|(log, _color)| log.clone())
+            .map(|entry| entry.text.clone())
             .collect::<Vec<String>>()
             .join("\n");
         
@@ -635,8 +1166,31 @@ impl MonitorApp {
                 ui.label("Logs with Colored Formatting:");
                 ui.add_space(5.0);
                 
-                for (log, color) in &self.logs {
-                    ui.label(RichText::new(log).color(*color));
+                let mut clicked_change = None;
+                for entry in &self.logs {
+                    ui.horizontal(|ui| {
+                        let hover = if entry.change.is_some() { "Copy this change with its details" } else { "Copy this line" };
+                        if ui.small_button("📋").on_hover_text(hover).clicked() {
+                            ui.output_mut(|output| output.copied_text = entry.copy_text());
+                        }
+                        let text = RichText::new(&entry.text).color(entry.color);
+                        match &entry.change {
+                            Some(change) => {
+                                if ui.add(egui::Label::new(text.underline()).sense(egui::Sense::click()))
+                                    .on_hover_text("Show details")
+                                    .clicked()
+                                {
+                                    clicked_change = Some((entry.text.clone(), change.clone()));
+                                }
+                            }
+                            None => {
+                                ui.label(text);
+                            }
+                        }
+                    });
+                }
+                if clicked_change.is_some() {
+                    self.viewing_change = clicked_change;
                 }
             });
     }
@@ -658,10 +1212,283 @@ impl MonitorApp {
         self.show_add_task_dialog = show_dialog;
     }
     
-    /// Draw edit task dialog
-    fn draw_edit_task_dialog(&mut self, ctx: &egui::Context) {
-        let mut show_dialog = self.show_edit_task_dialog;
-        
+    /// Draw the bulk add dialog, creating and saving one task per line with shared settings, left stopped
+    fn draw_bulk_add_dialog(&mut self, ctx: &egui::Context) {
+        let Some(mut template) = self.bulk_task.take() else {
+            return;
+        };
+        let label_width = 120.0;
+        let input_width = 250.0;
+        
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Bulk Add Tasks")
+            .resizable(false)
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Monitor Type:"));
+                    egui::ComboBox::from_id_source("bulk_task_type")
+                        .selected_text(template.task_type.to_string())
+                        .show_ui(ui, |ui| {
+                            for task_type in [TaskType::Static, TaskType::Api, TaskType::Xml, TaskType::JsRendered, TaskType::Hyperliquid] {
+                                ui.selectable_value(&mut template.task_type, task_type, task_type.to_string());
+                            }
+                        });
+                });
+                
+                let hint = match template.task_type {
+                    TaskType::Hyperliquid => "One 0x address per line",
+                    _ => "One URL per line",
+                };
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Lines:"));
+                    egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                        ui.add_sized([input_width, 120.0], egui::TextEdit::multiline(&mut self.bulk_lines)
+                            .hint_text(hint)
+                            .margin(egui::vec2(8.0, 4.0)));
+                    });
+                });
+                
+                match template.task_type {
+                    TaskType::Hyperliquid => {
+                        ui.horizontal(|ui| {
+                            ui.add_sized([label_width, 24.0], egui::Label::new("Monitor Options:"));
+                            ui.vertical(|ui| {
+                                ui.checkbox(&mut template.monitor_contract, "Contract");
+                                ui.checkbox(&mut template.monitor_spot, "Spot");
+                                ui.checkbox(&mut template.monitor_funding, "Funding");
+                                ui.checkbox(&mut template.monitor_spot_balance, "Spot balances")
+                                    .on_hover_text("Report token balance changes, catching deposits, withdrawals and transfers that aren't fills");
+                                ui.checkbox(&mut template.monitor_vaults, "Vaults")
+                                    .on_hover_text("Report vault deposits, withdrawals and equity changes");
+                                ui.checkbox(&mut template.stream_fills, "Stream fills")
+                                    .on_hover_text("Receive fills over the WebSocket API as they happen instead of polling for them");
+                            });
+                        });
+                    }
+                    _ => {
+                        ui.horizontal(|ui| {
+                            ui.add_sized([label_width, 24.0], egui::Label::new("Selector:"));
+                            ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut template.selector)
+                                .hint_text("Optional for web pages")
+                                .margin(egui::vec2(8.0, 4.0)));
+                        });
+                    }
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Interval (sec):"));
+                    ui.add_sized([input_width, 24.0], egui::Slider::new(&mut template.interval_secs, 1..=3600)
+                        .clamp_to_range(true)
+                        .suffix(" sec"));
+                });
+                
+                for (label, field) in [("Name:", &mut template.name), ("Notes:", &mut template.notes)] {
+                    ui.horizontal(|ui| {
+                        ui.add_sized([label_width, 24.0], egui::Label::new(label));
+                        ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(field)
+                            .hint_text("{line} is replaced by each line")
+                            .margin(egui::vec2(8.0, 4.0)));
+                    });
+                }
+                
+                // Every line must make a valid task before any is added
+                let tasks = template.expand_lines(&self.bulk_lines);
+                let mut problems: Vec<String> = tasks
+                    .iter()
+                    .filter_map(|task| {
+                        let errors = task.validate();
+                        (!errors.is_empty()).then(|| format!("{}: {}", task.target(), errors.join("; ")))
+                    })
+                    .collect();
+                if tasks.len() > 1 && !template.name.contains("{line}") {
+                    problems.insert(0, "Name must contain {line} so the tasks can be told apart".to_string());
+                }
+                ui.add_space(10.0);
+                for problem in problems.iter().take(5) {
+                    ui.label(RichText::new(problem).color(Color32::RED));
+                }
+                if problems.len() > 5 {
+                    ui.label(RichText::new(format!("…and {} more invalid lines", problems.len() - 5)).color(Color32::RED));
+                }
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let add = egui::Button::new(format!("Add {} Tasks", tasks.len()));
+                    if ui.add_enabled(!tasks.is_empty() && problems.is_empty(), add).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        
+        if confirmed {
+            let tasks = template.expand_lines(&self.bulk_lines);
+            self.add_log(&format!("Adding {} tasks", tasks.len()), Color32::LIGHT_BLUE);
+            for task in tasks {
+                self.push_task(task);
+            }
+        }
+        if !(confirmed || cancelled || !open) {
+            self.bulk_task = Some(template);
+        }
+    }
+    
+    /// Draw the delete confirmation dialog
+    fn draw_delete_confirm_dialog(&mut self, ctx: &egui::Context) {
+        let Some(index) = self.pending_delete.filter(|index| *index < self.configs.tasks.len()) else {
+            self.pending_delete = None;
+            return;
+        };
+        
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Delete Task")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!("Delete task '{}'?", self.configs.tasks[index].name));
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button(RichText::new("Delete").color(Color32::RED)).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+        
+        if confirmed {
+            self.delete_task(index);
+        }
+        if confirmed || cancelled || !open {
+            self.pending_delete = None;
+        }
+    }
+    
+    /// Draw the details of the change clicked in the log
+    fn draw_change_detail_dialog(&mut self, ctx: &egui::Context) {
+        let Some((line, change)) = &self.viewing_change else {
+            return;
+        };
+        
+        let mut open = true;
+        egui::Window::new("Change Details")
+            .resizable(true)
+            .collapsible(false)
+            .default_size(Vec2::new(480.0, 360.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(line).strong());
+                ui.add_space(5.0);
+                if ui.button("📋 Copy").on_hover_text("Copy the message and details").clicked() {
+                    ui.output_mut(|output| output.copied_text = format!("{}\n\n{}", line, change.details));
+                }
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .id_source("change_details_scroll_area")
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        ui.label(RichText::new(&change.details).monospace());
+                    });
+            });
+        
+        if !open {
+            self.viewing_change = None;
+        }
+    }
+    
+    /// Draw the notification history, newest first
+    fn draw_history_dialog(&mut self, ctx: &egui::Context) {
+        let Some(entries) = &self.history else {
+            return;
+        };
+        
+        let mut tasks: Vec<&str> = entries.iter().map(|entry| entry.task.as_str()).collect();
+        tasks.sort_unstable();
+        tasks.dedup();
+        
+        let mut open = true;
+        let mut reload = false;
+        let mut selected = self.history_task.clone();
+        egui::Window::new("Notification History")
+            .resizable(true)
+            .collapsible(false)
+            .default_size(Vec2::new(560.0, 420.0))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Task:");
+                    egui::ComboBox::from_id_source("history_task")
+                        .selected_text(if selected.is_empty() { "All tasks" } else { selected.as_str() })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut selected, String::new(), "All tasks");
+                            for task in &tasks {
+                                ui.selectable_value(&mut selected, task.to_string(), *task);
+                            }
+                        });
+                    if ui.button("Reload").clicked() {
+                        reload = true;
+                    }
+                });
+                ui.separator();
+                
+                let shown: Vec<&HistoryEntry> = entries
+                    .iter()
+                    .rev()
+                    .filter(|entry| selected.is_empty() || entry.task == selected)
+                    .collect();
+                if shown.is_empty() {
+                    ui.label("No notifications sent yet");
+                }
+                egui::ScrollArea::vertical()
+                    .id_source("history_scroll_area")
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        for (i, entry) in shown.iter().enumerate() {
+                            let status = if entry.delivered {
+                                RichText::new("Sent").color(Color32::GREEN)
+                            } else if entry.muted {
+                                RichText::new("Muted").color(Color32::GRAY)
+                            } else {
+                                RichText::new(format!("Failed: {}", entry.error.as_deref().unwrap_or("unknown error"))).color(Color32::RED)
+                            };
+                            ui.horizontal(|ui| {
+                                ui.label(format_timestamp(entry.time));
+                                ui.label(RichText::new(format!("[{}] {}", entry.task, entry.title)).strong());
+                            });
+                            ui.label(status);
+                            egui::CollapsingHeader::new("Content")
+                                .id_source(("history_content", i))
+                                .show(ui, |ui| {
+                                    ui.label(RichText::new(&entry.content).monospace());
+                                });
+                            ui.separator();
+                        }
+                    });
+            });
+        
+        self.history_task = selected;
+        if !open {
+            self.history = None;
+        } else if reload {
+            self.history = Some(NotificationHistory::new(HISTORY_PATH).load());
+        }
+    }
+    
+    /// Draw edit task dialog
+    fn draw_edit_task_dialog(&mut self, ctx: &egui::Context) {
+        let mut show_dialog = self.show_edit_task_dialog;
+        
         if show_dialog {
             egui::Window::new("Edit Monitoring Task")
                 .resizable(false)
@@ -675,6 +1502,126 @@ impl MonitorApp {
         self.show_edit_task_dialog = show_dialog;
     }
     
+    /// Draw the "Test Selector" and "Preview Response" buttons with their latest results
+    fn draw_fetch_tools(&mut self, ui: &mut Ui, label_width: f32) {
+        ui.horizontal(|ui| {
+            ui.add_space(label_width + ui.spacing().item_spacing.x);
+            let button = ui.add_enabled(!self.selector_test_running, egui::Button::new("Test Selector"));
+            if button.clicked() {
+                self.test_selector();
+            }
+            let button = ui.add_enabled(!self.response_preview_running, egui::Button::new("Preview Response"));
+            if button.clicked() {
+                self.preview_response();
+            }
+            if self.selector_test_running || self.response_preview_running {
+                ui.spinner();
+            }
+        });
+        
+        if let Some(result) = &self.selector_test {
+            let (text, color) = match result {
+                Ok(text) => (text.as_str(), ui.visuals().text_color()),
+                Err(e) => (e.as_str(), Color32::RED),
+            };
+            egui::ScrollArea::vertical()
+                .id_source("selector_test")
+                .max_height(120.0)
+                .show(ui, |ui| {
+                    ui.label(RichText::new(text).monospace().color(color));
+                });
+        }
+        
+        match &self.response_preview {
+            Some(Ok(body)) => {
+                ui.label("Response preview:");
+                egui::ScrollArea::both()
+                    .id_source("response_preview")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        ui.add(egui::TextEdit::multiline(&mut body.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY));
+                    });
+            }
+            Some(Err(e)) => {
+                ui.label(RichText::new(format!("Preview failed: {}", e)).color(Color32::RED));
+            }
+            None => {}
+        }
+    }
+    
+    /// Fetch the form's URL once on the runtime for the response preview
+    fn preview_response(&mut self) {
+        let task = self.editing_task.clone();
+        let tx = self.message_sender.clone();
+        
+        self.response_preview = None;
+        self.response_preview_running = true;
+        self.runtime.spawn(async move {
+            let result = preview::preview_body(&task).await.map_err(|e| e.to_string());
+            let _ = tx.send(Message::ResponsePreviewed(result));
+        });
+    }
+    
+    /// Draw the baseline of a static or API task, with buttons to take it from the current content or clear it
+    fn draw_baseline_field(&mut self, ui: &mut Ui, label_width: f32) {
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Baseline:"));
+            match &self.editing_task.baseline {
+                Some(baseline) => ui.label(format!("{} bytes, every check is compared with it", baseline.len()))
+                    .on_hover_text(baseline.chars().take(500).collect::<String>()),
+                None => ui.label("None, each check is compared with the previous one"),
+            };
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(label_width + ui.spacing().item_spacing.x);
+            let button = ui.add_enabled(!self.baseline_running, egui::Button::new("Set current as baseline"))
+                .on_hover_text("Fetch the content now and alert on every check until it matches again, e.g. to catch defacement");
+            if button.clicked() {
+                self.fetch_baseline();
+            }
+            if self.editing_task.baseline.is_some() && ui.button("Clear baseline").clicked() {
+                self.editing_task.baseline = None;
+            }
+            if self.baseline_running {
+                ui.spinner();
+            }
+        });
+        if let Some(e) = &self.baseline_error {
+            ui.label(RichText::new(format!("Fetching the baseline failed: {}", e)).color(Color32::RED));
+        }
+    }
+    
+    /// Fetch the content the form's task compares on the runtime, to become its baseline
+    fn fetch_baseline(&mut self) {
+        let task = self.editing_task.clone();
+        let tx = self.message_sender.clone();
+        
+        self.baseline_error = None;
+        self.baseline_running = true;
+        self.runtime.spawn(async move {
+            let result = match task.build_monitor() {
+                Ok(mut monitor) => monitor.current_content().await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(Message::BaselineFetched(result));
+        });
+    }
+    
+    /// Fetch the form's URL once and run its selector on the runtime
+    fn test_selector(&mut self) {
+        let task = self.editing_task.clone();
+        let tx = self.message_sender.clone();
+        
+        self.selector_test = None;
+        self.selector_test_running = true;
+        self.runtime.spawn(async move {
+            let result = preview::test_selector(&task).await.map_err(|e| e.to_string());
+            let _ = tx.send(Message::SelectorTested(result));
+        });
+    }
+    
     /// Draw task form
     fn draw_task_form(&mut self, ui: &mut Ui) {
         // Define unified input field width
@@ -685,48 +1632,73 @@ impl MonitorApp {
         
         // Set window title
         let is_edit_mode = self.editing_task_index.is_some();
-        match self.editing_task.task_type.as_str() {
-            "Static Web" => {
+        match self.editing_task.task_type {
+            TaskType::Static => {
                 if is_edit_mode {
                     ui.heading("Edit Web Monitor");
                 } else {
                     ui.heading("Add Web Monitor");
                 }
             },
-            "API Monitor" => {
+            TaskType::Api => {
                 if is_edit_mode {
                     ui.heading("Edit API Monitor");
                 } else {
                     ui.heading("Add API Monitor");
                 }
             },
-            "Hyperliquid" => {
+            TaskType::Xml => {
+                if is_edit_mode {
+                    ui.heading("Edit XML Monitor");
+                } else {
+                    ui.heading("Add XML Monitor");
+                }
+            },
+            TaskType::JsRendered => {
+                if is_edit_mode {
+                    ui.heading("Edit JS Rendered Monitor");
+                } else {
+                    ui.heading("Add JS Rendered Monitor");
+                }
+            },
+            TaskType::Hyperliquid => {
                 if is_edit_mode {
                     ui.heading("Edit Hyperliquid Monitor");
                 } else {
                     ui.heading("Add Hyperliquid Monitor");
                 }
             }
-            _ => {}
         }
         ui.add_space(20.0);
         
         // Monitor type selection
         ui.label("Monitor Type:");
         ui.horizontal(|ui| {
-            if ui.radio_value(&mut self.editing_task.task_type, "Static Web".to_string(), "Static Web").clicked() {
+            if ui.radio_value(&mut self.editing_task.task_type, TaskType::Static, TaskType::Static.to_string()).clicked() {
                 // Reset relevant fields when switching to Web monitor type
                 if !is_edit_mode {
                     self.editing_task.selector = "".to_string();
                 }
             }
-            if ui.radio_value(&mut self.editing_task.task_type, "API Monitor".to_string(), "API Monitor").clicked() {
+            if ui.radio_value(&mut self.editing_task.task_type, TaskType::Api, TaskType::Api.to_string()).clicked() {
                 // Reset relevant fields when switching to API monitor type
                 if !is_edit_mode {
                     self.editing_task.selector = "$.data.price".to_string();
                 }
             }
-            if ui.radio_value(&mut self.editing_task.task_type, "Hyperliquid".to_string(), "Hyperliquid").clicked() {
+            if ui.radio_value(&mut self.editing_task.task_type, TaskType::Xml, TaskType::Xml.to_string()).clicked() {
+                // Reset relevant fields when switching to XML monitor type
+                if !is_edit_mode {
+                    self.editing_task.selector = "/rss/channel/item[1]/title".to_string();
+                }
+            }
+            if ui.radio_value(&mut self.editing_task.task_type, TaskType::JsRendered, TaskType::JsRendered.to_string()).clicked() {
+                // Reset relevant fields when switching to JS rendered monitor type
+                if !is_edit_mode {
+                    self.editing_task.selector = "".to_string();
+                }
+            }
+            if ui.radio_value(&mut self.editing_task.task_type, TaskType::Hyperliquid, TaskType::Hyperliquid.to_string()).clicked() {
                 // Reset relevant fields when switching to Hyperliquid monitor type
                 if !is_edit_mode {
                     self.editing_task.address = "0x...".to_string();
@@ -737,8 +1709,8 @@ impl MonitorApp {
         ui.add_space(15.0);
         
         // Display different form fields based on task type
-        match self.editing_task.task_type.as_str() {
-            "Static Web" => {
+        match self.editing_task.task_type {
+            TaskType::Static => {
                 // Web monitor form
                 ui.horizontal(|ui| {
                     ui.add_sized([label_width, 24.0], egui::Label::new("Website URL:"));
@@ -750,12 +1722,81 @@ impl MonitorApp {
                 ui.add_space(10.0);
                 
                 ui.horizontal(|ui| {
-                    ui.add_sized([label_width, 24.0], egui::Label::new("ServerChan Key:"));
-                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.configs.notification.server_chan_keys[0])
-                        .hint_text("ServerChan API key")
+                    ui.add_sized([label_width, 24.0], egui::Label::new("CSS Selector:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.selector)
+                        .hint_text("#content (used by the Selected text compare mode)")
                         .margin(egui::vec2(8.0, 4.0)));
                 });
                 
+                self.draw_fetch_tools(ui, label_width);
+                self.draw_baseline_field(ui, label_width);
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Compare:"));
+                    let compare_mode = &mut self.editing_task.compare_mode;
+                    egui::ComboBox::from_id_source("compare_mode")
+                        .selected_text(compare_mode.label())
+                        .show_ui(ui, |ui| {
+                            for option in CompareMode::ALL {
+                                ui.selectable_value(compare_mode, option, option.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Normalized text ignores markup and whitespace, selected text needs a CSS selector, hash only keeps no diff but uses minimal memory");
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Rendering:"));
+                    ui.checkbox(&mut self.editing_task.render_js, "Render JavaScript")
+                        .on_hover_text("Load the page in headless Chrome (must be installed) for content built client-side");
+                });
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Ignore:"));
+                    let response = ui.add_sized([input_width, 48.0], egui::TextEdit::multiline(&mut self.editing_ignore_patterns)
+                        .hint_text("Optional regexes removed before comparing, one per line, e.g. visitors: \\d+")
+                        .margin(egui::vec2(8.0, 4.0)));
+                    if response.changed() {
+                        self.editing_task.ignore_patterns = parse_patterns(&self.editing_ignore_patterns);
+                    }
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Require:"));
+                    let response = ui.add_sized([input_width, 48.0], egui::TextEdit::multiline(&mut self.editing_require_patterns)
+                        .hint_text("Optional regexes, notify only when one matches the changed text")
+                        .margin(egui::vec2(8.0, 4.0)));
+                    if response.changed() {
+                        self.editing_task.require_patterns = parse_patterns(&self.editing_require_patterns);
+                    }
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Number:"));
+                    let mut numeric_regex = self.editing_task.numeric_regex.clone().unwrap_or_default();
+                    let response = ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut numeric_regex)
+                        .hint_text("Optional regex capturing a number to track, e.g. Price:\\s*([\\d,.]+)")
+                        .margin(egui::vec2(8.0, 4.0)))
+                        .on_hover_text("Track the captured number and report its moves instead of content changes");
+                    if response.changed() {
+                        self.editing_task.numeric_regex = Some(numeric_regex).filter(|regex| !regex.trim().is_empty());
+                    }
+                });
+                
+                ui.add_space(10.0);
+                
+                self.draw_cookies_field(ui, label_width, input_width);
+                
+                self.draw_advanced_section(ui, label_width, input_width);
+                
+                ui.add_space(10.0);
+                
+                self.draw_notification_fields(ui, label_width, input_width);
+                
                 ui.add_space(10.0);
                 
                 ui.horizontal(|ui| {
@@ -765,7 +1806,7 @@ impl MonitorApp {
                         .margin(egui::vec2(8.0, 4.0)));
                 });
             },
-            "API Monitor" => {
+            TaskType::Api => {
                 // API monitor form
                 ui.horizontal(|ui| {
                     ui.add_sized([label_width, 24.0], egui::Label::new("API URL:"));
@@ -778,22 +1819,112 @@ impl MonitorApp {
                 
                 ui.horizontal(|ui| {
                     ui.add_sized([label_width, 24.0], egui::Label::new("JSONPath:"));
+                    ui.add_sized([input_width, 72.0], egui::TextEdit::multiline(&mut self.editing_task.selector)
+                        .hint_text("One per line, e.g. $.data.price\n(leave empty to monitor entire response)")
+                        .desired_rows(3)
+                        .margin(egui::vec2(8.0, 4.0)));
+                });
+                
+                self.draw_fetch_tools(ui, label_width);
+                self.draw_baseline_field(ui, label_width);
+                
+                ui.add_space(10.0);
+                
+                self.draw_alert_condition_field(ui, label_width);
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Compare:"));
+                    ui.checkbox(&mut self.editing_task.array_length_mode, "Item count only")
+                        .on_hover_text("Compare how many items the JSONPath matches, e.g. new listings, instead of their content");
+                });
+                
+                ui.add_space(10.0);
+                
+                self.draw_cookies_field(ui, label_width, input_width);
+                
+                self.draw_advanced_section(ui, label_width, input_width);
+                
+                ui.add_space(10.0);
+                
+                self.draw_notification_fields(ui, label_width, input_width);
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Notes:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.notes)
+                        .hint_text("Optional notes")
+                        .margin(egui::vec2(8.0, 4.0)));
+                });
+            },
+            TaskType::Xml => {
+                // XML monitor form
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Document URL:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.url)
+                        .hint_text("https://example.com/feed.xml")
+                        .margin(egui::vec2(8.0, 4.0)));
+                });
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("XPath:"));
                     ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.selector)
-                        .hint_text("$.data.price (leave empty to monitor entire response)")
+                        .hint_text("/rss/channel/item[1]/title")
                         .margin(egui::vec2(8.0, 4.0)));
                 });
                 
+                self.draw_fetch_tools(ui, label_width);
+                
                 ui.add_space(10.0);
                 
+                self.draw_advanced_section(ui, label_width, input_width);
+                
+                ui.add_space(10.0);
+                
+                self.draw_notification_fields(ui, label_width, input_width);
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Notes:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.notes)
+                        .hint_text("Optional notes")
+                        .margin(egui::vec2(8.0, 4.0)));
+                });
+            },
+            TaskType::JsRendered => {
+                // JS rendered page form, loaded in headless Chrome
                 ui.horizontal(|ui| {
-                    ui.add_sized([label_width, 24.0], egui::Label::new("ServerChan Key:"));
-                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.configs.notification.server_chan_keys[0])
-                        .hint_text("ServerChan API key")
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Page URL:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.url)
+                        .hint_text("https://app.example.com/dashboard")
                         .margin(egui::vec2(8.0, 4.0)));
                 });
                 
                 ui.add_space(10.0);
                 
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("CSS Selector:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.selector)
+                        .hint_text("#price (leave empty to monitor all visible text)")
+                        .margin(egui::vec2(8.0, 4.0)));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_space(label_width + 8.0);
+                    ui.label(RichText::new("Requires Chrome or Chromium; each check launches it in the background").small().weak());
+                });
+                
+                self.draw_fetch_tools(ui, label_width);
+                
+                ui.add_space(10.0);
+                
+                self.draw_notification_fields(ui, label_width, input_width);
+                
+                ui.add_space(10.0);
+                
                 ui.horizontal(|ui| {
                     ui.add_sized([label_width, 24.0], egui::Label::new("Notes:"));
                     ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.notes)
@@ -801,7 +1932,7 @@ impl MonitorApp {
                         .margin(egui::vec2(8.0, 4.0)));
                 });
             },
-            "Hyperliquid" => {
+            TaskType::Hyperliquid => {
                 // Hyperliquid monitor form
                 ui.horizontal(|ui| {
                     ui.add_sized([label_width, 24.0], egui::Label::new("Wallet Address:"));
@@ -812,12 +1943,7 @@ impl MonitorApp {
                 
                 ui.add_space(10.0);
                 
-                ui.horizontal(|ui| {
-                    ui.add_sized([label_width, 24.0], egui::Label::new("ServerChan Key:"));
-                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.configs.notification.server_chan_keys[0])
-                        .hint_text("ServerChan API key")
-                        .margin(egui::vec2(8.0, 4.0)));
-                });
+                self.draw_notification_fields(ui, label_width, input_width);
                 
                 ui.add_space(10.0);
                 
@@ -836,12 +1962,89 @@ impl MonitorApp {
                     ui.vertical(|ui| {
                         ui.checkbox(&mut self.editing_task.monitor_contract, "Contract");
                         ui.checkbox(&mut self.editing_task.monitor_spot, "Spot");
+                        ui.checkbox(&mut self.editing_task.monitor_funding, "Funding");
+                        ui.checkbox(&mut self.editing_task.monitor_spot_balance, "Spot balances")
+                            .on_hover_text("Report token balance changes, catching deposits, withdrawals and transfers that aren't fills");
+                        ui.checkbox(&mut self.editing_task.monitor_vaults, "Vaults")
+                            .on_hover_text("Report vault deposits, withdrawals and equity changes");
+                        ui.checkbox(&mut self.editing_task.stream_fills, "Stream fills")
+                            .on_hover_text("Receive fills over the WebSocket API as they happen instead of polling for them");
+                    });
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Backfill:"));
+                    ui.add(egui::DragValue::new(&mut self.editing_task.backfill_minutes)
+                        .clamp_range(0..=1440)
+                        .suffix(" min"))
+                        .on_hover_text("On start, report fills from this many recent minutes (0 only records the latest fill)");
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Position Threshold:"));
+                    let mut threshold = self.editing_task.position_change_threshold_pct.unwrap_or(0.0);
+                    if ui.add(egui::DragValue::new(&mut threshold)
+                        .clamp_range(0.0..=100.0)
+                        .speed(0.5)
+                        .suffix("%"))
+                        .on_hover_text("Only notify when a position's size moves at least this much since the last notification (0 notifies on every change)")
+                        .changed()
+                    {
+                        self.editing_task.position_change_threshold_pct = Some(threshold).filter(|threshold| *threshold > 0.0);
+                    }
+                });
+                
+                if self.editing_task.monitor_vaults {
+                    ui.horizontal(|ui| {
+                        ui.add_sized([label_width, 24.0], egui::Label::new("Vault Threshold:"));
+                        let mut threshold = self.editing_task.vault_change_threshold_pct.unwrap_or(DEFAULT_VAULT_CHANGE_PCT);
+                        if ui.add(egui::DragValue::new(&mut threshold)
+                            .clamp_range(0.0..=100.0)
+                            .speed(0.1)
+                            .suffix("%"))
+                            .on_hover_text("Only notify when a vault's equity moves at least this much since the last notification; deposits and withdrawals always notify")
+                            .changed()
+                        {
+                            self.editing_task.vault_change_threshold_pct = Some(threshold).filter(|threshold| *threshold != DEFAULT_VAULT_CHANGE_PCT);
+                        }
                     });
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("PnL Alert:"));
+                    let mut threshold = self.editing_task.pnl_alert_threshold_usd.unwrap_or(0.0);
+                    if ui.add(egui::DragValue::new(&mut threshold)
+                        .clamp_range(0.0..=1_000_000.0)
+                        .speed(10.0)
+                        .prefix("$"))
+                        .on_hover_text("Notify when a position's unrealized PnL moves this much since it was last reported (0 turns PnL alerts off)")
+                        .changed()
+                    {
+                        self.editing_task.pnl_alert_threshold_usd = Some(threshold).filter(|threshold| *threshold > 0.0);
+                    }
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("Explorer:"));
+                    ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_task.explorer)
+                        .hint_text("hyperdash, hypurrscan or https://...{address}")
+                        .margin(egui::vec2(8.0, 4.0)))
+                        .on_hover_text("Explorer linked from notifications, by name or as a URL where {address} is replaced by the wallet address");
                 });
             },
-            _ => {}
         }
         
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Tags:"));
+            let response = ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_tags)
+                .hint_text("Optional, comma-separated, e.g. prices, wallets")
+                .margin(egui::vec2(8.0, 4.0)));
+            if response.changed() {
+                self.editing_task.tags = parse_tags(&self.editing_tags);
+            }
+        });
+        
         // Monitor interval settings
         ui.add_space(15.0);
         ui.horizontal(|ui| {
@@ -850,6 +2053,92 @@ impl MonitorApp {
                 .clamp_to_range(true)
                 .suffix(" sec"));
         });
+        ui.horizontal(|ui| {
+            ui.add_space(label_width + ui.spacing().item_spacing.x);
+            for (label, secs) in INTERVAL_PRESETS {
+                if ui.selectable_label(self.editing_task.interval_secs == secs, label).clicked() {
+                    self.editing_task.interval_secs = secs;
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(label_width + ui.spacing().item_spacing.x);
+            ui.checkbox(&mut self.editing_task.aligned, "Align to clock")
+                .on_hover_text("Check on multiples of the interval, e.g. exactly on the minute, instead of an interval after each check. Jitter is not applied");
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(label_width + ui.spacing().item_spacing.x);
+            ui.checkbox(&mut self.editing_task.notify_on_start, "Notify on start")
+                .on_hover_text("Send a \"Started monitoring\" notification on the first check. Off only records the initial state, later changes still notify");
+        });
+        if let Some(warning) = self.editing_task.interval_warning() {
+            ui.label(RichText::new(format!("⚠ {}", warning)).color(Color32::YELLOW));
+        }
+        
+        // Optional auto-stop after a fixed monitoring window
+        ui.add_space(10.0);
+        let mut auto_stop = self.editing_task.max_runtime_secs.is_some_and(|secs| secs > 0);
+        let mut max_runtime_mins = self.editing_task.max_runtime_secs.unwrap_or(0) / 60;
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Auto-stop:"));
+            ui.checkbox(&mut auto_stop, "Stop after");
+            ui.add_enabled(auto_stop, egui::DragValue::new(&mut max_runtime_mins)
+                .clamp_range(1..=43200)
+                .suffix(" min"));
+        });
+        self.editing_task.max_runtime_secs = if auto_stop {
+            Some(max_runtime_mins.max(1) * 60)
+        } else {
+            None
+        };
+        
+        // Alert once after this many failed checks in a row
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Alert after:"));
+            ui.add(egui::DragValue::new(&mut self.editing_task.failure_threshold)
+                .clamp_range(1..=100)
+                .suffix(" failed checks"));
+        });
+        
+        // Alert once when a target that should update regularly stops changing
+        let mut stale_alert = self.editing_task.stale_after_secs.is_some();
+        let mut stale_after_mins = self.editing_task.stale_after_secs.unwrap_or(3600) / 60;
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Stale alert:"));
+            ui.checkbox(&mut stale_alert, "No change for")
+                .on_hover_text("Notify once when nothing changed for this long, e.g. a frozen price feed or status page");
+            ui.add_enabled(stale_alert, egui::DragValue::new(&mut stale_after_mins)
+                .clamp_range(1..=43200)
+                .suffix(" min"));
+        });
+        self.editing_task.stale_after_secs = stale_alert.then(|| stale_after_mins.max(1) * 60);
+        
+        // Only poll within these days and hours
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Active hours:"));
+            let response = ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut self.editing_schedule)
+                .hint_text("Optional, e.g. mon-fri 09:30-16:00 (local time)")
+                .margin(egui::vec2(8.0, 4.0)));
+            if response.changed() {
+                self.editing_task.active_schedule = self.editing_schedule.parse().ok();
+            }
+        });
+        
+        // Show validation problems inline
+        let mut validation_errors = self.editing_task.validate();
+        if !self.editing_schedule.trim().is_empty() {
+            if let Err(e) = self.editing_schedule.parse::<ActiveSchedule>() {
+                validation_errors.push(e.to_string());
+            }
+        }
+        if !validation_errors.is_empty() {
+            ui.add_space(10.0);
+            for error in &validation_errors {
+                ui.label(RichText::new(error).color(Color32::RED));
+            }
+        }
+        let is_valid = validation_errors.is_empty();
         
         ui.add_space(20.0);
         
@@ -862,14 +2151,14 @@ impl MonitorApp {
             let btn_size = egui::Vec2::new(100.0, 32.0);
             
             if is_edit_mode {
-                if ui.add_sized(btn_size, egui::Button::new("Update")).clicked() {
+                if ui.add_enabled_ui(is_valid, |ui| ui.add_sized(btn_size, egui::Button::new("Update"))).inner.clicked() {
                     // Update task
                     if let Some(_idx) = self.editing_task_index {
                         self.update_task();
                     }
                 }
             } else {
-                if ui.add_sized(btn_size, egui::Button::new("Start Monitor")).clicked() {
+                if ui.add_enabled_ui(is_valid, |ui| ui.add_sized(btn_size, egui::Button::new("Start Monitor"))).inner.clicked() {
                     // Create new task and start monitoring
                     self.add_task();
                     
@@ -893,6 +2182,134 @@ impl MonitorApp {
         });
     }
     
+    /// Draw the collapsed "Advanced" request settings
+    fn draw_advanced_section(&mut self, ui: &mut Ui, label_width: f32, input_width: f32) {
+        egui::CollapsingHeader::new("Advanced")
+            .id_source("task_form_advanced")
+            .show(ui, |ui| {
+                let mut user_agent = self.editing_task.user_agent.clone().unwrap_or_default();
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("User-Agent:"));
+                    let response = ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut user_agent)
+                        .hint_text("Optional, defaults to a desktop Chrome")
+                        .margin(egui::vec2(8.0, 4.0)))
+                        .on_hover_text(DEFAULT_USER_AGENT);
+                    
+                    if response.changed() {
+                        self.editing_task.user_agent = if user_agent.trim().is_empty() {
+                            None
+                        } else {
+                            Some(user_agent.trim().to_string())
+                        };
+                    }
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.add_sized([label_width, 24.0], egui::Label::new("TLS:"));
+                    ui.checkbox(&mut self.editing_task.accept_invalid_certs, "Accept invalid certificates")
+                        .on_hover_text("For internal endpoints with self-signed certificates. Insecure: the connection can be intercepted");
+                    if self.editing_task.accept_invalid_certs {
+                        ui.label(RichText::new("⚠ Insecure").color(Color32::RED));
+                    }
+                });
+                
+                if matches!(self.editing_task.task_type, TaskType::Static | TaskType::Api) {
+                    let mut slow_alert = self.editing_task.slow_threshold_ms.is_some();
+                    let mut threshold_ms = self.editing_task.slow_threshold_ms.unwrap_or(2000);
+                    ui.horizontal(|ui| {
+                        ui.add_sized([label_width, 24.0], egui::Label::new("Slow alert:"));
+                        ui.checkbox(&mut slow_alert, "Alert when responses stay over")
+                            .on_hover_text(format!("Sent after {} slow responses in a row", SLOW_CHECKS_BEFORE_ALERT));
+                        ui.add_enabled(slow_alert, egui::DragValue::new(&mut threshold_ms).clamp_range(1..=60_000).suffix(" ms"));
+                    });
+                    self.editing_task.slow_threshold_ms = slow_alert.then_some(threshold_ms);
+                }
+            });
+    }
+    
+    /// Draw the session cookie field, hidden like the keys
+    fn draw_cookies_field(&mut self, ui: &mut Ui, label_width: f32, input_width: f32) {
+        let mut cookies = self.editing_task.cookies.clone().unwrap_or_default();
+        
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Cookies:"));
+            let response = ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut cookies)
+                .password(!self.show_secrets)
+                .hint_text("Optional, e.g. session=abc123 (copy from the browser)")
+                .margin(egui::vec2(8.0, 4.0)))
+                .on_hover_text("Sent as the Cookie header. Sessions expire: paste a fresh cookie when checks start failing");
+            
+            if response.changed() {
+                self.editing_task.cookies = if cookies.trim().is_empty() {
+                    None
+                } else {
+                    Some(cookies.trim().to_string())
+                };
+            }
+        });
+    }
+    
+    /// Draw the API alert condition, an operator and the value compared with
+    fn draw_alert_condition_field(&mut self, ui: &mut Ui, label_width: f32) {
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Alert when:"));
+            let mut enabled = self.editing_task.alert_when.is_some();
+            if ui.checkbox(&mut enabled, "value").on_hover_text("Only notify when a value starts meeting the condition, e.g. == 0 for out of stock").changed() {
+                self.editing_task.alert_when = enabled.then_some((AlertOperator::Gt, 0.0));
+            }
+            
+            if let Some((operator, value)) = &mut self.editing_task.alert_when {
+                egui::ComboBox::from_id_source("alert_operator")
+                    .width(50.0)
+                    .selected_text(operator.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in AlertOperator::ALL {
+                            ui.selectable_value(operator, option, option.to_string());
+                        }
+                    });
+                ui.add(egui::DragValue::new(value).speed(0.1));
+            }
+        });
+    }
+    
+    /// Draw the task-specific ServerChan key and notifier selection
+    fn draw_notification_fields(&mut self, ui: &mut Ui, label_width: f32, input_width: f32) {
+        let mut key = self.editing_task.server_chan_key.clone().unwrap_or_default();
+        
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("ServerChan Key:"));
+            let response = ui.add_sized([input_width, 24.0], egui::TextEdit::singleline(&mut key)
+                .password(!self.show_secrets)
+                .hint_text("Optional, defaults to global keys")
+                .margin(egui::vec2(8.0, 4.0)));
+            
+            if response.changed() {
+                self.editing_task.server_chan_key = if key.trim().is_empty() {
+                    None
+                } else {
+                    Some(key.trim().to_string())
+                };
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            ui.add_sized([label_width, 24.0], egui::Label::new("Notify via:"));
+            for kind in NotifierKind::ALL {
+                let mut selected = self.editing_task.notifiers.iter().any(|name| NotifierKind::from_name(name) == Some(*kind));
+                if ui.checkbox(&mut selected, kind.label()).changed() {
+                    self.editing_task.notifiers.retain(|name| NotifierKind::from_name(name) != Some(*kind));
+                    if selected {
+                        self.editing_task.notifiers.push(kind.name().to_string());
+                    }
+                }
+            }
+            if self.editing_task.notifiers.is_empty() {
+                ui.weak("(all configured)");
+            }
+        });
+    }
+    
     /// Draw task list
     fn draw_task_list(&mut self, ui: &mut Ui) {
         // Clone tasks to avoid borrow checker issues
@@ -902,6 +2319,10 @@ impl MonitorApp {
         // 记录要删除的任务索引
         let mut delete_index: Option<usize> = None;
         
+        if !tasks.iter().any(|task| task.matches_filter(&self.task_filter)) {
+            ui.label(format!("No tasks match \"{}\"", self.task_filter.trim()));
+        }
+        
         for i in 0..task_count {
             // 确保索引仍然有效
             if i >= self.configs.tasks.len() {
@@ -909,10 +2330,16 @@ impl MonitorApp {
             }
             
             let task_clone = self.configs.tasks[i].clone();
-            let status = self.task_statuses[i].clone();
+            if !task_clone.matches_filter(&self.task_filter) {
+                continue;
+            }
+            let status = self.task_statuses[i];
+            let unread = self.unread_changes[i];
+            let reachability = self.reachability[i].clone();
+            let response_time = self.response_times[i];
             
             // Task card style
-            egui::Frame::none()
+            let card = egui::Frame::none()
                 .fill(ui.visuals().extreme_bg_color)
                 .inner_margin(egui::style::Margin::symmetric(10.0, 10.0))
                 .show(ui, |ui| {
@@ -921,11 +2348,26 @@ impl MonitorApp {
                         let status_text = match &status {
                             TaskStatus::Running => RichText::new("⚡ Running").color(Color32::GREEN),
                             TaskStatus::Idle => RichText::new("⏹ Stopped").color(Color32::YELLOW),
+                            TaskStatus::Degraded => RichText::new("⚠ Degraded").color(DEGRADED_COLOR),
+                            TaskStatus::Scheduled => RichText::new("⏸ Scheduled (inactive)").color(Color32::LIGHT_BLUE),
                             TaskStatus::Error => RichText::new("❌ Error").color(Color32::RED),
                         };
                         
                         ui.label(format!("#{}: ", i + 1));
                         ui.add(egui::Label::new(RichText::new(&task_clone.name).strong().size(16.0)));
+                        for tag in &task_clone.tags {
+                            if ui.small_button(RichText::new(format!("#{}", tag)).color(Color32::LIGHT_BLUE))
+                                .on_hover_text("Show only tasks with this tag")
+                                .clicked() {
+                                self.task_filter = format!("#{}", tag);
+                            }
+                        }
+                        if unread > 0 {
+                            ui.label(RichText::new(format!("🔔 {} new", unread)).color(Color32::GOLD).strong());
+                            if ui.small_button("Ack").clicked() {
+                                self.unread_changes[i] = 0;
+                            }
+                        }
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             ui.label(status_text);
                         });
@@ -935,31 +2377,66 @@ impl MonitorApp {
                     
                     // Task details
                     ui.horizontal(|ui| {
-                        match task_clone.task_type.as_str() {
-                            "Static Web" => {
-                                ui.label(format!("Type: Static Web Monitor | URL: {} | Interval: {}s", 
-                                           task_clone.url, task_clone.interval_secs));
+                        match task_clone.task_type {
+                            TaskType::Static => {
+                                ui.label(format!("Type: Static Web Monitor | URL: {} | Interval: {}s{}{}", 
+                                           task_clone.url, task_clone.interval_secs,
+                                           if task_clone.compare_mode == CompareMode::RawBytes { String::new() } else { format!(" | {}", task_clone.compare_mode.label()) },
+                                           if task_clone.render_js { " | JS rendered" } else { "" }));
                             },
-                            "API Monitor" => {
-                                ui.label(format!("Type: API Monitor | URL: {} | JSONPath: {} | Interval: {}s", 
+                            TaskType::Api => {
+                                ui.label(format!("Type: API Monitor | URL: {} | JSONPath: {} | Interval: {}s{}", 
+                                           task_clone.url, task_clone.selector.lines().collect::<Vec<_>>().join(", "), task_clone.interval_secs,
+                                           if task_clone.array_length_mode { " | Item count" } else { "" }));
+                            },
+                            TaskType::Xml => {
+                                ui.label(format!("Type: XML Monitor | URL: {} | XPath: {} | Interval: {}s", 
                                            task_clone.url, task_clone.selector, task_clone.interval_secs));
                             },
-                            "Hyperliquid" => {
-                                ui.label(format!("Type: Hyperliquid Monitor | Address: {} | Spot: {} | Contract: {} | Interval: {}s", 
+                            TaskType::JsRendered => {
+                                ui.label(format!("Type: JS Rendered Monitor | URL: {} | Selector: {} | Interval: {}s", 
+                                           task_clone.url,
+                                           if task_clone.selector.trim().is_empty() { "(whole page)" } else { task_clone.selector.trim() },
+                                           task_clone.interval_secs));
+                            },
+                            TaskType::Hyperliquid => {
+                                ui.label(format!("Type: Hyperliquid Monitor | Address: {} | Spot: {} | Contract: {} | Funding: {} | Balances: {} | Vaults: {} | Interval: {}s", 
                                            task_clone.address, 
                                            if task_clone.monitor_spot { "Yes" } else { "No" }, 
                                            if task_clone.monitor_contract { "Yes" } else { "No" }, 
+                                           if task_clone.monitor_funding { "Yes" } else { "No" }, 
+                                           if task_clone.monitor_spot_balance { "Yes" } else { "No" }, 
+                                           if task_clone.monitor_vaults { "Yes" } else { "No" }, 
                                            task_clone.interval_secs));
                             },
-                            _ => {}
                         }
                     });
                     
                     ui.add_space(5.0);
                     
+                    // Connectivity check result from the last start
+                    match &reachability {
+                        Some(Ok(())) => {
+                            ui.label(RichText::new("✔ Reachable").color(Color32::GREEN));
+                            ui.add_space(5.0);
+                        }
+                        Some(Err(reason)) => {
+                            ui.label(RichText::new(format!("✖ Unreachable: {}", reason)).color(Color32::RED));
+                            ui.add_space(5.0);
+                        }
+                        None => {}
+                    }
+                    
+                    if let Some(response_time) = response_time {
+                        let slow = task_clone.slow_threshold_ms.is_some_and(|threshold| response_time.as_millis() > u128::from(threshold));
+                        let color = if slow { Color32::YELLOW } else { Color32::GRAY };
+                        ui.label(RichText::new(format!("Last response: {}ms", response_time.as_millis())).color(color));
+                        ui.add_space(5.0);
+                    }
+                    
                     // Operation buttons
                     ui.horizontal(|ui| {
-                        let is_running = matches!(status, TaskStatus::Running);
+                        let is_running = matches!(status, TaskStatus::Running | TaskStatus::Degraded | TaskStatus::Scheduled);
                         
                         if is_running {
                             if ui.button("Stop").clicked() {
@@ -973,10 +2450,40 @@ impl MonitorApp {
                         
                         ui.add_space(5.0);
                         
+                        if ui.button("Check Now").on_hover_text("Poll immediately without waiting for the interval").clicked() {
+                            if is_running {
+                                self.check_signals[i].notify_one();
+                            } else {
+                                self.check_once(i);
+                            }
+                        }
+                        
+                        ui.add_space(5.0);
+                        
+                        if task_clone.task_type == TaskType::Hyperliquid {
+                            if ui.button("Snapshot").on_hover_text("Show the wallet's current positions, open orders and account value").clicked() {
+                                self.take_snapshot(i);
+                            }
+                            ui.add_space(5.0);
+                        }
+                        
+                        if ui.button("Copy CLI").on_hover_text("Copy the command running this task from the command line").clicked() {
+                            ui.output_mut(|output| output.copied_text = task_clone.cli_command());
+                        }
+                        
+                        ui.add_space(5.0);
+                        
                         if ui.button("Edit").clicked() {
                             self.editing_task = task_clone.clone();
+                            self.editing_tags = task_clone.tags.join(", ");
+                            self.editing_ignore_patterns = task_clone.ignore_patterns.join("\n");
+                            self.editing_schedule = task_clone.active_schedule.as_ref().map(ToString::to_string).unwrap_or_default();
+                            self.editing_require_patterns = task_clone.require_patterns.join("\n");
                             self.editing_task_index = Some(i);
                             self.show_edit_task_dialog = true;
+                            self.selector_test = None;
+                            self.response_preview = None;
+                            self.baseline_error = None;
                         }
                         
                         ui.add_space(5.0);
@@ -988,18 +2495,35 @@ impl MonitorApp {
                     });
                 });
             
+            // Clicking anywhere on the card acknowledges its changes
+            if card.response.interact(egui::Sense::click()).clicked() {
+                self.unread_changes[i] = 0;
+            }
+            
             ui.add_space(8.0); // Space between cards
         }
         
-        // 在渲染循环之后执行删除操作
+        // 删除前先确认
         if let Some(index) = delete_index {
-            self.delete_task(index);
+            self.pending_delete = Some(index);
         }
     }
 }
 
 impl eframe::App for MonitorApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut Frame) {
+        // Pick up messages from monitoring tasks
+        self.process_messages();
+        
+        // Write configuration changes that have settled
+        self.flush_config();
+        
+        // Apply the selected theme, following OS changes when set to System
+        self.apply_theme(ctx, frame.info().system_theme);
+        
+        // Remember the window geometry, saved with the configuration on exit
+        self.track_window(&frame.info().window_info);
+        
         // Set overall style
         let mut style = (*ctx.style()).clone();
         style.spacing.item_spacing = egui::vec2(8.0, 8.0);
@@ -1010,6 +2534,26 @@ impl eframe::App for MonitorApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
                 ui.heading(RichText::new("Hyperliquid Monitoring System").size(24.0));
+                if self.configs.notification.dry_run {
+                    ui.label(RichText::new("DRY RUN: notifications are logged, not sent").strong().color(Color32::BLACK).background_color(Color32::YELLOW));
+                }
+                if let Some(mute) = self.notification_sender.mute() {
+                    let remaining = match mute.remaining() {
+                        Some(remaining) => {
+                            let secs = remaining.as_secs();
+                            format!("{}:{:02} left", secs / 60, secs % 60)
+                        }
+                        None => "until unmuted".to_string(),
+                    };
+                    ui.label(RichText::new(format!("🔇 MUTED: notifications are logged, not sent ({})", remaining))
+                        .size(16.0)
+                        .strong()
+                        .color(Color32::WHITE)
+                        .background_color(DEGRADED_COLOR));
+                }
+                if let Some(warning) = &self.config_warning {
+                    ui.label(RichText::new(format!("⚠ {}", warning)).strong().color(Color32::WHITE).background_color(Color32::DARK_RED));
+                }
             });
             
             ui.add_space(10.0);
@@ -1028,14 +2572,36 @@ impl eframe::App for MonitorApp {
             self.draw_edit_task_dialog(ctx);
         }
         
+        if self.bulk_task.is_some() {
+            self.draw_bulk_add_dialog(ctx);
+        }
+        
+        if self.pending_delete.is_some() {
+            self.draw_delete_confirm_dialog(ctx);
+        }
+        
+        if self.viewing_change.is_some() {
+            self.draw_change_detail_dialog(ctx);
+        }
+        
+        if self.history.is_some() {
+            self.draw_history_dialog(ctx);
+        }
+        
         // Refresh UI every second
         ctx.request_repaint_after(Duration::from_secs(1));
     }
     
     // Add on_exit method to stop all tasks when the application exits
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        // Stop all tasks
+        // Stop all tasks, then give the queued notifications, including the ones they held back, time to be sent
         self.stop_all_tasks();
+        if !self.runtime.block_on(self.notification_sender.drain(DRAIN_TIMEOUT)) {
+            eprintln!("Exiting with {} notification(s) not sent", self.notification_sender.len() + self.notification_sender.unsent());
+        }
+        if let Some(handle) = self.notification_handle.take() {
+            handle.abort();
+        }
         
         // Save configuration
         if let Err(e) = self.save_config() {
@@ -1044,134 +2610,366 @@ impl eframe::App for MonitorApp {
     }
 }
 
+/// Per-task settings for the monitoring loop
+#[derive(Clone)]
+struct RunOptions {
+    /// Stop the task after this long
+    max_runtime: Option<Duration>,
+    /// Quiet hours as (start, end) local hours
+    quiet_hours: Option<(u8, u8)>,
+    /// Consecutive failed checks before the task is reported as failing
+    failure_threshold: u32,
+    /// Alert when nothing changed for this long
+    stale_after: Option<Duration>,
+    /// When to check again after each check
+    schedule: Schedule,
+    /// Send a notification for the first check
+    notify_on_start: bool,
+    /// Slots shared with the other tasks' checks
+    limiter: CheckLimiter,
+    /// Local days and hours to poll in, None for all the time
+    active_schedule: Option<ActiveSchedule>,
+    /// Pauses every task after too many failures across tasks
+    breaker: CircuitBreaker,
+    /// Sender through the global notifiers for the circuit breaker opening, None without any
+    breaker_notifications: Option<NotificationSender>,
+}
+
 /// Run monitoring task
 async fn run_monitor_task<M: Monitor + ?Sized>(
     task_index: usize, 
     mut monitor: Box<M>, 
-    notifier: Option<Arc<ServerChanNotifier>>,
-    tx: mpsc::Sender<Message>
+    notifications: Option<NotificationSender>,
+    options: RunOptions,
+    check_now: Arc<Notify>,
+    logger: TaskLogger,
+    tx: mpsc::UnboundedSender<Message>
 ) {
+    let RunOptions { max_runtime, quiet_hours, failure_threshold, stale_after, schedule, notify_on_start, limiter, active_schedule, breaker, breaker_notifications } = options;
     let interval_secs = monitor.interval();
+    let started_at = Instant::now();
+    
+    // Notifications held back during quiet hours, sent when the task stops at the latest
+    let mut held = HeldNotifications::new(notifications.clone(), quiet_hours);
+    
+    // Consecutive failures, alerting once when the threshold is crossed
+    let mut failures = FailureTracker::new(failure_threshold);
+    
+    // Time since the last change, alerting once when the target goes quiet for too long
+    let mut stale = StaleTracker::new(stale_after);
+    
+    // The first successful check captures the initial content
+    let mut initialized = false;
     
     // Send task start message
-    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Running)).await;
+    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Running));
     
-    // Get initial content and send initial notification
-    match monitor.check().await {
-        Ok(Some(change)) => {
-            // This is unusual - we already have a change on first check
-            // Still, we'll treat it as our initial status
-            let initial_message = format!("[{}] Started monitoring: {}", monitor.get_notes(), change.message);
-            
-            // Send notification about monitoring start with initial content
-            if let Some(notifier) = &notifier {
-                if let Err(e) = notifier.send(&initial_message, &change.details).await {
-                    let _ = tx.send(Message::Log(
-                        format!("Failed to send initial notification: {}", e),
-                        Color32::RED
-                    )).await;
-                } else {
-                    let _ = tx.send(Message::Log(
-                        format!("Initial notification sent: {}", initial_message),
-                        Color32::LIGHT_BLUE
-                    )).await;
-                }
-            }
-            
-            // Also send to our message system
-            let _ = tx.send(Message::ChangeDetected(task_index, change.clone())).await;
-        },
-        Ok(None) => {
-            // Normal case - no change but we have initial content
-            // Get task name and use it in initial notification
-            let task_note = &monitor.get_name();
-            let initial_message = format!("[{}] Started monitoring: {}", monitor.get_notes(), task_note);
-            let details = format!("Initial content captured. Will notify when changes are detected.");
-            
-            // Send notification about monitoring start
-            if let Some(notifier) = &notifier {
-                if let Err(e) = notifier.send(&initial_message, &details).await {
-                    let _ = tx.send(Message::Log(
-                        format!("Failed to send initial notification: {}", e),
-                        Color32::RED
-                    )).await;
-                } else {
-                    let _ = tx.send(Message::Log(
-                        format!("Initial notification sent: {}", initial_message),
-                        Color32::LIGHT_BLUE
-                    )).await;
-                }
-            }
-            
-            // Log the start
-            let _ = tx.send(Message::Log(
-                format!("Task #{} initialized with initial content", task_index + 1),
-                Color32::LIGHT_GREEN
-            )).await;
-        },
-        Err(e) => {
-            // Error on first check
-            let _ = tx.send(Message::TaskStatusChanged(
-                task_index,
-                TaskStatus::Error
-            )).await;
-            
-            let _ = tx.send(Message::Log(
-                format!("Error getting initial content: {}", e),
-                Color32::RED
-            )).await;
-            
-            // Wait for a while before retrying
-            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
-        },
+    // Catch misconfigured targets before the first check
+    let reachability = monitor.preflight().await.map_err(|e| e.to_string());
+    match &reachability {
+        Ok(()) => send_log(&tx, &logger, Level::Info, "Reachable", Color32::LIGHT_GREEN),
+        Err(e) => send_log(&tx, &logger, Level::Error, &format!("Unreachable: {}", e), Color32::RED),
     }
+    let _ = tx.send(Message::Reachability(task_index, reachability));
     
     // Main monitoring loop
     loop {
-        match monitor.check().await {
-            Ok(Some(change)) => {
-                // Send change detection message
-                let _ = tx.send(Message::ChangeDetected(task_index, change.clone())).await;
+        // Deliver notifications held back once quiet hours are over
+        let released = held.release();
+        if released > 0 {
+            send_log(&tx, &logger, Level::Info, &format!("Quiet hours ended, sent {} held notification(s)", released), Color32::LIGHT_BLUE);
+        }
+        
+        // Stop once the configured monitoring window has elapsed
+        if let Some(max_runtime) = max_runtime {
+            if started_at.elapsed() >= max_runtime {
+                let title = format!("[{}] Monitoring window ended", monitor.get_notes());
+                let details = format!("{} stopped after running for {} seconds.", monitor.get_name(), max_runtime.as_secs());
+                queue_notification(&notifications, ChangeKind::Stopped, &title, &details);
+                
+                send_log(&tx, &logger, Level::Info, "Reached its maximum runtime", Color32::YELLOW);
+                let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Idle));
+                return;
+            }
+        }
+        
+        // Sleep through the hours outside the active schedule, "Check now" still checks
+        if let Some(wait) = active_schedule.as_ref().and_then(|active| active.until_active(chrono::Local::now().naive_local())) {
+            let wait = max_runtime.map_or(wait, |max_runtime| wait.min(max_runtime.saturating_sub(started_at.elapsed())));
+            let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Scheduled));
+            send_log(&tx, &logger, Level::Info, &format!("Outside its active schedule, next check in {}s", wait.as_secs()), Color32::GRAY);
+            let paused_at = Instant::now();
+            let woken = tokio::select! {
+                _ = tokio::time::sleep(wait) => false,
+                _ = check_now.notified() => true,
+            };
+            stale.pause(paused_at.elapsed());
+            let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Running));
+            if !woken {
+                continue;
+            }
+        }
+        
+        // Every task waits while the circuit breaker is open
+        if let Some(wait) = breaker.open_for() {
+            send_log(&tx, &logger, Level::Debug, &format!("Circuit open, polling resumes in {}s", wait.as_secs()), Color32::GRAY);
+            tokio::time::sleep(wait).await;
+            continue;
+        }
+        
+        logger.event(EventKind::CheckStarted, "");
+        let result = match limiter.check(monitor.as_mut()).await {
+            Ok(Some(change)) if change.kind == ChangeKind::Error => Err(format!("{}\n{}", change.message, change.details)),
+            Ok(change) => Ok(change),
+            Err(e) => Err(e.to_string()),
+        };
+        logger.event(EventKind::CheckCompleted, check_outcome(&result));
+        if let Some(elapsed) = monitor.last_response_time() {
+            let _ = tx.send(Message::ResponseTimed(task_index, elapsed));
+        }
+        
+        // Alerts raised besides the check's change, like persistently slow responses, are notified on their own
+        if let Some(alert) = monitor.take_alert() {
+            send_log(&tx, &logger, Level::Info, &alert.message, Color32::YELLOW);
+            let title = format!("[{}] {}", monitor.get_notes(), alert.message);
+            if !held.hold(alert.kind, &title, &alert.details) {
+                queue_notification(&notifications, alert.kind, &title, &alert.details);
+            }
+        }
+        if breaker.record(result.is_err()) {
+            let details = format!(
+                "{}, the last one by {}; all polling is paused for {}s and then resumes.",
+                breaker.threshold(),
+                monitor.get_name(),
+                breaker.cooldown().as_secs(),
+            );
+            send_log(&tx, &logger, Level::Error, &format!("Circuit opened: {}", details), Color32::RED);
+            let title = format!("[{}] Circuit opened: polling paused", monitor.get_notes());
+            queue_notification(&breaker_notifications, ChangeKind::Error, &title, &details);
+        }
+        
+        match result {
+            Ok(change) => {
+                let had_failures = failures.consecutive() > 0;
+                if failures.record_success() {
+                    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Running));
+                    send_log(&tx, &logger, Level::Info, "Recovered", Color32::LIGHT_GREEN);
+                    
+                    let title = format!("[{}] Task recovered", monitor.get_notes());
+                    let details = format!("{} is checking successfully again.", monitor.get_name());
+                    queue_notification(&notifications, ChangeKind::Recovered, &title, &details);
+                } else if had_failures {
+                    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Running));
+                }
                 
-                // Send notification with notes in title
-                if let Some(notifier) = &notifier {
+                // Anything but a start on the first check, like a deviation from a baseline, is reported as a change
+                if !initialized && change.as_ref().is_some_and(|change| change.kind != ChangeKind::Started) {
+                    initialized = true;
+                }
+                
+                if !initialized {
+                    initialized = true;
+                    stale.record_change(Instant::now());
+                    
+                    // Announce the start, with the initial content when the monitor reports it
+                    let (initial_message, details) = match &change {
+                        Some(change) => (
+                            format!("[{}] Started monitoring: {}", monitor.get_notes(), change.message),
+                            change.details.clone(),
+                        ),
+                        None => (
+                            format!("[{}] Started monitoring: {}", monitor.get_notes(), monitor.get_name()),
+                            "Initial content captured. Will notify when changes are detected.".to_string(),
+                        ),
+                    };
+                    
+                    if !notify_on_start {
+                        send_log(&tx, &logger, Level::Debug, "Start notification turned off", Color32::GRAY);
+                    } else if held.hold(ChangeKind::Started, &initial_message, &details) {
+                        send_log(&tx, &logger, Level::Debug, "Notification held until quiet hours end", Color32::GRAY);
+                    } else if queue_notification(&notifications, ChangeKind::Started, &initial_message, &details) {
+                        send_log(&tx, &logger, Level::Info, &format!("Initial notification queued: {}", initial_message), Color32::LIGHT_BLUE);
+                    }
+                    
+                    match change {
+                        Some(change) => {
+                            let _ = tx.send(Message::ChangeDetected(task_index, change));
+                        }
+                        None => send_log(&tx, &logger, Level::Info, "Initialized with initial content", Color32::LIGHT_GREEN),
+                    }
+                } else if let Some(change) = change {
+                    stale.record_change(Instant::now());
+                    logger.event(EventKind::ChangeDetected, &change.message);
+                    
+                    // Send change detection message
+                    let _ = tx.send(Message::ChangeDetected(task_index, change.clone()));
+                    
+                    // Send notification with notes in title
                     let notification_title = format!("[{}] {}", monitor.get_notes(), change.message);
-                    if let Err(e) = notifier.send(&notification_title, &change.details).await {
-                        let _ = tx.send(Message::Log(
-                            format!("Failed to send notification: {}", e),
-                            Color32::RED
-                        )).await;
+                    if held.hold(change.kind, &notification_title, &change.details) {
+                        send_log(&tx, &logger, Level::Debug, "Notification held until quiet hours end", Color32::GRAY);
+                    } else {
+                        queue_notification(&notifications, change.kind, &notification_title, &change.details);
+                    }
+                } else if stale.record_unchanged(Instant::now()) {
+                    send_log(&tx, &logger, Level::Info, &format!("No changes for {} minutes", stale.window_minutes()), Color32::YELLOW);
+                    
+                    let title = format!("[{}] No updates for {} minutes", monitor.get_notes(), stale.window_minutes());
+                    let details = format!("{} has not changed for {} minutes, the target may be frozen.", monitor.get_name(), stale.window_minutes());
+                    if !held.hold(ChangeKind::Stale, &title, &details) {
+                        queue_notification(&notifications, ChangeKind::Stale, &title, &details);
                     }
+                } else {
+                    // No change
+                    send_log(&tx, &logger, Level::Debug, "Detected no changes", Color32::GRAY);
                 }
             },
-            Ok(None) => {
-                // No change
-                let _ = tx.send(Message::Log(
-                    format!("Task #{} detected no changes", task_index + 1),
-                    Color32::GRAY
-                )).await;
-            },
-            Err(_e) => {
-                // Send error message
-                let _ = tx.send(Message::TaskStatusChanged(
-                    task_index,
-                    TaskStatus::Error
-                )).await;
-                
-                // Wait for a while before retrying
-                tokio::time::sleep(Duration::from_secs(interval_secs)).await;
-                continue;
+            Err(e) => {
+                send_log(&tx, &logger, Level::Error, &format!("Error during monitoring: {}", e), Color32::RED);
+                logger.event(EventKind::Error, &e);
+                
+                // Report the task as failing once, not on every retry
+                if failures.record_failure() {
+                    let _ = tx.send(Message::TaskStatusChanged(
+                        task_index,
+                        TaskStatus::Error
+                    ));
+                    
+                    let title = format!("[{}] Task failing", monitor.get_notes());
+                    let details = format!("{} failed {} checks in a row.\n\nLast error:\n{}", monitor.get_name(), failures.consecutive(), e);
+                    queue_notification(&notifications, ChangeKind::Error, &title, &details);
+                } else if failures.consecutive() == 1 {
+                    // Still retrying, flag the task before it is reported as failing
+                    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Degraded));
+                }
             },
         }
         
-        // Wait for next check
-        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        // Wait for next check, or check right away when asked to
+        tokio::select! {
+            _ = tokio::time::sleep(next_sleep(interval_secs, schedule, started_at, max_runtime)) => {}
+            _ = check_now.notified() => send_log(&tx, &logger, Level::Info, "Checking now", Color32::LIGHT_BLUE),
+        }
+    }
+}
+
+/// Report the changes a task's push stream delivers, reconnecting with backoff when it drops
+async fn run_stream_task(
+    task_index: usize,
+    mut stream: Box<dyn StreamMonitor>,
+    notifications: Option<NotificationSender>,
+    options: RunOptions,
+    logger: TaskLogger,
+    tx: mpsc::UnboundedSender<Message>
+) {
+    let RunOptions { quiet_hours, failure_threshold, notify_on_start, .. } = options;
+    send_log(&tx, &logger, Level::Info, &format!("Starting stream: {}", stream.get_name()), Color32::LIGHT_BLUE);
+    
+    // Notifications held back during quiet hours, sent when the task stops at the latest
+    let mut held = HeldNotifications::new(notifications.clone(), quiet_hours);
+    
+    // Consecutive failed connections, alerting once when the threshold is crossed
+    let mut failures = FailureTracker::new(failure_threshold);
+    let mut backoff = reconnect_backoff();
+    
+    loop {
+        // Deliver notifications held back once quiet hours are over
+        let released = held.release();
+        if released > 0 {
+            send_log(&tx, &logger, Level::Info, &format!("Quiet hours ended, sent {} held notification(s)", released), Color32::LIGHT_BLUE);
+        }
+        
+        // Wake up now and then while notifications are held, the stream may stay quiet for hours
+        let result = tokio::select! {
+            result = stream.next_change() => result,
+            _ = tokio::time::sleep(Duration::from_secs(60)), if !held.is_empty() => continue,
+        };
+        
+        match result {
+            Ok(change) => {
+                backoff.reset();
+                let had_failures = failures.consecutive() > 0;
+                if failures.record_success() {
+                    send_log(&tx, &logger, Level::Info, "Stream recovered", Color32::LIGHT_GREEN);
+                    let title = format!("[{}] Stream recovered", stream.get_notes());
+                    let details = format!("{} is receiving events again.", stream.get_name());
+                    queue_notification(&notifications, ChangeKind::Recovered, &title, &details);
+                }
+                if had_failures {
+                    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Running));
+                }
+                
+                let Some(change) = change else {
+                    continue;
+                };
+                if change.kind != ChangeKind::Started {
+                    logger.event(EventKind::ChangeDetected, &change.message);
+                }
+                let _ = tx.send(Message::ChangeDetected(task_index, change.clone()));
+                
+                let notification_title = format!("[{}] {}", stream.get_notes(), change.message);
+                if change.kind == ChangeKind::Started && !notify_on_start {
+                    send_log(&tx, &logger, Level::Debug, "Start notification turned off", Color32::GRAY);
+                } else if held.hold(change.kind, &notification_title, &change.details) {
+                    send_log(&tx, &logger, Level::Debug, "Notification held until quiet hours end", Color32::GRAY);
+                } else {
+                    queue_notification(&notifications, change.kind, &notification_title, &change.details);
+                }
+            }
+            Err(e) => {
+                let delay = backoff.next_delay();
+                send_log(&tx, &logger, Level::Error, &format!("Stream error, reconnection attempt {} in {:?}: {}", backoff.attempts(), delay, e), Color32::RED);
+                logger.event(EventKind::Error, &e.to_string());
+                
+                // Flag the task while retrying, and report it failing once reconnecting keeps failing
+                if failures.record_failure() {
+                    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Error));
+                    let title = format!("[{}] Stream failing", stream.get_notes());
+                    let details = format!("{} failed to connect {} times in a row.\n\nLast error:\n{}", stream.get_name(), failures.consecutive(), e);
+                    queue_notification(&notifications, ChangeKind::Error, &title, &details);
+                } else if failures.consecutive() == 1 {
+                    let _ = tx.send(Message::TaskStatusChanged(task_index, TaskStatus::Degraded));
+                }
+                
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Queue a notification if a notifier is configured and notifications aren't muted; returns whether it was queued
+fn queue_notification(notifications: &Option<NotificationSender>, kind: ChangeKind, title: &str, details: &str) -> bool {
+    match notifications {
+        Some(notifications) => notifications.send(kind, title, details),
+        None => false,
+    }
+}
+
+/// Combine built notifiers into one, None when there are none
+fn combined_notifier(notifiers: Vec<Box<dyn Notifier>>) -> Option<Arc<dyn Notifier>> {
+    if notifiers.is_empty() {
+        return None;
+    }
+    Some(Arc::new(CompositeNotifier::new(notifiers)))
+}
+
+/// Log a task message and forward it to the UI log panel
+fn send_log(tx: &mpsc::UnboundedSender<Message>, logger: &TaskLogger, level: Level, message: &str, color: Color32) {
+    let _ = tx.send(Message::Log(logger.log(level, message), color));
+}
+
+/// Time to sleep before the next check, jittered and cut short by the end of the monitoring window
+fn next_sleep(interval_secs: u64, schedule: Schedule, started_at: Instant, max_runtime: Option<Duration>) -> Duration {
+    let interval = schedule.next_sleep(Duration::from_secs(interval_secs));
+    match max_runtime {
+        Some(max_runtime) => interval.min(max_runtime.saturating_sub(started_at.elapsed())),
+        None => interval,
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
-    // Initialize logger with debug level
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug"))
+    // Initialize logger at info level, RUST_LOG overrides it
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format_timestamp(None)
         .format_level(true)
         .format_target(false)
@@ -1180,8 +2978,14 @@ fn main() -> Result<(), eframe::Error> {
     info!("Starting Hyperliquid Monitor GUI...");
     debug!("Debug logging enabled for GUI");
     
+    // Restore the window geometry from the last session
+    let window = Configs::load(CONFIG_PATH).ok().and_then(|configs| configs.window);
     let options = eframe::NativeOptions {
-        initial_window_size: Some(egui::vec2(800.0, 600.0)),
+        initial_window_size: Some(window.map_or(egui::vec2(800.0, 600.0), |window| egui::vec2(window.width, window.height))),
+        initial_window_pos: window.and_then(|window| Some(egui::pos2(window.x?, window.y?))),
+        maximized: window.is_some_and(|window| window.maximized),
+        // The theme is applied by the app from its saved configuration
+        follow_system_theme: false,
         ..Default::default()
     };
     
@@ -0,0 +1,21 @@
+//! Monitors, notifiers and configuration behind the CLI and GUI binaries, usable as a library
+//! to embed monitoring in another application
+//!
+//! The main types are re-exported at the crate root: build a monitor, call [`Monitor::check`]
+//! on your own schedule and hand the returned [`Change`]s to a [`Notifier`].
+
+pub mod config;
+pub mod monitors;
+pub mod notifiers;
+pub mod utils;
+
+pub use monitors::{Change, ChangeKind, Monitor};
+pub use monitors::api_monitor::{ApiMonitor, ApiMonitorBuilder};
+pub use monitors::fetcher::HttpMonitor;
+pub use monitors::hyperliquid_monitor::{HyperliquidMonitor, HyperliquidMonitorBuilder};
+pub use monitors::js_monitor::JsMonitor;
+pub use monitors::static_monitor::{StaticMonitor, StaticMonitorBuilder};
+pub use monitors::stream_monitor::{HyperliquidFillStream, StreamMonitor};
+pub use monitors::xml_monitor::XmlMonitor;
+pub use notifiers::{CompositeNotifier, Notifier};
+pub use notifiers::server_chan::ServerChanNotifier;
@@ -1,36 +1,148 @@
-use std::path::PathBuf;
-use clap::{Parser, Subcommand};
-use log::{info, error, debug};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use clap::{ArgAction, ArgGroup, Parser, Subcommand, ValueEnum};
+use log::{info, error, debug, warn};
 use anyhow::Result;
 use dotenv::dotenv;
 
-mod monitors;
-mod notifiers;
-mod utils;
+use hyperliquid_monitor::{config, monitors, notifiers, utils};
+mod status;
+mod metrics;
 
+use config::{Configs, NotificationConfig, TaskConfig, TaskType};
 use monitors::{
-    static_monitor::StaticMonitor,
-    hyperliquid_monitor::HyperliquidMonitor,
-    Monitor
+    static_monitor::{CompareMode, StaticMonitor},
+    hyperliquid_monitor::{HyperliquidMonitor, DEFAULT_VAULT_CHANGE_PCT},
+    stream_monitor::{reconnect_backoff, HyperliquidFillStream, StreamMonitor},
+    ChangeKind,
+    CheckLimiter,
+    CircuitBreaker,
+    Monitor,
+    CIRCUIT_BREAKER_ID,
 };
-use notifiers::server_chan::ServerChanNotifier;
-use notifiers::Notifier;
+use notifiers::{build_notifiers, build_task_notifiers, CompositeNotifier, FailureTracker, StaleTracker};
+use notifiers::history::{NotificationHistory, HISTORY_PATH};
+use notifiers::pending::{PendingNotifications, PENDING_PATH};
+use notifiers::queue::{HeldNotifications, NotificationQueue, NotificationSender, DRAIN_TIMEOUT, QUEUE_CAPACITY};
+use status::{CheckOutcome, StatusBoard};
+use metrics::Metrics;
+use utils::{ActiveSchedule, Schedule, TaskLogger};
+use utils::events::{check_outcome, EventKind};
+
+/// Default configuration file, shared with the GUI
+const DEFAULT_CONFIG_PATH: &str = "config.json";
 
 /// A tool for monitoring website data changes and Hyperliquid user transactions
 #[derive(Parser)]
 #[command(name = "hyperliquid_monitor")]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    /// Configuration file path
+    /// Configuration file path (defaults to config.json)
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
 
+    /// Log notifications instead of sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Log level: off, error, warn, info, debug or trace; RUST_LOG is used when not given, info when neither is set
+    #[arg(long, global = true, value_name = "LEVEL")]
+    log_level: Option<log::LevelFilter>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Run all enabled tasks from the configuration file
+    Run {
+        /// Serve /healthz and /status on this port
+        #[arg(long)]
+        status_port: Option<u16>,
+        
+        /// Serve Prometheus /metrics on this port
+        #[arg(long)]
+        metrics_port: Option<u16>,
+    },
+    /// Print the tasks in the configuration file
+    ListTasks {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        output: OutputFormat,
+    },
+    /// Check the configuration file and every task, exiting with an error if anything is invalid
+    ValidateConfig,
+    /// Add a task to the configuration file
+    AddTask {
+        /// Task name
+        #[arg(short, long)]
+        name: String,
+
+        /// Task type: static, api, xml, js or hyperliquid
+        #[arg(short = 't', long = "type")]
+        task_type: TaskType,
+
+        /// URL to monitor (all types but hyperliquid)
+        #[arg(short, long, default_value = "")]
+        url: String,
+
+        /// CSS selector, JSONPath (one per line) or XPath, depending on the type
+        #[arg(short, long, default_value = "")]
+        selector: String,
+
+        /// Wallet address (hyperliquid)
+        #[arg(short, long, default_value = "")]
+        address: String,
+
+        /// Monitoring interval (seconds), defaults to the configured default interval
+        #[arg(short, long)]
+        interval: Option<u64>,
+
+        /// Notes included in notifications
+        #[arg(long, default_value = "")]
+        notes: String,
+
+        /// Comma-separated tags, e.g. "prices, wallets"
+        #[arg(long, default_value = "")]
+        tags: String,
+
+        /// Monitor spot trading (hyperliquid)
+        #[arg(long)]
+        spot: bool,
+
+        /// Monitor contract trading (hyperliquid)
+        #[arg(long)]
+        contract: bool,
+
+        /// Monitor funding payments (hyperliquid)
+        #[arg(long)]
+        funding: bool,
+
+        /// Monitor spot token balances (hyperliquid)
+        #[arg(long)]
+        spot_balance: bool,
+
+        /// Monitor vault deposits, withdrawals and equity (hyperliquid)
+        #[arg(long)]
+        vaults: bool,
+
+        /// Add the task disabled
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// Remove a task from the configuration file
+    #[command(group(ArgGroup::new("task").required(true).args(["index", "name"])))]
+    RemoveTask {
+        /// Index of the task, as shown by list-tasks
+        #[arg(long)]
+        index: Option<usize>,
+
+        /// Name of the task
+        #[arg(long)]
+        name: Option<String>,
+    },
     /// Monitor static webpage changes
     Static {
         /// Webpage URL to monitor
@@ -44,24 +156,88 @@ enum Commands {
         /// Monitoring interval (seconds)
         #[arg(short, long, default_value_t = 300)]
         interval: u64,
+
+        /// Render the page in headless Chrome before selecting
+        #[arg(long)]
+        render_js: bool,
+
+        /// Raw Cookie header sent with each request, e.g. "session=abc123"
+        #[arg(long)]
+        cookies: Option<String>,
+
+        /// Regex removed from the content before comparing, can be repeated
+        #[arg(long = "ignore")]
+        ignore_patterns: Vec<String>,
+
+        /// Regex that must match the changed text for a notification, can be repeated
+        #[arg(long = "require")]
+        require_patterns: Vec<String>,
+
+        /// Regex capturing a number to track instead of the content, e.g. "Price:\s*([\d,.]+)"
+        #[arg(long)]
+        numeric_regex: Option<String>,
+
+        /// How the content is compared: raw_bytes, normalized_text (text only), selector or hash
+        #[arg(long, default_value = "raw_bytes")]
+        compare: CompareMode,
     },
     /// Monitor Hyperliquid user transactions
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Hyperliquid {
+        #[command(subcommand)]
+        action: Option<HyperliquidAction>,
+
         /// Wallet address to monitor
-        #[arg(short, long)]
-        address: String,
+        #[arg(short, long, required = true)]
+        address: Option<String>,
 
         /// Monitoring interval (seconds)
         #[arg(short, long, default_value_t = 120)]
         interval: u64,
 
-        /// Whether to monitor spot trading
-        #[arg(long, default_value_t = true)]
+        /// Whether to monitor spot trading, `--spot false` to skip it
+        #[arg(long, default_value_t = true, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
         spot: bool,
 
-        /// Whether to monitor contract trading
-        #[arg(long, default_value_t = true)]
+        /// Whether to monitor contract trading, `--contract false` to skip it
+        #[arg(long, default_value_t = true, action = ArgAction::Set, num_args = 0..=1, default_missing_value = "true")]
         contract: bool,
+
+        /// Whether to monitor funding payments
+        #[arg(long)]
+        funding: bool,
+
+        /// Report spot token balance changes, e.g. deposits, withdrawals and transfers
+        #[arg(long)]
+        spot_balance: bool,
+
+        /// Report vault deposits, withdrawals and equity changes
+        #[arg(long)]
+        vaults: bool,
+
+        /// Report fills from this many recent minutes on the first check
+        #[arg(long, default_value_t = 0)]
+        backfill_minutes: u64,
+
+        /// Only report position changes whose size moved at least this percent
+        #[arg(long, default_value_t = 0.0)]
+        position_threshold: f64,
+
+        /// Only report vault equity moves of at least this percent, deposits and withdrawals are always reported
+        #[arg(long, default_value_t = DEFAULT_VAULT_CHANGE_PCT)]
+        vault_threshold: f64,
+
+        /// Alert when a position's unrealized PnL moves at least this many USD
+        #[arg(long)]
+        pnl_threshold: Option<f64>,
+
+        /// Receive fills over the WebSocket API as they happen instead of polling for them
+        #[arg(long)]
+        stream: bool,
+
+        /// Explorer linked from notifications: hyperdash, hypurrscan or a URL containing {address}
+        #[arg(long, default_value = "hyperdash")]
+        explorer: String,
     },
     /// Monitor API data changes
     Api {
@@ -69,23 +245,87 @@ enum Commands {
         #[arg(short, long)]
         url: String,
 
-        /// JSONPath selector
+        /// JSONPath selector, repeat to monitor several fields of the same response
+        #[arg(short, long, required = true)]
+        selector: Vec<String>,
+
+        /// Monitoring interval (seconds)
+        #[arg(short, long, default_value_t = 60)]
+        interval: u64,
+
+        /// Raw Cookie header sent with each request, e.g. "session=abc123"
+        #[arg(long)]
+        cookies: Option<String>,
+
+        /// Only notify when a value starts meeting this condition, e.g. "== 0" or ">= 100"
+        #[arg(long, value_parser = monitors::api_monitor::parse_alert_condition)]
+        alert_when: Option<(monitors::api_monitor::AlertOperator, f64)>,
+
+        /// Compare how many items the selectors match instead of their content, e.g. for new listings
+        #[arg(long)]
+        array_length: bool,
+    },
+    /// Monitor a value selected by XPath from an XML document
+    Xml {
+        /// Document URL to monitor
+        #[arg(short, long)]
+        url: String,
+
+        /// XPath expression
         #[arg(short, long)]
+        xpath: String,
+
+        /// Monitoring interval (seconds)
+        #[arg(short, long, default_value_t = 300)]
+        interval: u64,
+    },
+    /// Monitor a page rendered by JavaScript, loaded in headless Chrome
+    Js {
+        /// Page URL to monitor
+        #[arg(short, long)]
+        url: String,
+
+        /// CSS selector limiting the monitored text (defaults to the whole page)
+        #[arg(short, long, default_value = "")]
         selector: String,
 
         /// Monitoring interval (seconds)
-        #[arg(short, long, default_value_t = 60)]
+        #[arg(short, long, default_value_t = 300)]
         interval: u64,
     },
 }
 
+/// One-off Hyperliquid commands
+#[derive(Subcommand)]
+enum HyperliquidAction {
+    /// Print the wallet's current positions, open orders and account value
+    Snapshot {
+        /// Wallet address
+        #[arg(short, long)]
+        address: String,
+    },
+}
+
+/// How `list-tasks` prints the tasks
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Aligned columns for reading
+    Table,
+    /// JSON for scripts
+    Json,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize environment variables and logging
     dotenv().ok();
+    let cli = Cli::parse();
     
-    // Initialize logger with debug level
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug"))
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if let Some(level) = cli.log_level {
+        logger.filter_level(level);
+    }
+    logger
         .format_timestamp(None)
         .format_level(true)
         .format_target(false)
@@ -94,30 +334,132 @@ async fn main() -> Result<()> {
     info!("Starting Hyperliquid Monitor...");
     debug!("Debug logging enabled");
     
-    let cli = Cli::parse();
-    
-    // If a configuration file is provided, load settings from it
-    if let Some(config_path) = cli.config {
-        info!("Loading settings from config file: {:?}", config_path);
-        // TODO: Implement loading settings from config file
-    }
+    let config_path = cli.config.unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
     
     // Execute the appropriate monitoring task based on command line arguments
     match &cli.command {
-        Some(Commands::Static { url, selector, interval }) => {
+        Some(Commands::Run { status_port, metrics_port }) => {
+            info!("Loading settings from config file: {:?}", config_path);
+            let mut configs = Configs::load(&config_path)?;
+            configs.notification.dry_run |= cli.dry_run;
+            utils::set_display_timezone(configs.display_timezone()?);
+            run_tasks(configs, *status_port, *metrics_port).await?;
+        }
+        Some(Commands::ListTasks { output }) => {
+            let configs = Configs::load(&config_path)?;
+            print!("{}", list_tasks(&configs.tasks, *output)?);
+        }
+        Some(Commands::ValidateConfig) => {
+            validate_config_file(&config_path)?;
+        }
+        Some(Commands::AddTask { name, task_type, url, selector, address, interval, notes, tags, spot, contract, funding, spot_balance, vaults, disabled }) => {
+            let mut configs = load_or_default(&config_path)?;
+            let mut task = configs.new_task();
+            task.name = name.clone();
+            task.task_type = *task_type;
+            task.url = url.clone();
+            task.selector = selector.clone();
+            task.address = address.clone();
+            task.interval_secs = interval.unwrap_or(task.interval_secs);
+            task.notes = notes.clone();
+            task.tags = config::parse_tags(tags);
+            // Without any market flag a Hyperliquid task keeps the default markets
+            if *spot || *contract || *funding || *spot_balance || *vaults {
+                task.monitor_spot = *spot;
+                task.monitor_contract = *contract;
+                task.monitor_funding = *funding;
+                task.monitor_spot_balance = *spot_balance;
+                task.monitor_vaults = *vaults;
+            }
+            task.enabled = !*disabled;
+            
+            let errors = task.validate();
+            if !errors.is_empty() {
+                return Err(anyhow::anyhow!("Invalid task: {}", errors.join("; ")));
+            }
+            if let Some(warning) = task.interval_warning() {
+                warn!("Task '{}': {}", task.name, warning);
+            }
+            
+            configs.tasks.push(task);
+            configs.save(&config_path)?;
+            println!("Added task {}: {}", configs.tasks.len() - 1, name);
+        }
+        Some(Commands::RemoveTask { index, name }) => {
+            let mut configs = Configs::load(&config_path)?;
+            let index = match (index, name) {
+                (Some(index), _) if *index < configs.tasks.len() => *index,
+                (Some(index), _) => return Err(anyhow::anyhow!("No task at index {}, there are {} tasks", index, configs.tasks.len())),
+                (None, Some(name)) => configs.find_task(name)?,
+                (None, None) => unreachable!("clap requires an index or a name"),
+            };
+            
+            let task = configs.tasks.remove(index);
+            configs.save(&config_path)?;
+            println!("Removed task {}: {}", index, task.name);
+        }
+        Some(Commands::Static { url, selector, interval, render_js, cookies, ignore_patterns, require_patterns, numeric_regex, compare }) => {
             info!("Starting static webpage monitoring: {}", url);
-            let monitor = StaticMonitor::new(url, selector, *interval);
-            run_monitor(monitor).await?;
+            let monitor = StaticMonitor::builder(url, selector)
+                .interval(*interval)
+                .render_js(*render_js)
+                .cookies(cookies.clone())
+                .ignore_patterns(ignore_patterns)
+                .require_patterns(require_patterns)
+                .numeric_regex(numeric_regex.as_deref())
+                .compare_mode(*compare)
+                .build()?;
+            run_single(Box::new(monitor), None, cli.dry_run).await?;
+        }
+        Some(Commands::Hyperliquid { action: Some(HyperliquidAction::Snapshot { address }), .. }) => {
+            let snapshot = HyperliquidMonitor::builder(address).build()?.snapshot().await?;
+            println!("{}\n\n{}", snapshot.message, snapshot.details);
         }
-        Some(Commands::Hyperliquid { address, interval, spot, contract }) => {
+        Some(Commands::Hyperliquid { action: None, address: Some(address), interval, spot, contract, funding, spot_balance, vaults, backfill_minutes, position_threshold, vault_threshold, pnl_threshold, stream, explorer }) => {
             info!("Starting Hyperliquid user transaction monitoring: {}", address);
-            let monitor = HyperliquidMonitor::new(address, *interval, *spot, *contract);
-            run_monitor(monitor).await?;
+            // Spot fills come from the stream when it is on
+            let monitor = HyperliquidMonitor::builder(address)
+                .interval(*interval)
+                .spot(*spot && !*stream)
+                .contract(*contract)
+                .monitor_funding(*funding)
+                .monitor_spot_balance(*spot_balance)
+                .monitor_vaults(*vaults)
+                .backfill_minutes(*backfill_minutes)
+                .position_change_threshold(*position_threshold)
+                .vault_change_threshold(*vault_threshold)
+                .pnl_alert_threshold(*pnl_threshold)
+                .explorer(explorer)
+                .build()?;
+            // Perpetual trades are already reported by position polling
+            let fills: Option<Box<dyn StreamMonitor>> = if *stream && *spot {
+                Some(Box::new(HyperliquidFillStream::new(address, true, false)))
+            } else {
+                None
+            };
+            run_single(Box::new(monitor), fills, cli.dry_run).await?;
         }
-        Some(Commands::Api { url, selector, interval }) => {
+        Some(Commands::Hyperliquid { action: None, address: None, .. }) => unreachable!("clap requires an address"),
+        Some(Commands::Api { url, selector, interval, cookies, alert_when, array_length }) => {
             info!("Starting API data monitoring: {}", url);
-            let monitor = monitors::api_monitor::ApiMonitor::new(url.clone(), selector.clone(), *interval);
-            run_monitor(monitor).await?;
+            let monitor = monitors::api_monitor::ApiMonitor::builder(url, &selector.join("\n"))
+                .interval(*interval)
+                .cookies(cookies.clone())
+                .alert_when(*alert_when)
+                .array_length(*array_length)
+                .build();
+            run_single(Box::new(monitor), None, cli.dry_run).await?;
+        }
+        Some(Commands::Xml { url, xpath, interval }) => {
+            info!("Starting XML document monitoring: {}", url);
+            monitors::xml_monitor::validate_xpath(xpath)?;
+            let monitor = monitors::xml_monitor::XmlMonitor::new(url, xpath, *interval);
+            run_single(Box::new(monitor), None, cli.dry_run).await?;
+        }
+        Some(Commands::Js { url, selector, interval }) => {
+            info!("Starting JS-rendered page monitoring: {}", url);
+            let monitor = monitors::js_monitor::JsMonitor::new(url, selector, *interval);
+            run_single(Box::new(monitor), None, cli.dry_run).await?;
         }
         None => {
             // If no subcommand is specified, display help information
@@ -128,68 +470,752 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn run_monitor<M: Monitor>(mut monitor: M) -> Result<()> {
-    // Create notification service
-    let server_chan_key = std::env::var("SERVER_CHAN_KEY").unwrap_or_default();
-    let notifier = ServerChanNotifier::new(&server_chan_key);
+/// Load the configuration file, or start an empty configuration when there is none yet
+fn load_or_default(path: &std::path::Path) -> Result<Configs> {
+    if path.exists() {
+        Configs::load(path)
+    } else {
+        info!("Creating configuration file {:?}", path);
+        Ok(Configs::default())
+    }
+}
+
+/// Render the tasks as a table with a summary line, or as JSON
+fn list_tasks(tasks: &[TaskConfig], output: OutputFormat) -> Result<String> {
+    // Task types in order of first appearance, with their counts
+    let mut counts: Vec<(config::TaskType, usize)> = Vec::new();
+    for task in tasks {
+        match counts.iter_mut().find(|(task_type, _)| *task_type == task.task_type) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((task.task_type, 1)),
+        }
+    }
+    
+    if output == OutputFormat::Json {
+        let tasks: Vec<serde_json::Value> = tasks
+            .iter()
+            .enumerate()
+            .map(|(index, task)| serde_json::json!({
+                "index": index,
+                "name": task.name,
+                "type": task.task_type,
+                "target": task.target(),
+                "interval_secs": task.interval_secs,
+                "enabled": task.enabled,
+            }))
+            .collect();
+        let counts: serde_json::Map<String, serde_json::Value> = counts
+            .iter()
+            .map(|(task_type, count)| (format!("{:?}", task_type), (*count).into()))
+            .collect();
+        return Ok(format!("{}\n", serde_json::to_string_pretty(&serde_json::json!({ "tasks": tasks, "counts": counts }))?));
+    }
+    
+    let header = ["#", "Name", "Type", "Target", "Interval", "Enabled"].map(str::to_string);
+    let rows: Vec<[String; 6]> = tasks
+        .iter()
+        .enumerate()
+        .map(|(index, task)| [
+            index.to_string(),
+            task.name.clone(),
+            task.task_type.to_string(),
+            task.target().to_string(),
+            format!("{}s", task.interval_secs),
+            if task.enabled { "yes" } else { "no" }.to_string(),
+        ])
+        .collect();
+    let mut widths = header.clone().map(|column| column.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let cells: Vec<String> = row.iter().zip(widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    
+    let enabled = tasks.iter().filter(|task| task.enabled).count();
+    let by_type: Vec<String> = counts.iter().map(|(task_type, count)| format!("{} {}", count, task_type)).collect();
+    table.push_str(&format!("\n{} tasks ({} enabled)", tasks.len(), enabled));
+    if !by_type.is_empty() {
+        table.push_str(&format!(": {}", by_type.join(", ")));
+    }
+    table.push('\n');
+    Ok(table)
+}
+
+/// Print the validation report of a configuration file, failing when anything is invalid; the file is only read,
+/// never migrated or rewritten
+fn validate_config_file(config_path: &Path) -> Result<()> {
+    let configs = Configs::read(config_path)?;
+    let (report, invalid) = validate_config(&configs);
+    print!("{}", report);
+    if invalid > 0 {
+        return Err(anyhow::anyhow!("Configuration file {:?} has {} invalid entries", config_path, invalid));
+    }
+    Ok(())
+}
+
+/// One line per task, OK or ERROR with its problems, and the number of invalid tasks and settings
+fn validate_config(configs: &Configs) -> (String, usize) {
+    let mut report = String::new();
+    let mut invalid = 0;
     
-    // Get initial content and send initial notification
-    let monitor_name = monitor.get_name();
-    info!("Starting monitoring: {}", monitor_name);
+    if let Err(e) = configs.display_timezone() {
+        report.push_str(&format!("ERROR  settings: {}\n", e));
+        invalid += 1;
+    }
+    for (index, task) in configs.tasks.iter().enumerate() {
+        let errors = task.validate();
+        if errors.is_empty() {
+            report.push_str(&format!("OK     {} {}\n", index, task.name));
+        } else {
+            report.push_str(&format!("ERROR  {} {}: {}\n", index, task.name, errors.join("; ")));
+            invalid += 1;
+        }
+    }
     
-    // First check to get initial content
-    match monitor.check().await {
-        Ok(Some(change)) => {
-            // Already have a change on first check - unusual but possible
-            info!("Initial check detected change: {}", change.message);
-            
-            // Send initial notification with the change details
-            let initial_message = format!("Started monitoring: {}", monitor_name);
-            if let Err(e) = notifier.send(&initial_message, &change.details).await {
-                error!("Failed to send initial notification: {}", e);
-            } else {
-                info!("Initial notification sent");
+    report.push_str(&format!("\n{} tasks checked, {} invalid\n", configs.tasks.len(), invalid));
+    (report, invalid)
+}
+
+/// Run a single monitor given on the command line, with its push stream if any, notifying via SERVER_CHAN_KEY
+async fn run_single(monitor: Box<dyn Monitor>, stream: Option<Box<dyn StreamMonitor>>, dry_run: bool) -> Result<()> {
+    let notification = NotificationConfig { dry_run, ..Default::default() };
+    let notifier = Arc::new(CompositeNotifier::new(build_notifiers(&notification)));
+    if notifier.is_empty() {
+        warn!("No notifiers configured, set SERVER_CHAN_KEY to be notified of changes");
+    }
+    
+    let board = StatusBoard::default();
+    let slot = board.register(&monitor.get_name(), &monitor.id());
+    let logger = TaskLogger::new(&monitor.get_name());
+    let queue = notification_queue(&notification);
+    let breaker_notifications = queue.sender(CIRCUIT_BREAKER_ID, notifier.clone(), TaskLogger::new("Circuit breaker"));
+    let notifications = queue.sender(&monitor.id(), notifier, logger.clone());
+    
+    let options = CliRunOptions {
+        quiet_hours: notification.quiet_hours,
+        failure_threshold: config::default_failure_threshold(),
+        stale_after: None,
+        schedule: Schedule::Jittered(0),
+        notify_on_start: notification.notify_on_start,
+        limiter: CheckLimiter::new(1),
+        active_schedule: None,
+        breaker: CircuitBreaker::default(),
+        breaker_notifications,
+    };
+    let mut handles = Vec::new();
+    if let Some(stream) = stream {
+        let slot = board.register(&stream.get_name(), &stream.id());
+        handles.push(tokio::spawn(run_stream_monitor(stream, notifications.clone(), board.clone(), slot, logger.clone(), options.clone())));
+    }
+    handles.push(tokio::spawn(run_monitor(monitor, notifications, board, slot, logger, options)));
+    run_until_interrupted(handles, &queue).await
+}
+
+/// Wait for the tasks to stop, or stop them on Ctrl+C, then send what is still queued before returning
+async fn run_until_interrupted(mut handles: Vec<JoinHandle<Result<()>>>, queue: &NotificationQueue) -> Result<()> {
+    let result = tokio::select! {
+        result = wait_for_tasks(&mut handles) => result,
+        _ = tokio::signal::ctrl_c() => {
+            info!("Interrupted, stopping tasks");
+            Ok(())
+        }
+    };
+    
+    // Dropping the tasks queues the notifications they held back
+    for handle in &handles {
+        handle.abort();
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+    if !queue.drain(DRAIN_TIMEOUT).await {
+        warn!("Exiting with {} notification(s) not sent", queue.len() + queue.unsent());
+    }
+    result
+}
+
+/// Wait for every task to stop, logging the ones that stopped with an error; stopped tasks are removed
+async fn wait_for_tasks(handles: &mut Vec<JoinHandle<Result<()>>>) -> Result<()> {
+    while let Some(handle) = handles.last_mut() {
+        let result = handle.await;
+        handles.pop();
+        if let Err(e) = result? {
+            error!("Task stopped with error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Start the background worker sending notifications for all tasks
+fn notification_queue(notification: &NotificationConfig) -> NotificationQueue {
+    let queue = NotificationQueue::new(QUEUE_CAPACITY);
+    queue.set_history(Some(NotificationHistory::new(HISTORY_PATH)));
+    queue.set_pending(Some(PendingNotifications::new(PENDING_PATH)));
+    queue.set_title_prefixes(notification.title_prefixes.clone());
+    queue.spawn(|notification, e| {
+        notification.logger.error(&format!("Failed to send notification \"{}\": {}", notification.title, e));
+    });
+    queue
+}
+
+/// Run every enabled task from the configuration concurrently
+async fn run_tasks(configs: Configs, status_port: Option<u16>, metrics_port: Option<u16>) -> Result<()> {
+    let tasks: Vec<&TaskConfig> = configs.tasks.iter().filter(|task| task.enabled).collect();
+    if tasks.is_empty() {
+        return Err(anyhow::anyhow!("No enabled tasks in configuration"));
+    }
+    
+    let board = match metrics_port {
+        Some(port) => {
+            let metrics = Arc::new(Metrics::new()?);
+            let board = StatusBoard::with_metrics(metrics.clone());
+            tokio::spawn(async move {
+                if let Err(e) = metrics::serve(port, metrics).await {
+                    error!("{}", e);
+                }
+            });
+            board
+        }
+        None => StatusBoard::default(),
+    };
+    if let Some(port) = status_port {
+        let board = board.clone();
+        tokio::spawn(async move {
+            if let Err(e) = status::serve(port, board).await {
+                error!("{}", e);
             }
-        },
-        Ok(None) => {
-            // Normal case - content captured but no change
-            info!("Initial content captured for: {}", monitor_name);
-            
-            // Send notification about monitoring start
-            let initial_message = format!("Started monitoring: {}", monitor_name);
-            let details = format!("Initial content captured. Will notify when changes are detected.");
-            
-            if let Err(e) = notifier.send(&initial_message, &details).await {
-                error!("Failed to send initial notification: {}", e);
-            } else {
-                info!("Initial notification sent");
+        });
+    }
+    
+    if configs.notification.dry_run {
+        warn!("Dry run: notifications are logged, not sent");
+    }
+    if build_notifiers(&configs.notification).is_empty() && tasks.iter().all(|task| task.server_chan_key.is_none()) {
+        warn!("No notifiers configured, changes will only be logged");
+    }
+    
+    let limiter = CheckLimiter::new(configs.max_concurrent_checks);
+    let cache = configs.response_cache();
+    let breaker = configs.circuit_breaker();
+    let global_notifier = Arc::new(CompositeNotifier::new(build_notifiers(&configs.notification)));
+    let events = configs.event_log();
+    let queue = notification_queue(&configs.notification);
+    if let Some(interval) = configs.notification.digest_interval() {
+        info!("Sending non-critical notifications as a digest every {:?}", interval);
+        queue.set_digest_interval(Some(interval));
+    }
+    let breaker_notifications = queue.sender(CIRCUIT_BREAKER_ID, global_notifier, TaskLogger::new("Circuit breaker"));
+    let mut handles = Vec::new();
+    for task in tasks {
+        let errors = task.validate();
+        if !errors.is_empty() {
+            error!("Skipping task '{}': {}", task.name, errors.join("; "));
+            continue;
+        }
+        
+        if let Some(warning) = task.interval_warning() {
+            warn!("Task '{}': {}", task.name, warning);
+        }
+        
+        info!("Starting task: {}", task.name);
+        let monitor = match task.build_monitor_with_cache(cache.as_ref()) {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                error!("Skipping task '{}': {}", task.name, e);
+                continue;
             }
-        },
-        Err(e) => {
-            // Error on first check
-            error!("Error getting initial content: {}", e);
-            // Continue to monitor anyway
-        },
+        };
+        let slot = board.register(&task.name, &monitor.id());
+        let board = board.clone();
+        let mut logger = TaskLogger::new(&task.name);
+        logger.set_events(events.clone(), &monitor.id());
+        let notifier = task_notifier(&configs, task);
+        if notifier.is_empty() && !task.notifiers.is_empty() {
+            warn!("Task '{}': none of its notifiers ({}) are configured", task.name, task.notifiers.join(", "));
+        }
+        let notifications = queue.sender(&monitor.id(), Arc::new(notifier), logger.clone());
+        let options = CliRunOptions {
+            quiet_hours: configs.notification.quiet_hours,
+            failure_threshold: task.failure_threshold,
+            stale_after: task.stale_after_secs.map(std::time::Duration::from_secs),
+            schedule: Schedule::new(task.aligned, configs.jitter_percent),
+            notify_on_start: task.announces_start(&configs.notification),
+            limiter: limiter.clone(),
+            active_schedule: task.active_schedule.clone(),
+            breaker: breaker.clone(),
+            breaker_notifications: breaker_notifications.clone(),
+        };
+        if let Some(stream) = task.build_stream_monitor() {
+            let slot = board.register(&format!("{} (stream)", task.name), &stream.id());
+            handles.push(tokio::spawn(run_stream_monitor(stream, notifications.clone(), board.clone(), slot, logger.clone(), options.clone())));
+        }
+        handles.push(tokio::spawn(run_monitor(monitor, notifications, board, slot, logger, options)));
+    }
+    
+    run_until_interrupted(handles, &queue).await
+}
+
+/// Notifiers a task selected: its own key, else the configured keys, else SERVER_CHAN_KEY
+fn task_notifier(configs: &Configs, task: &TaskConfig) -> CompositeNotifier {
+    CompositeNotifier::new(build_task_notifiers(&configs.notification, task))
+}
+
+/// How a task runs, from its configuration
+#[derive(Clone)]
+struct CliRunOptions {
+    /// Quiet hours as (start, end) local hours
+    quiet_hours: Option<(u8, u8)>,
+    /// Consecutive failed checks before the task is reported as failing
+    failure_threshold: u32,
+    /// Alert when nothing changed for this long
+    stale_after: Option<std::time::Duration>,
+    /// When to check again after each check
+    schedule: Schedule,
+    /// Send a notification for the first check
+    notify_on_start: bool,
+    /// Slots shared with the other tasks' checks
+    limiter: CheckLimiter,
+    /// Local days and hours to poll in, None for all the time
+    active_schedule: Option<ActiveSchedule>,
+    /// Pauses every task after too many failures across tasks
+    breaker: CircuitBreaker,
+    /// Sender through the global notifiers for the circuit breaker opening
+    breaker_notifications: NotificationSender,
+}
+
+async fn run_monitor(
+    mut monitor: Box<dyn Monitor>,
+    notifications: NotificationSender,
+    board: StatusBoard,
+    slot: usize,
+    logger: TaskLogger,
+    options: CliRunOptions,
+) -> Result<()> {
+    let CliRunOptions { quiet_hours, failure_threshold, stale_after, schedule, notify_on_start, limiter, active_schedule, breaker, breaker_notifications } = options;
+    let monitor_name = monitor.get_name();
+    logger.info(&format!("Starting monitoring: {}", monitor_name));
+    
+    // Catch misconfigured targets before the first check
+    match monitor.preflight().await {
+        Ok(()) => logger.info("Reachable"),
+        Err(e) => logger.error(&format!("Unreachable: {}", e)),
     }
     
-    // Start monitoring loop
+    // Notifications held back during quiet hours, sent when the task stops at the latest
+    let mut held = HeldNotifications::new(Some(notifications.clone()), quiet_hours);
+    
+    // Consecutive failures, alerting once when the threshold is crossed
+    let mut failures = FailureTracker::new(failure_threshold);
+    
+    // Time since the last change, alerting once when the target goes quiet for too long
+    let mut stale = StaleTracker::new(stale_after);
+    
+    // The first successful check captures the initial content
+    let mut initialized = false;
+    
     loop {
-        match monitor.check().await {
-            Ok(Some(change)) => {
-                info!("Change detected: {}", change.message);
-                if let Err(e) = notifier.send(&change.message, &change.details).await {
-                    error!("Failed to send notification: {}", e);
-                }
+        // Deliver notifications held back once quiet hours are over
+        let released = held.release();
+        if released > 0 {
+            logger.info(&format!("Quiet hours ended, sent {} held notification(s)", released));
+        }
+        
+        // Sleep through the hours outside the task's active schedule
+        if let Some(wait) = active_schedule.as_ref().and_then(|active| active.until_active(chrono::Local::now().naive_local())) {
+            logger.info(&format!("Outside its active schedule, next check in {}s", wait.as_secs()));
+            tokio::time::sleep(wait).await;
+            stale.pause(wait);
+            continue;
+        }
+        
+        // Every task waits while the circuit breaker is open
+        if let Some(wait) = breaker.open_for() {
+            logger.debug(&format!("Circuit open, polling resumes in {}s", wait.as_secs()));
+            tokio::time::sleep(wait).await;
+            continue;
+        }
+        
+        logger.event(EventKind::CheckStarted, "");
+        let result = match limiter.check(monitor.as_mut()).await {
+            Ok(Some(change)) if change.kind == ChangeKind::Error => Err(anyhow::anyhow!("{}\n{}", change.message, change.details)),
+            result => result,
+        };
+        logger.event(EventKind::CheckCompleted, check_outcome(&result));
+        
+        // Anything but a start on the first check, like a deviation from a baseline, is reported as a change
+        if !initialized && result.as_ref().is_ok_and(|change| change.as_ref().is_some_and(|change| change.kind != ChangeKind::Started)) {
+            initialized = true;
+        }
+        board.record(slot, match &result {
+            Ok(Some(_)) if initialized => CheckOutcome::Changed,
+            Ok(_) => CheckOutcome::Unchanged,
+            Err(_) => CheckOutcome::Error,
+        });
+        if let Some(elapsed) = monitor.last_response_time() {
+            board.record_response_time(slot, elapsed);
+        }
+        
+        // Alerts raised besides the check's change, like persistently slow responses, are notified on their own
+        if let Some(alert) = monitor.take_alert() {
+            logger.info(&alert.message);
+            if !held.hold(alert.kind, &alert.message, &alert.details) {
+                notifications.send(alert.kind, &alert.message, &alert.details);
             }
-            Ok(None) => {
-                info!("No changes detected");
+        }
+        if breaker.record(result.is_err()) {
+            let details = format!(
+                "{}, the last one by {}; all polling is paused for {}s and then resumes.",
+                breaker.threshold(),
+                monitor_name,
+                breaker.cooldown().as_secs(),
+            );
+            logger.error(&format!("Circuit opened: {}", details));
+            breaker_notifications.send(ChangeKind::Error, &format!("[{}] Circuit opened: polling paused", monitor.get_notes()), &details);
+        }
+        
+        match result {
+            Ok(change) => {
+                if failures.record_success() {
+                    logger.info("Recovered");
+                    let details = format!("{} is checking successfully again.", monitor_name);
+                    notifications.send(ChangeKind::Recovered, &format!("Task recovered: {}", monitor_name), &details);
+                }
+                
+                if !initialized {
+                    initialized = true;
+                    stale.record_change(std::time::Instant::now());
+                    
+                    // Send notification about monitoring start, with the initial content when reported
+                    let initial_message = format!("Started monitoring: {}", monitor_name);
+                    let details = match &change {
+                        Some(change) => {
+                            logger.info(&format!("Initial check detected change: {}", change.message));
+                            change.details.clone()
+                        }
+                        None => {
+                            logger.info(&format!("Initial content captured for: {}", monitor_name));
+                            "Initial content captured. Will notify when changes are detected.".to_string()
+                        }
+                    };
+                    
+                    if notify_on_start && !held.hold(ChangeKind::Started, &initial_message, &details) {
+                        notifications.send(ChangeKind::Started, &initial_message, &details);
+                    }
+                } else if let Some(change) = change {
+                    stale.record_change(std::time::Instant::now());
+                    logger.info(&format!("Change detected: {}", change.message));
+                    logger.event(EventKind::ChangeDetected, &change.message);
+                    if held.hold(change.kind, &change.message, &change.details) {
+                        logger.debug("Notification held until quiet hours end");
+                    } else {
+                        notifications.send(change.kind, &change.message, &change.details);
+                    }
+                } else if stale.record_unchanged(std::time::Instant::now()) {
+                    let title = format!("No updates for {} minutes: {}", stale.window_minutes(), monitor_name);
+                    let details = format!("{} has not changed for {} minutes, the target may be frozen.", monitor_name, stale.window_minutes());
+                    logger.info(&title);
+                    if !held.hold(ChangeKind::Stale, &title, &details) {
+                        notifications.send(ChangeKind::Stale, &title, &details);
+                    }
+                } else {
+                    logger.debug("No changes detected");
+                }
             }
             Err(e) => {
-                error!("Error during monitoring: {}", e);
+                logger.error(&format!("Error during monitoring: {}", e));
+                logger.event(EventKind::Error, &e.to_string());
+                
+                // Report the task as failing once, not on every retry
+                if failures.record_failure() {
+                    let details = format!("{} failed {} checks in a row.\n\nLast error:\n{}", monitor_name, failures.consecutive(), e);
+                    notifications.send(ChangeKind::Error, &format!("Task failing: {}", monitor_name), &details);
+                }
             }
         }
         
         // Wait for next check
-        tokio::time::sleep(std::time::Duration::from_secs(monitor.interval())).await;
+        let interval = std::time::Duration::from_secs(monitor.interval());
+        tokio::time::sleep(schedule.next_sleep(interval)).await;
+    }
+}
+
+/// Report the changes a push stream delivers, reconnecting with backoff when it drops
+async fn run_stream_monitor(
+    mut stream: Box<dyn StreamMonitor>,
+    notifications: NotificationSender,
+    board: StatusBoard,
+    slot: usize,
+    logger: TaskLogger,
+    options: CliRunOptions,
+) -> Result<()> {
+    let stream_name = stream.get_name();
+    logger.info(&format!("Starting stream: {}", stream_name));
+    
+    // Notifications held back during quiet hours, sent when the stream stops at the latest
+    let mut held = HeldNotifications::new(Some(notifications.clone()), options.quiet_hours);
+    
+    // Consecutive failed connections, alerting once when the threshold is crossed
+    let mut failures = FailureTracker::new(options.failure_threshold);
+    let mut backoff = reconnect_backoff();
+    
+    loop {
+        // Deliver notifications held back once quiet hours are over
+        let released = held.release();
+        if released > 0 {
+            logger.info(&format!("Quiet hours ended, sent {} held notification(s)", released));
+        }
+        
+        // Wake up now and then while notifications are held, the stream may stay quiet for hours
+        let result = tokio::select! {
+            result = stream.next_change() => result,
+            _ = tokio::time::sleep(std::time::Duration::from_secs(60)), if !held.is_empty() => continue,
+        };
+        board.record(slot, match &result {
+            Ok(Some(change)) if change.kind != ChangeKind::Started => CheckOutcome::Changed,
+            Ok(_) => CheckOutcome::Unchanged,
+            Err(_) => CheckOutcome::Error,
+        });
+        
+        match result {
+            Ok(change) => {
+                backoff.reset();
+                if failures.record_success() {
+                    logger.info("Stream recovered");
+                    let details = format!("{} is receiving events again.", stream_name);
+                    notifications.send(ChangeKind::Recovered, &format!("Task recovered: {}", stream_name), &details);
+                }
+                
+                match change {
+                    Some(change) if change.kind == ChangeKind::Started && !options.notify_on_start => {
+                        logger.info(&format!("Stream started: {}", change.message));
+                    }
+                    Some(change) => {
+                        logger.info(&format!("Change detected: {}", change.message));
+                        if change.kind != ChangeKind::Started {
+                            logger.event(EventKind::ChangeDetected, &change.message);
+                        }
+                        if held.hold(change.kind, &change.message, &change.details) {
+                            logger.debug("Notification held until quiet hours end");
+                        } else {
+                            notifications.send(change.kind, &change.message, &change.details);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Err(e) => {
+                let delay = backoff.next_delay();
+                logger.error(&format!("Stream error, reconnection attempt {} in {:?}: {}", backoff.attempts(), delay, e));
+                logger.event(EventKind::Error, &e.to_string());
+                
+                if failures.record_failure() {
+                    let details = format!("{} failed to connect {} times in a row.\n\nLast error:\n{}", stream_name, failures.consecutive(), e);
+                    notifications.send(ChangeKind::Error, &format!("Task failing: {}", stream_name), &details);
+                }
+                
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use monitors::Change;
+    use notifiers::Notifier;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Records the titles and contents it sends
+    struct Recording(Arc<Mutex<Vec<(String, String)>>>);
+
+    #[async_trait::async_trait]
+    impl Notifier for Recording {
+        async fn send(&self, title: &str, content: &str) -> Result<()> {
+            self.0.lock().unwrap().push((title.to_string(), content.to_string()));
+            Ok(())
+        }
+    }
+
+    /// Checks a real monitor every second, below the minimum interval of its type
+    struct EverySecond(Box<dyn Monitor>);
+
+    #[async_trait::async_trait]
+    impl Monitor for EverySecond {
+        async fn check(&mut self) -> Result<Option<Change>> {
+            self.0.check().await
+        }
+
+        fn interval(&self) -> u64 {
+            1
+        }
+
+        fn get_name(&self) -> String {
+            self.0.get_name()
+        }
+
+        fn get_notes(&self) -> String {
+            self.0.get_notes()
+        }
+
+        fn id(&self) -> String {
+            self.0.id()
+        }
+    }
+
+    /// Run a monitor until what it sent satisfies `done` or 10 seconds have passed, returning what it sent
+    async fn run_until<F>(monitor: Box<dyn Monitor>, notify_on_start: bool, failure_threshold: u32, done: F) -> Vec<(String, String)>
+    where
+        F: Fn(&[(String, String)]) -> bool,
+    {
+        let queue = NotificationQueue::new(QUEUE_CAPACITY);
+        queue.spawn(|_, _| {});
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let id = monitor.id();
+        let logger = TaskLogger::new(&monitor.get_name());
+        let notifications = queue.sender(&id, Arc::new(Recording(sent.clone())), logger.clone());
+        let board = StatusBoard::default();
+        let slot = board.register(&monitor.get_name(), &id);
+        let options = CliRunOptions {
+            quiet_hours: None,
+            failure_threshold,
+            stale_after: None,
+            schedule: Schedule::Jittered(0),
+            notify_on_start,
+            limiter: CheckLimiter::new(1),
+            active_schedule: None,
+            breaker: CircuitBreaker::default(),
+            breaker_notifications: notifications.clone(),
+        };
+        let run = tokio::spawn(run_monitor(monitor, notifications, board, slot, logger, options));
+
+        for _ in 0..100 {
+            if done(&sent.lock().unwrap()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        run.abort();
+        let sent = sent.lock().unwrap().clone();
+        sent
     }
-} 
\ No newline at end of file
+
+    /// Run a monitor until it has sent a notification, with the default failure threshold
+    async fn run_until_notified(monitor: Box<dyn Monitor>, notify_on_start: bool) -> Vec<(String, String)> {
+        run_until(monitor, notify_on_start, config::default_failure_threshold(), |sent| !sent.is_empty()).await
+    }
+
+    /// Reports a change on its second check
+    struct ChangesOnce {
+        checks: u32,
+    }
+
+    #[async_trait::async_trait]
+    impl Monitor for ChangesOnce {
+        async fn check(&mut self) -> Result<Option<Change>> {
+            self.checks += 1;
+            Ok((self.checks == 2).then(|| Change {
+                kind: ChangeKind::Updated,
+                message: "BTC - Price changed".to_string(),
+                details: String::new(),
+            }))
+        }
+
+        fn interval(&self) -> u64 {
+            1
+        }
+
+        fn get_name(&self) -> String {
+            "BTC price".to_string()
+        }
+
+        fn get_notes(&self) -> String {
+            "BTC".to_string()
+        }
+
+        fn id(&self) -> String {
+            "changes-once".to_string()
+        }
+    }
+
+    #[test]
+    fn validating_a_config_lists_the_invalid_task_and_leaves_the_file_alone() {
+        let mut configs = Configs::default();
+        let mut valid = configs.new_task();
+        valid.name = "Prices".to_string();
+        valid.url = "https://example.com/prices".to_string();
+        let mut invalid = configs.new_task();
+        invalid.name = "Broken".to_string();
+        invalid.url = String::new();
+        configs.tasks = vec![valid, invalid];
+
+        let (report, count) = validate_config(&configs);
+        assert_eq!(count, 1);
+        assert!(report.starts_with("OK     0 Prices\nERROR  1 Broken: "), "{}", report);
+        assert!(report.ends_with("\n2 tasks checked, 1 invalid\n"), "{}", report);
+
+        // An unversioned file would be migrated when loaded for running, validating must not rewrite it
+        let mut raw = serde_json::to_value(&configs).unwrap();
+        raw.as_object_mut().unwrap().remove("version");
+        let contents = serde_json::to_string(&raw).unwrap();
+        let path = std::env::temp_dir().join(format!("validate_config_{}.json", std::process::id()));
+        std::fs::write(&path, &contents).unwrap();
+
+        let error = validate_config_file(&path).unwrap_err();
+        assert!(error.to_string().contains("has 1 invalid entries"), "{}", error);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn turning_off_the_start_notification_still_reports_changes() {
+        let sent = run_until_notified(Box::new(ChangesOnce { checks: 0 }), false).await;
+        let titles: Vec<&str> = sent.iter().map(|(title, _)| title.as_str()).collect();
+        assert_eq!(titles, ["BTC - Price changed"]);
+    }
+
+    #[tokio::test]
+    async fn a_failing_endpoint_alerts_once_and_again_on_recovery() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(503))
+            .up_to_n_times(3)
+            .mount(&server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(serde_json::json!({ "price": 1.0 })))
+            .mount(&server)
+            .await;
+        let monitor = monitors::api_monitor::ApiMonitor::builder(&server.uri(), "$.price").notes("BTC").build();
+        let name = monitor.get_name();
+
+        let sent = run_until(Box::new(EverySecond(Box::new(monitor))), false, 2, |sent| sent.len() >= 2).await;
+        let titles: Vec<&str> = sent.iter().map(|(title, _)| title.as_str()).collect();
+        assert_eq!(titles, [format!("Task failing: {}", name), format!("Task recovered: {}", name)]);
+        assert!(sent[0].1.contains("failed 2 checks in a row"), "{}", sent[0].1);
+    }
+
+    #[tokio::test]
+    async fn a_login_page_instead_of_json_is_alerted_with_the_start_of_its_body() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_raw("<html><body>Please log in</body></html>", "text/html"))
+            .mount(&server)
+            .await;
+        let monitor = monitors::api_monitor::ApiMonitor::builder(&server.uri(), "$.price").notes("BTC").build();
+
+        // The first check already alerts, before the failure threshold is reached
+        let sent = run_until_notified(Box::new(monitor), true).await;
+        assert_eq!(sent.len(), 1, "{:?}", sent);
+        assert_eq!(sent[0].0, "BTC expected JSON, got text/html");
+        assert!(sent[0].1.contains("<html><body>Please log in</body></html>"), "{}", sent[0].1);
+    }
+}
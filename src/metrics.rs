@@ -0,0 +1,139 @@
+use anyhow::{Result, anyhow};
+use hyper::{Body, Method, Response, StatusCode};
+use prometheus::{Encoder, GaugeVec, IntCounterVec, Opts, Registry, TextEncoder};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::status::{self, CheckOutcome};
+
+/// Prometheus metrics for monitor checks
+pub struct Metrics {
+    registry: Registry,
+    checks: IntCounterVec,
+    changes: IntCounterVec,
+    errors: IntCounterVec,
+    last_check: GaugeVec,
+    response_time: GaugeVec,
+}
+
+impl Metrics {
+    /// Create and register all metrics
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+
+        let checks = IntCounterVec::new(
+            Opts::new("monitor_checks_total", "Number of checks performed"),
+            &["task", "monitor", "result"],
+        )?;
+        let changes = IntCounterVec::new(
+            Opts::new("monitor_changes_total", "Number of changes detected"),
+            &["task", "monitor"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("monitor_errors_total", "Number of failed checks"),
+            &["task", "monitor"],
+        )?;
+        let last_check = GaugeVec::new(
+            Opts::new("monitor_last_check_timestamp", "Unix time of the last check"),
+            &["task", "monitor"],
+        )?;
+
+        let response_time = GaugeVec::new(
+            Opts::new("monitor_response_time_seconds", "Time taken by the last response"),
+            &["task", "monitor"],
+        )?;
+
+        registry.register(Box::new(checks.clone()))?;
+        registry.register(Box::new(changes.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(last_check.clone()))?;
+        registry.register(Box::new(response_time.clone()))?;
+
+        Ok(Self { registry, checks, changes, errors, last_check, response_time })
+    }
+
+    /// Record the outcome of a check, labelled with the task name and monitor id
+    pub fn record(&self, task: &str, monitor: &str, outcome: CheckOutcome) {
+        let result = match outcome {
+            CheckOutcome::Pending => return,
+            CheckOutcome::Unchanged => "unchanged",
+            CheckOutcome::Changed => "changed",
+            CheckOutcome::Error => "error",
+        };
+
+        self.checks.with_label_values(&[task, monitor, result]).inc();
+        match outcome {
+            CheckOutcome::Changed => self.changes.with_label_values(&[task, monitor]).inc(),
+            CheckOutcome::Error => self.errors.with_label_values(&[task, monitor]).inc(),
+            _ => {}
+        }
+        self.last_check
+            .with_label_values(&[task, monitor])
+            .set(chrono::Utc::now().timestamp() as f64);
+    }
+
+    /// Record the time the last response of a task took
+    pub fn record_response_time(&self, task: &str, monitor: &str, elapsed: Duration) {
+        self.response_time.with_label_values(&[task, monitor]).set(elapsed.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text format
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| anyhow!("Metrics are not valid UTF-8: {}", e))
+    }
+}
+
+/// Serve `/metrics` on the given port until the process exits
+pub async fn serve(port: u16, metrics: Arc<Metrics>) -> Result<()> {
+    status::serve_http(port, "Metrics endpoint", move |req| {
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/metrics") => match metrics.render() {
+                Ok(text) => Response::builder()
+                    .header(hyper::header::CONTENT_TYPE, TextEncoder::new().format_type())
+                    .body(Body::from(text))
+                    .unwrap_or_default(),
+                Err(e) => Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from(e.to_string()))
+                    .unwrap_or_default(),
+            },
+            _ => status::not_found(),
+        }
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_render_as_labelled_prometheus_metrics() {
+        let metrics = Metrics::new().unwrap();
+        metrics.record("Prices", "static-1", CheckOutcome::Pending);
+        metrics.record("Prices", "static-1", CheckOutcome::Unchanged);
+        metrics.record("Prices", "static-1", CheckOutcome::Changed);
+        metrics.record("Feed", "xml-1", CheckOutcome::Error);
+        metrics.record_response_time("Prices", "static-1", Duration::from_millis(250));
+
+        let text = metrics.render().unwrap();
+        for line in [
+            "# TYPE monitor_checks_total counter",
+            r#"monitor_checks_total{monitor="static-1",result="unchanged",task="Prices"} 1"#,
+            r#"monitor_checks_total{monitor="static-1",result="changed",task="Prices"} 1"#,
+            r#"monitor_checks_total{monitor="xml-1",result="error",task="Feed"} 1"#,
+            r#"monitor_changes_total{monitor="static-1",task="Prices"} 1"#,
+            r#"monitor_errors_total{monitor="xml-1",task="Feed"} 1"#,
+            "# TYPE monitor_last_check_timestamp gauge",
+            r#"monitor_response_time_seconds{monitor="static-1",task="Prices"} 0.25"#,
+        ] {
+            assert!(text.lines().any(|rendered| rendered == line), "missing {}\n{}", line, text);
+        }
+        assert!(text.contains(r#"monitor_last_check_timestamp{monitor="xml-1",task="Feed"} "#), "{}", text);
+
+        // Pending isn't a check, and nothing failed for the first task
+        assert!(!text.contains(r#"result="pending""#), "{}", text);
+        assert!(!text.contains(r#"monitor_errors_total{monitor="static-1""#), "{}", text);
+    }
+}
@@ -1,35 +1,159 @@
 use log::{debug, info};
-use reqwest::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use jsonpath_lib as jsonpath;
 use anyhow::{Result, anyhow};
 
-use crate::monitors::{Change, Monitor};
+use crate::monitors::{clamp_interval, monitor_id, Baseline, ResponseTimer, DEFAULT_INTERVAL_SECS, MIN_INTERVAL_SECS_API, probe, Change, ChangeKind, Monitor};
+use crate::monitors::fetcher::{FetchOptions, FetchRequest, HttpFetcher, HttpMonitor, ResponseCache};
+use crate::utils::mask_secret;
+
+/// Characters of an unexpected body included when reporting it
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// Comparison of an extracted number with the alert value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertOperator {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+    #[serde(rename = ">=")]
+    Gte,
+    #[serde(rename = "<=")]
+    Lte,
+}
+
+impl AlertOperator {
+    /// Every operator, in the order they are offered
+    pub const ALL: [AlertOperator; 6] = [
+        AlertOperator::Gt,
+        AlertOperator::Gte,
+        AlertOperator::Lt,
+        AlertOperator::Lte,
+        AlertOperator::Eq,
+        AlertOperator::Ne,
+    ];
+    
+    /// Whether `value` compared with `target` meets the condition
+    pub fn matches(&self, value: f64, target: f64) -> bool {
+        match self {
+            AlertOperator::Gt => value > target,
+            AlertOperator::Lt => value < target,
+            AlertOperator::Eq => value == target,
+            AlertOperator::Ne => value != target,
+            AlertOperator::Gte => value >= target,
+            AlertOperator::Lte => value <= target,
+        }
+    }
+}
+
+impl fmt::Display for AlertOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            AlertOperator::Gt => ">",
+            AlertOperator::Lt => "<",
+            AlertOperator::Eq => "==",
+            AlertOperator::Ne => "!=",
+            AlertOperator::Gte => ">=",
+            AlertOperator::Lte => "<=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl FromStr for AlertOperator {
+    type Err = anyhow::Error;
+    
+    /// Parse a symbol such as `>=` or a name such as `gte`
+    fn from_str(operator: &str) -> Result<Self> {
+        match operator.trim().to_lowercase().as_str() {
+            ">" | "gt" => Ok(AlertOperator::Gt),
+            "<" | "lt" => Ok(AlertOperator::Lt),
+            "==" | "=" | "eq" => Ok(AlertOperator::Eq),
+            "!=" | "ne" => Ok(AlertOperator::Ne),
+            ">=" | "gte" => Ok(AlertOperator::Gte),
+            "<=" | "lte" => Ok(AlertOperator::Lte),
+            _ => Err(anyhow!("Unknown operator {}, expected >, <, ==, !=, >= or <=", operator.trim())),
+        }
+    }
+}
+
+/// Parse an alert condition such as `<= 100` or `== 0`
+pub fn parse_alert_condition(condition: &str) -> Result<(AlertOperator, f64)> {
+    let condition = condition.trim();
+    let split = condition
+        .find(|c: char| !matches!(c, '<' | '>' | '=' | '!'))
+        .filter(|index| *index > 0)
+        .ok_or_else(|| anyhow!("Alert condition {} must start with >, <, ==, !=, >= or <=", condition))?;
+    let (operator, value) = condition.split_at(split);
+    let value: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Alert condition {} must compare with a number", condition))?;
+    Ok((operator.parse()?, value))
+}
 
 /// Monitor JSON data returned from API
 pub struct ApiMonitor {
     /// API URL
     url: String,
-    /// JSONPath selector
-    selector: String,
-    /// Last detected value
-    last_value: Option<String>,
+    /// JSONPath selectors, all evaluated against the same response
+    selectors: Vec<String>,
+    /// Last detected value per selector
+    last_values: HashMap<String, String>,
+    /// Only report values meeting this condition, when they start meeting it
+    alert_when: Option<(AlertOperator, f64)>,
+    /// Whether each selector's last value met the alert condition
+    alert_met: HashMap<String, bool>,
+    /// Compare the number of items a selector matches instead of their content
+    array_length: bool,
+    /// Whether the initial values have been captured
+    initialized: bool,
+    /// Known-good values every check is compared with instead of the previous check
+    baseline: Option<Baseline>,
     /// Check interval (seconds)
     interval_secs: u64,
+    /// Raw `Cookie` header sent with every request, for APIs behind a login
+    cookies: Option<String>,
+    /// Non-JSON content type already reported with the start of its body, so it is reported once while it lasts
+    unexpected_content_type: Option<(String, String)>,
+    /// Time taken by the requests, with the slow-response alert
+    response_timer: ResponseTimer,
+    /// User-Agent, TLS and cache settings, with the fetcher performing the HTTP requests
+    fetch: FetchOptions,
     /// User-provided notes/remarks
     notes: String,
 }
 
 impl ApiMonitor {
-    /// Create a new API monitor
+    /// Create a new API monitor, `selector` holds one JSONPath per line
     pub fn new(url: String, selector: String, interval_secs: u64) -> Self {
         let url_clone = url.clone();
         ApiMonitor {
             url,
-            selector,
-            last_value: None,
-            interval_secs,
+            selectors: parse_selectors(&selector),
+            last_values: HashMap::new(),
+            alert_when: None,
+            alert_met: HashMap::new(),
+            array_length: false,
+            initialized: false,
+            baseline: None,
+            interval_secs: clamp_interval(interval_secs, MIN_INTERVAL_SECS_API, &url_clone),
+            cookies: None,
+            unexpected_content_type: None,
+            response_timer: ResponseTimer::default(),
+            fetch: FetchOptions::new(),
             notes: url_clone, // Use cloned URL as the note
         }
     }
@@ -50,8 +174,320 @@ impl ApiMonitor {
         }
     }
 
+    /// Send a raw `Cookie` header (e.g. `session=abc; theme=dark`) with every request
+    pub fn set_cookies(&mut self, cookies: Option<String>) {
+        self.cookies = cookies
+            .map(|cookies| cookies.trim().to_string())
+            .filter(|cookies| !cookies.is_empty());
+        if let Some(cookies) = &self.cookies {
+            debug!("Sending cookies {} to {}", mask_secret(cookies), self.url);
+        }
+    }
+
+    /// Alert once requests are persistently slower than this, None to only measure them
+    pub fn set_slow_threshold(&mut self, threshold: Option<Duration>) {
+        self.response_timer.set_threshold(threshold);
+    }
+
+    /// Only report a value once it starts meeting a condition such as `== 0`, recording other changes silently;
+    /// None reports every change
+    pub fn set_alert_when(&mut self, alert_when: Option<(AlertOperator, f64)>) {
+        self.alert_when = alert_when;
+    }
+    
+    /// Track how many items each selector matches, the length of an array or the number of matches,
+    /// instead of their content, so a growing list reports `items: 12 → 15 (+3)` rather than a diff
+    pub fn set_array_length(&mut self, array_length: bool) {
+        self.array_length = array_length;
+    }
+    
+    /// Whether a value is a number meeting the alert condition
+    fn meets_alert(&self, value: &str) -> bool {
+        let Some((operator, target)) = self.alert_when else {
+            return false;
+        };
+        match value.trim().parse::<f64>() {
+            Ok(number) => operator.matches(number, target),
+            Err(_) => {
+                debug!("Value {} is not a number, alert condition not met", value);
+                false
+            }
+        }
+    }
+    
+    /// Compare every check with these values, as given by `current_content`, instead of with the previous check;
+    /// None to follow the API
+    pub fn set_baseline(&mut self, baseline: Option<&str>) {
+        self.baseline = baseline.map(Baseline::new);
+    }
+    
+    /// With a baseline, report the values that just started meeting the alert condition; the first check only records them
+    fn baseline_alert(&mut self, results: &[(String, Option<String>)]) -> Option<Change> {
+        let (operator, target) = self.alert_when?;
+        let first = !std::mem::replace(&mut self.initialized, true);
+        let mut triggered = Vec::new();
+        for (selector, value) in results {
+            let Some(value) = value else {
+                continue;
+            };
+            let met = self.meets_alert(value);
+            let was_met = self.alert_met.insert(selector.clone(), met).unwrap_or(false);
+            if met && !was_met && !first {
+                triggered.push(format!("{} = {} ({} {})", selector, value, operator, target));
+            }
+        }
+        if triggered.is_empty() {
+            return None;
+        }
+        Some(Change {
+            kind: ChangeKind::ThresholdCrossed,
+            message: format!("{} alert: {}", self.notes, triggered.join(", ")),
+            details: format!("URL: {}\nAlert condition met: value {} {}\n\n{}", self.url, operator, target, triggered.join("\n")),
+        })
+    }
+    
+    /// Value of every selector in a response, None for those matching nothing
+    fn select_values(&self, json: &Value) -> Result<Vec<(String, Option<String>)>> {
+        self.selectors
+            .iter()
+            .map(|selector| {
+                let value = if self.array_length { extract_length(json, selector)? } else { extract(json, selector)? };
+                Ok((selector.clone(), value))
+            })
+            .collect()
+    }
+    
+    /// Request for the API URL, with the session cookies if any
+    fn request(&self) -> FetchRequest {
+        FetchRequest::get(&self.url).cookies(self.cookies.as_deref())
+    }
+
+    /// Fetch the API and compare every selected value with the previous check
+    async fn check_values(&mut self) -> Result<Option<Change>> {
+        info!("Checking API at {}", self.url);
+        
+        let started = Instant::now();
+        let response = match self.fetch.fetcher().fetch(self.request()).await {
+                Ok(resp) => {
+                    if !resp.cached {
+                        self.response_timer.record(started.elapsed(), &self.url);
+                    }
+                    resp
+                }
+                Err(e) => {
+                    debug!("Failed to fetch API: {}", e);
+                    return Ok(Some(Change {
+                        kind: ChangeKind::Error,
+                        message: format!("API request failed: {}", e),
+                        details: format!("URL: {}", self.url),
+                    }));
+                }
+            };
+            
+        if !response.is_success() {
+            debug!("API returned non-success status code: {}", response.status);
+            return Ok(Some(Change {
+                kind: ChangeKind::Error,
+                message: format!("API returned status code {}", response.status),
+                details: format!("URL: {}", self.url),
+            }));
+        }
+        
+        // A JSON body served with another type (e.g. text/plain) is still accepted
+        let content_type = response
+            .header(CONTENT_TYPE.as_str())
+            .map(|content_type| content_type.split(';').next().unwrap_or_default().trim().to_lowercase());
+        if let Some(content_type) = content_type.filter(|content_type| !content_type.contains("json")) {
+            if serde_json::from_slice::<Value>(&response.body).is_err() {
+                return self.report_content_type(content_type, &response.text());
+            }
+        }
+        self.unexpected_content_type = None;
+        
+        let json: Value = match serde_json::from_slice::<Value>(&response.body) {
+            Ok(json) => {
+                // 添加调试日志，输出完整的JSON响应
+                debug!("Received JSON response: {}", json);
+                json
+            },
+            Err(e) => {
+                debug!("Failed to parse JSON response: {}", e);
+                return Ok(Some(Change {
+                    kind: ChangeKind::Error,
+                    message: format!("Failed to parse JSON response: {}", e),
+                    details: format!("URL: {}", self.url),
+                }));
+            }
+        };
+        
+        // Extract data for every selector
+        let results = self.select_values(&json)?;
+        if self.baseline.is_some() {
+            // The alert condition still applies to the values, reported first when both happen
+            let alert = self.baseline_alert(&results);
+            let deviation = self.baseline.as_mut().and_then(|baseline| baseline.compare(&values_content(&results), &self.notes));
+            return Ok(match (alert, deviation) {
+                (Some(mut alert), Some(deviation)) => {
+                    alert.details.push_str(&format!("\n\n---\n\n{}\n\n{}", deviation.message, deviation.details));
+                    Some(alert)
+                }
+                (alert, deviation) => alert.or(deviation),
+            });
+        }
+        
+        if !self.initialized {
+            // First check, record the initial values
+            self.initialized = true;
+            
+            let mut sections = Vec::new();
+            for (selector, value) in results {
+                match value {
+                    Some(value) => {
+                        debug!("First check, recording initial value for {}: {}", selector, value);
+                        let mut section = format!("JSONPath: {}\nInitial value: {}", selector, value);
+                        if let Some((operator, target)) = self.alert_when {
+                            let met = self.meets_alert(&value);
+                            section.push_str(&format!("\nAlert when {} {}: {}", operator, target, if met { "met now" } else { "not met" }));
+                            self.alert_met.insert(selector.clone(), met);
+                        }
+                        sections.push(section);
+                        self.last_values.insert(selector, value);
+                    }
+                    None => {
+                        debug!("Could not extract initial data using selector: {}", selector);
+                        sections.push(format!("JSONPath: {}\nThe JSONPath selector did not match any data. Please check if your selector is correct.", selector));
+                    }
+                }
+            }
+            
+            return Ok(Some(Change {
+                kind: ChangeKind::Started,
+                message: format!("start: {}", self.notes),
+                details: format!("URL: {}\n\n{}\n\nNote: A value may represent multiple elements if the JSONPath selector matches more than one.", 
+                    self.url, sections.join("\n\n")),
+            }));
+        }
+        
+        let mut changed = Vec::new();
+        let mut missing = Vec::new();
+        for (selector, value) in results {
+            match (value, self.last_values.get(&selector)) {
+                (Some(new_value), Some(old_value)) if *old_value == new_value => {}
+                (Some(new_value), old_value) => {
+                    // Changed, or matched for the first time
+                    let old_value = old_value.cloned().unwrap_or_default();
+                    debug!("Old value for {}: {}", selector, old_value);
+                    debug!("New value for {}: {}", selector, new_value);
+                    self.last_values.insert(selector.clone(), new_value.clone());
+                    changed.push((selector, old_value, new_value));
+                }
+                (None, Some(_)) => {
+                    debug!("Could not extract data using selector: {}", selector);
+                    missing.push(selector);
+                }
+                (None, None) => {}
+            }
+        }
+        
+        // With an alert condition only values that just started meeting it are reported
+        if self.alert_when.is_some() {
+            let mut triggered = Vec::new();
+            for (selector, old_value, new_value) in changed {
+                let met = self.meets_alert(&new_value);
+                let was_met = self.alert_met.insert(selector.clone(), met).unwrap_or(false);
+                if met && !was_met {
+                    triggered.push((selector, old_value, new_value));
+                }
+            }
+            changed = triggered;
+        }
+        
+        if changed.is_empty() {
+            if missing.is_empty() {
+                debug!("No change detected");
+                return Ok(None);
+            }
+            
+            return Ok(Some(Change {
+                kind: ChangeKind::Error,
+                message: format!("{} - Data extraction failed", self.notes),
+                details: format!("URL: {}\nSelector: {}\n\nThe JSONPath selector did not match any data after a previous successful match. The data structure may have changed.", 
+                    self.url, missing.join(", ")),
+            }));
+        }
+        
+        info!("Detected change in API data: {}", changed.iter().map(|(selector, _, _)| selector.as_str()).collect::<Vec<_>>().join(", "));
+        
+        let mut sections = Vec::new();
+        for (selector, old_value, new_value) in &changed {
+            // 创建更易读的变化描述
+            let change_description = self.generate_change_description(old_value, new_value);
+            sections.push(format!("JSONPath: {}\n\nChanges:\n{}\n\nCurrent value:\n{}\n\nPrevious value:\n{}", 
+                selector, change_description, new_value, old_value));
+        }
+        if !missing.is_empty() {
+            sections.push(format!("No longer matching: {}", missing.join(", ")));
+        }
+        
+        if let Some((operator, target)) = self.alert_when {
+            let met: Vec<String> = changed
+                .iter()
+                .map(|(selector, _, new_value)| format!("{} = {} ({} {})", selector, new_value, operator, target))
+                .collect();
+            return Ok(Some(Change {
+                kind: ChangeKind::ThresholdCrossed,
+                message: format!("{} alert: {}", self.notes, met.join(", ")),
+                details: format!("Alert condition met: value {} {}\n\n{}", operator, target, sections.join("\n\n---\n\n")),
+            }));
+        }
+        
+        let message = if self.selectors.len() == 1 {
+            let (_, old_value, new_value) = &changed[0];
+            format!("{} {}", self.notes, self.generate_change_description(old_value, new_value))
+        } else {
+            let names: Vec<&str> = changed.iter().map(|(selector, _, _)| selector.as_str()).collect();
+            format!("{} changed: {}", self.notes, names.join(", "))
+        };
+        
+        Ok(Some(Change {
+            kind: ChangeKind::Updated,
+            message,
+            details: format!("{}\n\nNote: If a JSONPath selector matches multiple elements, its value represents the combined changes.", 
+                sections.join("\n\n---\n\n")),
+        }))
+    }
+    
+    /// Report a response that isn't JSON as down the first time, later ones are errors repeating the body snippet
+    fn report_content_type(&mut self, content_type: String, body: &str) -> Result<Option<Change>> {
+        if let Some((_, snippet)) = self.unexpected_content_type.as_ref().filter(|(reported, _)| *reported == content_type) {
+            return Err(anyhow!("Expected JSON, still getting {}. Start of the body:\n{}", content_type, snippet));
+        }
+        
+        let mut snippet: String = body.split_whitespace().collect::<Vec<_>>().join(" ");
+        if let Some((index, _)) = snippet.char_indices().nth(BODY_SNIPPET_CHARS) {
+            snippet.truncate(index);
+            snippet.push_str("...");
+        }
+        
+        // Not an error, the run loop would only keep the body for the failure alert after several checks
+        let change = Change {
+            kind: ChangeKind::Down,
+            message: format!("{} expected JSON, got {}", self.notes, content_type),
+            details: format!("URL: {}\n\nThe endpoint returned {} instead of JSON, e.g. an error or login page. Start of the body:\n{}",
+                self.url, content_type, snippet),
+        };
+        self.unexpected_content_type = Some((content_type, snippet));
+        Ok(Some(change))
+    }
+
     /// 生成更易读的变化描述
     fn generate_change_description(&self, old_value: &str, new_value: &str) -> String {
+        if self.array_length {
+            if let (Ok(old_count), Ok(new_count)) = (old_value.parse::<i64>(), new_value.parse::<i64>()) {
+                return format!("items: {} → {} ({:+})", old_count, new_count, new_count - old_count);
+            }
+        }
+        
         if old_value.len() > 100 || new_value.len() > 100 {
             // 对于长字符串，尝试检测具体的变化部分
             if old_value.contains(',') && new_value.contains(',') {
@@ -92,147 +528,116 @@ impl ApiMonitor {
     }
 }
 
+impl ApiMonitor {
+    /// Start building an API monitor, checking every `DEFAULT_INTERVAL_SECS` unless set otherwise
+    pub fn builder(url: &str, selector: &str) -> ApiMonitorBuilder {
+        ApiMonitorBuilder {
+            monitor: ApiMonitor::new(url.to_string(), selector.to_string(), DEFAULT_INTERVAL_SECS),
+            interval_secs: DEFAULT_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Builds an `ApiMonitor` with chained options
+pub struct ApiMonitorBuilder {
+    monitor: ApiMonitor,
+    interval_secs: u64,
+}
+
+impl ApiMonitorBuilder {
+    /// Notes/remarks shown in notifications
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.monitor.set_notes(notes);
+        self
+    }
+
+    /// Check interval (seconds), raised to `MIN_INTERVAL_SECS_API`
+    pub fn interval(mut self, interval_secs: u64) -> Self {
+        self.interval_secs = interval_secs;
+        self
+    }
+
+    /// Custom User-Agent
+    pub fn user_agent(mut self, user_agent: Option<&str>) -> Self {
+        self.monitor.set_user_agent(user_agent);
+        self
+    }
+
+    /// Accept invalid TLS certificates, insecure
+    pub fn accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.monitor.set_accept_invalid_certs(accept_invalid_certs);
+        self
+    }
+
+    /// Values every check is compared with
+    pub fn baseline(mut self, baseline: Option<&str>) -> Self {
+        self.monitor.set_baseline(baseline);
+        self
+    }
+
+    /// Raw `Cookie` header sent with every request
+    pub fn cookies(mut self, cookies: Option<String>) -> Self {
+        self.monitor.set_cookies(cookies);
+        self
+    }
+
+    /// Slow-response alert threshold
+    pub fn slow_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.monitor.set_slow_threshold(threshold);
+        self
+    }
+
+    /// Compare item counts instead of content
+    pub fn array_length(mut self, array_length: bool) -> Self {
+        self.monitor.set_array_length(array_length);
+        self
+    }
+
+    /// Only report values once they start meeting this condition
+    pub fn alert_when(mut self, alert_when: Option<(AlertOperator, f64)>) -> Self {
+        self.monitor.set_alert_when(alert_when);
+        self
+    }
+
+    /// HTTP fetcher, e.g. with canned responses in tests
+    pub fn fetcher(mut self, fetcher: Arc<dyn HttpFetcher>) -> Self {
+        self.monitor.set_fetcher(fetcher);
+        self
+    }
+
+    /// Responses shared with the other monitors using the cache, None for no cache
+    pub fn response_cache(mut self, cache: Option<&ResponseCache>) -> Self {
+        if let Some(cache) = cache {
+            self.monitor.set_response_cache(cache);
+        }
+        self
+    }
+
+    /// Build the monitor
+    pub fn build(mut self) -> ApiMonitor {
+        self.monitor.interval_secs = clamp_interval(self.interval_secs, MIN_INTERVAL_SECS_API, &self.monitor.url);
+        self.monitor
+    }
+}
+
+impl HttpMonitor for ApiMonitor {
+    fn fetch_options(&mut self) -> &mut FetchOptions {
+        &mut self.fetch
+    }
+}
+
 #[async_trait::async_trait]
 impl Monitor for ApiMonitor {
     async fn check(&mut self) -> Result<Option<Change>> {
-        info!("Checking API at {}", self.url);
-        
-        let client = Client::new();
-        let response = match client
-            .get(&self.url)
-            .timeout(Duration::from_secs(30))
-            .send()
-            .await {
-                Ok(resp) => resp,
-                Err(e) => {
-                    debug!("Failed to fetch API: {}", e);
-                    return Ok(Some(Change {
-                        message: format!("API request failed: {}", e),
-                        details: format!("URL: {}", self.url),
-                    }));
-                }
-            };
-            
-        if !response.status().is_success() {
-            debug!("API returned non-success status code: {}", response.status());
-            return Ok(Some(Change {
-                message: format!("API returned status code {}", response.status()),
-                details: format!("URL: {}", self.url),
-            }));
-        }
-        
-        let json: Value = match response.json::<Value>().await {
-            Ok(json) => {
-                // 添加调试日志，输出完整的JSON响应
-                debug!("Received JSON response: {}", json.to_string());
-                json
-            },
-            Err(e) => {
-                debug!("Failed to parse JSON response: {}", e);
-                return Ok(Some(Change {
-                    message: format!("Failed to parse JSON response: {}", e),
-                    details: format!("URL: {}", self.url),
-                }));
-            }
-        };
-        
-        // Extract data using JSONPath
-        let selector = self.selector.trim();
-        let result = match jsonpath::select(&json, selector) {
-            Ok(results) if !results.is_empty() => {
-                // 处理多个结果，不仅仅是第一个
-                let result_str = if results.len() == 1 {
-                    // 单个结果的处理方式
-                    results[0].to_string().trim_matches('"').to_string()
-                } else {
-                    // 多个结果的处理方式 - 将所有结果合并成一个JSON数组字符串
-                    let values: Vec<String> = results.iter()
-                        .map(|r| r.to_string().trim_matches('"').to_string())
-                        .collect();
-                    format!("[{}]", values.join(", "))
-                };
-                
-                Some(result_str)
-            },
-            Ok(_) => {
-                debug!("JSONPath selector returned no results");
-                None
-            },
-            Err(e) => {
-                debug!("JSONPath selector error: {}", e);
-                return Err(anyhow!("JSONPath selector error: {}", e));
-            }
-        };
-        
-        match &self.last_value {
-            None => {
-                // First check
-                if let Some(new_value) = result {
-                    debug!("First check, recording initial value: {}", new_value);
-                    
-                    // Create change object with initial value
-                    let change = Change {
-                        message: format!("start: {}", self.notes),
-                        details: format!("JSONPath: {}\nInitial value: {}\n\nNote: This may represent multiple values if your JSONPath selector matches multiple elements.", 
-                            selector, new_value),
-                    };
-                    
-                    // Set the last value
-                    self.last_value = Some(new_value);
-                    
-                    // Return the change to send initial notification
-                    Ok(Some(change))
-                } else {
-                    // Could not extract initial data
-                    debug!("Could not extract initial data using selector: {}", self.selector);
-                    self.last_value = None;
-                    Ok(Some(Change {
-                        message: format!("start: {}", self.notes),
-                        details: format!("URL: {}\nSelector: {}\n\nThe JSONPath selector did not match any data. Please check if your selector is correct.", 
-                            self.url, self.selector),
-                    }))
-                }
-            }
-            Some(old_value) => {
-                if let Some(new_value) = result {
-                    if *old_value != new_value {
-                        // Change detected
-                        info!("Detected change in API data");
-                        debug!("Old value: {}", old_value);
-                        debug!("New value: {}", new_value);
-                        
-                        // 创建更易读的变化描述
-                        let change_description = self.generate_change_description(old_value, &new_value);
-                        
-                        // Create change object with old_value (already borrowed)
-                        let change = Change {
-                            message: format!("{} {}", self.notes, change_description),
-                            details: format!("JSONPath: {}\n\nChanges:\n{}\n\nCurrent value:\n{}\n\nPrevious value:\n{}\n\nNote: If your JSONPath selector matches multiple elements, this represents the combined changes.", 
-                                selector, change_description, &new_value, old_value),
-                        };
-                        
-                        // Now update the last_value after we've used old_value
-                        self.last_value = Some(new_value);
-                        
-                        Ok(Some(change))
-                    } else {
-                        // No change
-                        debug!("No change detected");
-                        // Since we don't need the old value anymore, we can update
-                        self.last_value = Some(new_value);
-                        Ok(None)
-                    }
-                } else {
-                    // Could not extract data
-                    debug!("Could not extract data using selector: {}", self.selector);
-                    Ok(Some(Change {
-                        message: format!("{} - Data extraction failed", self.notes),
-                        details: format!("URL: {}\nSelector: {}\n\nThe JSONPath selector did not match any data after a previous successful match. The data structure may have changed.", 
-                            self.url, self.selector),
-                    }))
-                }
-            }
-        }
+        self.check_values().await
+    }
+    
+    fn last_response_time(&self) -> Option<Duration> {
+        self.response_timer.last()
+    }
+    
+    fn take_alert(&mut self) -> Option<Change> {
+        self.response_timer.take_alert()
     }
 
     fn interval(&self) -> u64 {
@@ -246,4 +651,246 @@ impl Monitor for ApiMonitor {
     fn get_notes(&self) -> String {
         self.notes.clone()
     }
-} 
\ No newline at end of file
+    
+    fn id(&self) -> String {
+        let selectors = self.selectors.join("\n");
+        if self.array_length {
+            monitor_id("api", &[&self.url, &selectors, "length"])
+        } else {
+            monitor_id("api", &[&self.url, &selectors])
+        }
+    }
+    
+    async fn preflight(&self) -> Result<()> {
+        probe(self.fetch.fetcher(), self.request()).await
+    }
+    
+    async fn current_content(&mut self) -> Result<String> {
+        let response = self.fetch.fetcher().fetch(self.request()).await?;
+        if !response.is_success() {
+            return Err(anyhow!("API returned status code {}", response.status));
+        }
+        let json: Value = serde_json::from_slice(&response.body)
+            .map_err(|e| anyhow!("Failed to parse JSON response: {}", e))?;
+        Ok(values_content(&self.select_values(&json)?))
+    }
+}
+
+/// Selected values as one text, a line per selector when there are several
+fn values_content(values: &[(String, Option<String>)]) -> String {
+    let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "(no match)".to_string());
+    match values {
+        [(_, single)] => value(single),
+        values => values.iter().map(|(selector, v)| format!("{}: {}", selector, value(v))).collect::<Vec<_>>().join("\n"),
+    }
+}
+
+/// Split a selector field into one JSONPath per line, an empty field monitors the entire response
+pub fn parse_selectors(selector: &str) -> Vec<String> {
+    let selectors: Vec<String> = selector
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    
+    if selectors.is_empty() {
+        vec!["$".to_string()]
+    } else {
+        selectors
+    }
+}
+
+/// Extract the value matched by a JSONPath selector
+pub fn extract(json: &Value, selector: &str) -> Result<Option<String>> {
+    match jsonpath::select(json, selector) {
+        Ok(results) if !results.is_empty() => {
+            // 处理多个结果，不仅仅是第一个
+            let result_str = if results.len() == 1 {
+                // 单个结果的处理方式
+                results[0].to_string().trim_matches('"').to_string()
+            } else {
+                // 多个结果的处理方式 - 将所有结果合并成一个JSON数组字符串
+                let values: Vec<String> = results.iter()
+                    .map(|r| r.to_string().trim_matches('"').to_string())
+                    .collect();
+                format!("[{}]", values.join(", "))
+            };
+            
+            Ok(Some(result_str))
+        },
+        Ok(_) => {
+            debug!("JSONPath selector {} returned no results", selector);
+            Ok(None)
+        },
+        Err(e) => {
+            debug!("JSONPath selector error: {}", e);
+            Err(anyhow!("JSONPath selector error: {}", e))
+        }
+    }
+}
+
+/// Number of items a JSONPath selector matches: the length of a single matched array, else the number of matches
+pub fn extract_length(json: &Value, selector: &str) -> Result<Option<String>> {
+    match jsonpath::select(json, selector) {
+        Ok(results) if results.is_empty() => Ok(None),
+        Ok(results) => match results.as_slice() {
+            [Value::Array(items)] => Ok(Some(items.len().to_string())),
+            results => Ok(Some(results.len().to_string())),
+        },
+        Err(e) => Err(anyhow!("JSONPath selector error: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitors::fetcher::{FetchResponse, MockFetcher};
+
+    fn monitor(fetcher: MockFetcher) -> ApiMonitor {
+        let mut monitor = ApiMonitor::new("http://api.test/".to_string(), "$.price".to_string(), 60);
+        monitor.set_fetcher(Arc::new(fetcher));
+        monitor
+    }
+
+    #[tokio::test]
+    async fn detects_started_unchanged_and_updated() {
+        let mut monitor = monitor(MockFetcher::default()
+            .respond(200, r#"{"price":"1.0"}"#)
+            .respond(200, r#"{"price":"1.0"}"#)
+            .respond(200, r#"{"price":"2.0"}"#));
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::Started);
+        assert!(change.details.contains("Initial value: 1.0"), "{}", change.details);
+
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::Updated);
+        assert!(change.details.contains("Current value:\n2.0"), "{}", change.details);
+        assert!(change.details.contains("Previous value:\n1.0"), "{}", change.details);
+    }
+
+    #[tokio::test]
+    async fn current_values_become_a_baseline_that_is_never_replaced() {
+        let mut monitor = monitor(MockFetcher::default()
+            .respond(200, r#"{"price":"1.0"}"#)
+            .respond(200, r#"{"price":"2.0"}"#)
+            .respond(200, r#"{"price":"2.0"}"#)
+            .respond(200, r#"{"price":"1.0"}"#));
+
+        let baseline = monitor.current_content().await.unwrap();
+        assert_eq!(baseline, "1.0");
+        monitor.set_baseline(Some(&baseline));
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Recovered);
+    }
+
+    #[tokio::test]
+    async fn alert_condition_still_applies_with_a_baseline() {
+        let mut monitor = monitor(MockFetcher::default()
+            .respond(200, r#"{"price":"5"}"#)
+            .respond(200, r#"{"price":"0"}"#)
+            .respond(200, r#"{"price":"5"}"#));
+        monitor.set_baseline(Some("5"));
+        monitor.set_alert_when(Some(parse_alert_condition("== 0").unwrap()));
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::ThresholdCrossed);
+        assert!(change.details.contains("differs from the baseline"), "{}", change.details);
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Recovered);
+    }
+
+    #[tokio::test]
+    async fn alert_condition_reports_values_when_they_start_meeting_it() {
+        let mut monitor = monitor(MockFetcher::default()
+            .respond(200, r#"{"price":"5"}"#)
+            .respond(200, r#"{"price":"3"}"#)
+            .respond(200, r#"{"price":"0"}"#)
+            .respond(200, r#"{"price":"0.0"}"#)
+            .respond(200, r#"{"price":"2"}"#));
+        monitor.set_alert_when(Some(parse_alert_condition("== 0").unwrap()));
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert!(change.details.contains("Alert when == 0: not met"), "{}", change.details);
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::ThresholdCrossed);
+        assert!(change.message.ends_with("alert: $.price = 0 (== 0)"), "{}", change.message);
+
+        // Still met, then no longer met
+        assert!(monitor.check().await.unwrap().is_none());
+        assert!(monitor.check().await.unwrap().is_none());
+
+        assert!(parse_alert_condition("=> 1").is_err());
+        assert_eq!(parse_alert_condition(">=-2.5").unwrap(), (AlertOperator::Gte, -2.5));
+    }
+
+    #[tokio::test]
+    async fn array_length_mode_compares_item_counts() {
+        let mut monitor = ApiMonitor::builder("http://api.test/", "$.listings")
+            .array_length(true)
+            .fetcher(Arc::new(MockFetcher::default()
+                .respond(200, r#"{"listings":[{"id":1},{"id":2}]}"#)
+                .respond(200, r#"{"listings":[{"id":2},{"id":1}]}"#)
+                .respond(200, r#"{"listings":[{"id":1},{"id":2},{"id":3},{"id":4},{"id":5}]}"#)))
+            .build();
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert!(change.details.contains("Initial value: 2"), "{}", change.details);
+        assert!(monitor.check().await.unwrap().is_none());
+        let change = monitor.check().await.unwrap().unwrap();
+        assert!(change.message.ends_with("items: 2 → 5 (+3)"), "{}", change.message);
+
+        let json: Value = serde_json::from_str(r#"{"a":[{"b":1},{"b":2},{"b":3}]}"#).unwrap();
+        assert_eq!(extract_length(&json, "$.a[*].b").unwrap().as_deref(), Some("3"));
+        assert_eq!(extract_length(&json, "$.missing").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn failures_are_reported_as_error_changes() {
+        let mut monitor = monitor(MockFetcher::default()
+            .fail("connection refused")
+            .respond(500, "")
+            .respond(200, "not json"));
+
+        for _ in 0..3 {
+            let change = monitor.check().await.unwrap().unwrap();
+            assert_eq!(change.kind, ChangeKind::Error, "{}", change.message);
+        }
+    }
+
+    #[tokio::test]
+    async fn non_json_content_type_is_reported_once() {
+        let html = FetchResponse {
+            status: 200,
+            headers: [("content-type".to_string(), "text/html; charset=utf-8".to_string())].into(),
+            body: b"<html>\n  <body>Please log in</body>\n</html>".to_vec(),
+            ..FetchResponse::default()
+        };
+        let plain_json = FetchResponse {
+            status: 200,
+            headers: [("content-type".to_string(), "text/plain".to_string())].into(),
+            body: br#"{"price":"1.0"}"#.to_vec(),
+            ..FetchResponse::default()
+        };
+        let mut monitor = monitor(MockFetcher::default()
+            .respond_with(html.clone())
+            .respond_with(html)
+            .respond_with(plain_json));
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::Down);
+        assert!(change.message.ends_with("expected JSON, got text/html"), "{}", change.message);
+        assert!(change.details.contains("<html> <body>Please log in</body> </html>"), "{}", change.details);
+
+        let error = monitor.check().await.err().unwrap().to_string();
+        assert!(error.ends_with("<html> <body>Please log in</body> </html>"), "{}", error);
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+    }
+}
@@ -0,0 +1,434 @@
+use anyhow::{Result, anyhow};
+use log::warn;
+use reqwest::Client;
+use reqwest::header::COOKIE;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::monitors::{custom_user_agent, describe_error, http_client, http_client_with_options, Monitor, DEFAULT_USER_AGENT};
+
+/// HTTP request made by a monitor
+#[derive(Debug, Clone, Default)]
+pub struct FetchRequest {
+    /// Request URL
+    pub url: String,
+    /// Extra request headers
+    pub headers: Vec<(String, String)>,
+    /// JSON body, sent as a POST when present
+    pub json: Option<serde_json::Value>,
+    /// Stop reading and fail once the body grows past this many bytes, None for no limit
+    pub max_body_bytes: Option<usize>,
+}
+
+impl FetchRequest {
+    /// GET request for a URL
+    pub fn get(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Add a request header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Fail instead of reading a body larger than `max_body_bytes`
+    pub fn max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = Some(max_body_bytes);
+        self
+    }
+
+    /// Send a raw `Cookie` header, e.g. a session copied from the browser
+    pub fn cookies(self, cookies: Option<&str>) -> Self {
+        match cookies {
+            Some(cookies) => self.header(COOKIE.as_str(), cookies),
+            None => self,
+        }
+    }
+}
+
+/// HTTP response as seen by a monitor
+#[derive(Debug, Clone, Default)]
+pub struct FetchResponse {
+    /// Status code
+    pub status: u16,
+    /// Response headers, names in lower case
+    pub headers: HashMap<String, String>,
+    /// Raw body
+    pub body: Vec<u8>,
+    /// Whether it was answered from a `ResponseCache` rather than requested, so its timing means nothing
+    pub cached: bool,
+}
+
+impl FetchResponse {
+    /// Whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Header value by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Body decoded as UTF-8, invalid sequences replaced
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Performs the HTTP requests of a monitor, so monitors can be tested without network
+#[async_trait::async_trait]
+pub trait HttpFetcher: Send + Sync {
+    /// Send a request and read the whole response
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse>;
+    
+    /// Client settings changing the response to a request, e.g. the User-Agent, so a cache never shares
+    /// responses between fetchers with different settings
+    fn settings(&self) -> String {
+        String::new()
+    }
+}
+
+/// Fetcher backed by a real `reqwest` client
+pub struct ReqwestFetcher {
+    client: Client,
+    user_agent: String,
+    accept_invalid_certs: bool,
+}
+
+impl ReqwestFetcher {
+    /// Create a fetcher using the shared monitor client settings
+    pub fn new() -> Self {
+        Self {
+            client: http_client(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            accept_invalid_certs: false,
+        }
+    }
+
+    /// Create a fetcher sending a custom User-Agent, or the default one, that may accept invalid TLS certificates
+    pub fn with_options(user_agent: Option<&str>, accept_invalid_certs: bool) -> Self {
+        let user_agent = user_agent.unwrap_or(DEFAULT_USER_AGENT);
+        Self {
+            client: http_client_with_options(user_agent, accept_invalid_certs),
+            user_agent: user_agent.to_string(),
+            accept_invalid_certs,
+        }
+    }
+}
+
+impl Default for ReqwestFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpFetcher for ReqwestFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse> {
+        let mut builder = match &request.json {
+            Some(json) => self.client.post(&request.url).json(json),
+            None => self.client.get(&request.url),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let mut response = builder
+            .send()
+            .await
+            .map_err(|e| anyhow!("{}", describe_error(&e)))?;
+
+        let status = response.status().as_u16();
+        let headers = response.headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = match request.max_body_bytes {
+            Some(max_body_bytes) => {
+                if response.content_length().is_some_and(|length| length > max_body_bytes as u64) {
+                    return Err(body_too_large(max_body_bytes));
+                }
+                // Streamed, so a huge or endless body is dropped once it passes the limit
+                let mut body = Vec::new();
+                while let Some(chunk) = response.chunk()
+                    .await
+                    .map_err(|e| anyhow!("Failed to read response content: {}", e))?
+                {
+                    if body.len() + chunk.len() > max_body_bytes {
+                        return Err(body_too_large(max_body_bytes));
+                    }
+                    body.extend_from_slice(&chunk);
+                }
+                body
+            }
+            None => response.bytes()
+                .await
+                .map_err(|e| anyhow!("Failed to read response content: {}", e))?
+                .to_vec(),
+        };
+
+        Ok(FetchResponse { status, headers, body, cached: false })
+    }
+    
+    fn settings(&self) -> String {
+        format!("{} accept_invalid_certs={}", self.user_agent, self.accept_invalid_certs)
+    }
+}
+
+/// User-Agent, TLS and cache settings of a monitor fetching over HTTP, with the fetcher built from them
+#[derive(Clone)]
+pub struct FetchOptions {
+    /// User-Agent sent instead of the default browser one
+    pub(crate) user_agent: Option<String>,
+    /// Skip TLS certificate verification, for internal endpoints with self-signed certificates
+    pub(crate) accept_invalid_certs: bool,
+    /// Fetcher set in place of a `ReqwestFetcher`, kept when the other options change
+    injected: Option<Arc<dyn HttpFetcher>>,
+    cache: Option<ResponseCache>,
+    fetcher: Arc<dyn HttpFetcher>,
+}
+
+impl FetchOptions {
+    /// Default options, fetching with a `ReqwestFetcher`
+    pub fn new() -> Self {
+        Self {
+            user_agent: None,
+            accept_invalid_certs: false,
+            injected: None,
+            cache: None,
+            fetcher: Arc::new(ReqwestFetcher::new()),
+        }
+    }
+
+    /// Fetcher performing the monitor's requests
+    pub fn fetcher(&self) -> &dyn HttpFetcher {
+        self.fetcher.as_ref()
+    }
+
+    /// Build the fetcher again after an option changed
+    fn rebuild(&mut self) {
+        let fetcher = match &self.injected {
+            Some(fetcher) => fetcher.clone(),
+            None => Arc::new(ReqwestFetcher::with_options(self.user_agent.as_deref(), self.accept_invalid_certs)),
+        };
+        self.fetcher = match &self.cache {
+            Some(cache) => cache.wrap(fetcher),
+            None => fetcher,
+        };
+    }
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Monitors fetching over HTTP, whose User-Agent, TLS and cache settings live in their `FetchOptions`.
+/// The settings may be applied in any order
+pub trait HttpMonitor: Monitor {
+    /// Options the monitor's fetcher is built from
+    fn fetch_options(&mut self) -> &mut FetchOptions;
+
+    /// Replace the HTTP fetcher, e.g. with canned responses in tests; User-Agent and TLS settings then don't apply
+    fn set_fetcher(&mut self, fetcher: Arc<dyn HttpFetcher>) {
+        let options = self.fetch_options();
+        options.injected = Some(fetcher);
+        options.rebuild();
+    }
+
+    /// Send a custom User-Agent instead of the default browser one, None or blank for the default
+    fn set_user_agent(&mut self, user_agent: Option<&str>) {
+        let options = self.fetch_options();
+        options.user_agent = custom_user_agent(user_agent).map(str::to_string);
+        options.rebuild();
+    }
+
+    /// Accept invalid TLS certificates, e.g. the self-signed one of an internal dashboard.
+    /// Insecure: the connection can then be intercepted without notice
+    fn set_accept_invalid_certs(&mut self, accept_invalid_certs: bool) {
+        if accept_invalid_certs && !self.fetch_options().accept_invalid_certs {
+            warn!("INSECURE: {} skips TLS certificate verification, its traffic can be intercepted", self.get_name());
+        }
+        let options = self.fetch_options();
+        options.accept_invalid_certs = accept_invalid_certs;
+        options.rebuild();
+    }
+
+    /// Share responses with the other monitors using the cache
+    fn set_response_cache(&mut self, cache: &ResponseCache) {
+        let options = self.fetch_options();
+        options.cache = Some(cache.clone());
+        options.rebuild();
+    }
+}
+
+/// Cached response slot, locked while its request is in flight so concurrent callers wait for it
+type CacheSlot = Arc<tokio::sync::Mutex<Option<(Instant, FetchResponse)>>>;
+
+/// Short-lived responses shared by the tasks that request the same URL, so they hit it once per TTL.
+/// Cheap to clone, clones share the entries
+#[derive(Clone)]
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, CacheSlot>>>,
+}
+
+impl ResponseCache {
+    /// Cache keeping successful responses for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Wrap a fetcher so its requests go through this cache. Responses are shared between fetchers with the same
+    /// `settings` only
+    pub fn wrap(&self, fetcher: Arc<dyn HttpFetcher>) -> Arc<dyn HttpFetcher> {
+        Arc::new(CachingFetcher { inner: fetcher, cache: self.clone() })
+    }
+
+    /// Slot for a request, dropping expired entries nobody is waiting on
+    fn slot(&self, key: String) -> CacheSlot {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let ttl = self.ttl;
+        entries.retain(|_, slot| {
+            Arc::strong_count(slot) > 1
+                || slot.try_lock().map_or(true, |entry| entry.as_ref().is_some_and(|(time, _)| time.elapsed() < ttl))
+        });
+        entries.entry(key).or_default().clone()
+    }
+}
+
+/// Key of a request: fetcher settings, method, URL, headers (cookies included), body and size limit
+fn cache_key(settings: &str, request: &FetchRequest) -> String {
+    let method = if request.json.is_some() { "POST" } else { "GET" };
+    let mut headers = request.headers.clone();
+    headers.sort();
+    let body = request.json.as_ref().map(|json| json.to_string()).unwrap_or_default();
+    format!("{:?} {} {} {:?} {} {:?}", settings, method, request.url, headers, body, request.max_body_bytes)
+}
+
+/// Fetcher answering from a `ResponseCache`, errors and non-2xx responses are never cached
+struct CachingFetcher {
+    inner: Arc<dyn HttpFetcher>,
+    cache: ResponseCache,
+}
+
+#[async_trait::async_trait]
+impl HttpFetcher for CachingFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse> {
+        let slot = self.cache.slot(cache_key(&self.inner.settings(), &request));
+        let mut entry = slot.lock().await;
+        if let Some((time, response)) = entry.as_ref() {
+            if time.elapsed() < self.cache.ttl {
+                return Ok(FetchResponse { cached: true, ..response.clone() });
+            }
+        }
+
+        let response = self.inner.fetch(request).await?;
+        *entry = response.is_success().then(|| (Instant::now(), response.clone()));
+        Ok(response)
+    }
+    
+    fn settings(&self) -> String {
+        self.inner.settings()
+    }
+}
+
+/// Error for a body over the size limit
+fn body_too_large(max_body_bytes: usize) -> anyhow::Error {
+    anyhow!("Response body is larger than {} KB, not reading it", max_body_bytes / 1024)
+}
+
+/// Fetcher returning canned responses in order, recording the requests it received
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockFetcher {
+    responses: std::sync::Mutex<std::collections::VecDeque<Result<FetchResponse, String>>>,
+    requests: std::sync::Mutex<Vec<FetchRequest>>,
+}
+
+#[cfg(test)]
+impl MockFetcher {
+    /// Queue a response with the given status and body
+    pub fn respond(self, status: u16, body: &str) -> Self {
+        self.respond_with(FetchResponse {
+            status,
+            body: body.as_bytes().to_vec(),
+            ..FetchResponse::default()
+        })
+    }
+
+    /// Queue a full response
+    pub fn respond_with(self, response: FetchResponse) -> Self {
+        self.responses.lock().unwrap().push_back(Ok(response));
+        self
+    }
+
+    /// Queue a transport error
+    pub fn fail(self, error: &str) -> Self {
+        self.responses.lock().unwrap().push_back(Err(error.to_string()));
+        self
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<FetchRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl HttpFetcher for MockFetcher {
+    async fn fetch(&self, request: FetchRequest) -> Result<FetchResponse> {
+        self.requests.lock().unwrap().push(request);
+        match self.responses.lock().unwrap().pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(e)) => Err(anyhow!("{}", e)),
+            None => Err(anyhow!("No canned response left")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cache_shares_responses_until_they_expire() {
+        let mock = Arc::new(MockFetcher::default().respond(200, "first").respond(200, "second").respond(200, "third"));
+        let cache = ResponseCache::new(Duration::from_millis(200));
+        let (a, b) = (cache.wrap(mock.clone()), cache.wrap(mock.clone()));
+
+        let (first, again) = tokio::join!(a.fetch(FetchRequest::get("http://x/")), b.fetch(FetchRequest::get("http://x/")));
+        let (first, again) = (first.unwrap(), again.unwrap());
+        assert_eq!(first.text(), "first");
+        assert_eq!(again.text(), "first");
+        assert!(first.cached != again.cached, "exactly one of them was requested");
+
+        // Different headers make a different request
+        let with_cookie = b.fetch(FetchRequest::get("http://x/").cookies(Some("a=1"))).await.unwrap();
+        assert_eq!(with_cookie.text(), "second");
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(a.fetch(FetchRequest::get("http://x/")).await.unwrap().text(), "third");
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn fetchers_with_other_settings_do_not_share_responses() {
+        let request = FetchRequest::get("http://x/");
+        let default = ReqwestFetcher::new().settings();
+        assert_eq!(cache_key(&default, &request), cache_key(&ReqwestFetcher::with_options(None, false).settings(), &request));
+        assert_ne!(cache_key(&default, &request), cache_key(&ReqwestFetcher::with_options(Some("bot/1.0"), false).settings(), &request));
+        assert_ne!(cache_key(&default, &request), cache_key(&ReqwestFetcher::with_options(None, true).settings(), &request));
+    }
+}
@@ -1,11 +1,50 @@
 use anyhow::{Result, anyhow};
-use log::{debug, info};
+use log::debug;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use ethers::types::H160;
 use serde_json::{Value, json};
 use reqwest::header;
 
-use crate::monitors::{Monitor, Change};
+use crate::monitors::{clamp_interval, monitor_id, DEFAULT_INTERVAL_SECS, MIN_INTERVAL_SECS_HYPERLIQUID, describe_error, http_client, Monitor, Change, ChangeKind};
+use crate::utils::{format_pct, format_significant, format_timestamp, format_usd};
+
+/// Hyperliquid info endpoint
+pub const HYPERLIQUID_API_URL: &str = "https://api.hyperliquid.xyz/info";
+
+/// Vault equity moves smaller than this (USD) are rounding, not deposits or profit
+const VAULT_EQUITY_EPSILON_USD: f64 = 0.005;
+
+/// Default smallest vault equity move reported, in percent; vault PnL drifts a little on every check
+pub const DEFAULT_VAULT_CHANGE_PCT: f64 = 1.0;
+
+/// Most fills `userFillsByTime` returns per request, a full page means there may be more
+const FILLS_PAGE_SIZE: usize = 2000;
+
+/// Explorer linked from notifications unless another one is chosen
+pub const DEFAULT_EXPLORER: &str = "hyperdash";
+
+/// Known explorers by name, with the URL template of an address page
+const EXPLORERS: [(&str, &str); 2] = [
+    ("hyperdash", "https://hyperdash.info/trader/{address}"),
+    ("hypurrscan", "https://hypurrscan.io/address/{address}"),
+];
+
+/// URL template of an explorer given by name, e.g. `hypurrscan`, or as a URL containing `{address}`
+pub fn explorer_template(explorer: &str) -> Result<String> {
+    let explorer = explorer.trim();
+    if let Some((_, template)) = EXPLORERS.iter().find(|(name, _)| name.eq_ignore_ascii_case(explorer)) {
+        return Ok(template.to_string());
+    }
+    if (explorer.starts_with("https://") || explorer.starts_with("http://")) && explorer.contains("{address}") {
+        return Ok(explorer.to_string());
+    }
+    Err(anyhow!(
+        "Unknown explorer {}, expected {} or a URL containing {{address}}",
+        explorer,
+        EXPLORERS.map(|(name, _)| name).join(", ")
+    ))
+}
 
 /// Hyperliquid user transaction monitor, used to monitor user transaction activities
 pub struct HyperliquidMonitor {
@@ -15,14 +54,46 @@ pub struct HyperliquidMonitor {
     interval_secs: u64,
     /// Whether to monitor spot transactions
     monitor_spot: bool,
-    /// Whether to monitor contract transactions
+    /// Whether to monitor contract positions and perp fills
     monitor_contract: bool,
+    /// Whether to monitor funding payments
+    monitor_funding: bool,
+    /// Whether to monitor spot token balances
+    monitor_spot_balance: bool,
+    /// Whether to monitor vault equity
+    monitor_vaults: bool,
+    /// Whether the first check recorded every tracker's state
+    initialized: bool,
     /// Last detected spot transaction ID
     last_spot_trade_id: Option<String>,
-    /// Last detected contract transaction ID
-    last_contract_trade_id: Option<String>,
+    /// Last detected perp transaction ID
+    last_perp_trade_id: Option<String>,
     /// Last positions hash value to detect position changes
     last_positions_hash: Option<String>,
+    /// Positions as of the last position notification
+    notified_positions: Vec<PositionInfo>,
+    /// Only report position changes whose size moved at least this percent, 0 for every change
+    position_change_threshold_pct: f64,
+    /// Only report vault equity moves of at least this percent, deposits and withdrawals are always reported
+    vault_change_threshold_pct: f64,
+    /// Alert when a position's unrealized PnL moved at least this much (USD), None for no PnL alerts
+    pnl_alert_threshold: Option<f64>,
+    /// Unrealized PnL of each position as of its last report, keyed by asset and side
+    pnl_baseline: HashMap<String, f64>,
+    /// Time (ms) of the last detected funding payment
+    last_funding_time: Option<u64>,
+    /// Sum of the funding payments detected since monitoring started (USDC)
+    session_funding: f64,
+    /// Total balance per spot token as of the last check, None before the first
+    spot_balances: Option<HashMap<String, f64>>,
+    /// Equity (USD) per vault address as of its last report, None before the first check
+    vault_equities: Option<HashMap<String, f64>>,
+    /// On the first check, report fills from this many recent minutes instead of only recording the latest
+    backfill_minutes: u64,
+    /// Info endpoint URL
+    api_url: String,
+    /// URL template of the explorer page linked from notifications, `{address}` being replaced
+    explorer_template: String,
     /// HTTP client
     client: reqwest::Client,
     /// User-provided notes/remarks
@@ -46,7 +117,9 @@ struct PositionInfo {
     size: f64,
     /// Position value
     position_value: f64,
-    /// Profit/Loss percentage
+    /// Unrealized profit/loss (USD)
+    unrealized_pnl: f64,
+    /// Return on equity (percent)
     pnl_percentage: f64,
 }
 
@@ -55,13 +128,29 @@ impl HyperliquidMonitor {
     pub fn new(address: &str, interval_secs: u64, monitor_spot: bool, monitor_contract: bool) -> Self {
         Self {
             address: address.to_string(),
-            interval_secs,
+            interval_secs: clamp_interval(interval_secs, MIN_INTERVAL_SECS_HYPERLIQUID, address),
             monitor_spot,
             monitor_contract,
+            monitor_funding: false,
+            monitor_spot_balance: false,
+            monitor_vaults: false,
+            initialized: false,
             last_spot_trade_id: None,
-            last_contract_trade_id: None,
+            last_perp_trade_id: None,
             last_positions_hash: None,
-            client: reqwest::Client::new(),
+            notified_positions: Vec::new(),
+            position_change_threshold_pct: 0.0,
+            vault_change_threshold_pct: DEFAULT_VAULT_CHANGE_PCT,
+            pnl_alert_threshold: None,
+            pnl_baseline: HashMap::new(),
+            last_funding_time: None,
+            session_funding: 0.0,
+            spot_balances: None,
+            vault_equities: None,
+            backfill_minutes: 0,
+            api_url: HYPERLIQUID_API_URL.to_string(),
+            explorer_template: EXPLORERS[0].1.to_string(),
+            client: http_client(),
             notes: address.to_string(), // Default to using address as the note
         }
     }
@@ -82,6 +171,101 @@ impl HyperliquidMonitor {
         }
     }
     
+    /// Use a different info endpoint, e.g. the testnet or a local mock server
+    pub fn set_api_url(&mut self, api_url: &str) {
+        self.api_url = api_url.to_string();
+    }
+    
+    /// Link to another explorer in notifications, by name (`hyperdash`, `hypurrscan`) or as a URL template
+    /// containing `{address}`
+    pub fn set_explorer(&mut self, explorer: &str) -> Result<()> {
+        self.explorer_template = explorer_template(explorer)?;
+        Ok(())
+    }
+    
+    /// Page of the user on the chosen explorer
+    fn explorer_link(&self) -> String {
+        self.explorer_template.replace("{address}", &self.address)
+    }
+    
+    /// Also report funding payments
+    pub fn set_monitor_funding(&mut self, monitor_funding: bool) {
+        self.monitor_funding = monitor_funding;
+    }
+    
+    /// Also report spot token balance changes, catching deposits, withdrawals and transfers that aren't fills
+    pub fn set_monitor_spot_balance(&mut self, monitor_spot_balance: bool) {
+        self.monitor_spot_balance = monitor_spot_balance;
+    }
+    
+    /// Also report deposits into, withdrawals from and equity changes of the user's vaults
+    pub fn set_monitor_vaults(&mut self, monitor_vaults: bool) {
+        self.monitor_vaults = monitor_vaults;
+    }
+    
+    /// Report fills from the last `minutes` on the first check, 0 to only record the latest fill
+    pub fn set_backfill_minutes(&mut self, minutes: u64) {
+        self.backfill_minutes = minutes;
+    }
+    
+    /// Only report position changes whose size moved at least `percent` since the last report,
+    /// smaller changes such as partial fills are recorded silently; 0 reports every change
+    pub fn set_position_change_threshold(&mut self, percent: f64) {
+        self.position_change_threshold_pct = if percent.is_finite() { percent.max(0.0) } else { 0.0 };
+    }
+    
+    /// Only report vault equity moves of at least `percent` since the vault was last reported, so PnL
+    /// drift adds up until it crosses the threshold; 0 reports every move over a cent
+    pub fn set_vault_change_threshold(&mut self, percent: f64) {
+        self.vault_change_threshold_pct = if percent.is_finite() { percent.max(0.0) } else { DEFAULT_VAULT_CHANGE_PCT };
+    }
+    
+    /// Alert when a position's unrealized PnL moved at least `usd` since it was last reported, e.g. from
+    /// the mark price moving, separately from position changes; None to only report position changes
+    pub fn set_pnl_alert_threshold(&mut self, usd: Option<f64>) {
+        self.pnl_alert_threshold = usd.filter(|usd| usd.is_finite() && *usd > 0.0);
+    }
+    
+    /// Report positions whose unrealized PnL moved past the alert threshold since they were last reported
+    fn check_pnl(&mut self, positions: &[PositionInfo]) -> Option<Change> {
+        let threshold = self.pnl_alert_threshold?;
+        
+        let moved: Vec<&PositionInfo> = positions
+            .iter()
+            .filter(|pos| {
+                let baseline = self.pnl_baseline.get(&pnl_key(pos)).copied().unwrap_or(pos.unrealized_pnl);
+                (pos.unrealized_pnl - baseline).abs() >= threshold
+            })
+            .collect();
+        if moved.is_empty() {
+            return None;
+        }
+        
+        let mut summary = Vec::new();
+        let mut details = String::new();
+        for pos in &moved {
+            let baseline = self.pnl_baseline.get(&pnl_key(pos)).copied().unwrap_or_default();
+            summary.push(format!("{} {} {} -> {}", pos.asset, pos.position_type, format_usd(baseline), format_usd(pos.unrealized_pnl)));
+            details.push_str(&format!(
+                "Asset: {}\nType: {}\nEntry price: {}\nMark price: {}\nPosition size: {}\nPNL: {} -> {} ({})\n\n",
+                pos.asset, pos.position_type, format_price(pos.entry_price), format_price(pos.mark_price), format_size(pos.size),
+                format_usd(baseline), format_usd(pos.unrealized_pnl), format_pct(pos.pnl_percentage)
+            ));
+        }
+        for pos in &moved {
+            self.pnl_baseline.insert(pnl_key(pos), pos.unrealized_pnl);
+        }
+        
+        Some(Change {
+            kind: ChangeKind::ThresholdCrossed,
+            message: format!("{} - PnL moved: {}", self.notes, summary.join(" | ")),
+            details: format!(
+                "Unrealized PnL moved by at least {} since last reported, positions unchanged:\n\n{}\nView more information: {}\n\nNotes: {}",
+                format_usd(threshold), details.trim(), self.explorer_link(), self.notes
+            ),
+        })
+    }
+    
     /// Convert address string to H160 type
     fn parse_address(&self) -> Result<H160> {
         H160::from_str(&self.address)
@@ -93,7 +277,7 @@ impl HyperliquidMonitor {
         debug!("Getting user contract positions: {}", self.address);
         
         // API endpoint
-        let url = "https://api.hyperliquid.xyz/info";
+        let url = &self.api_url;
         
         // Create request body - Use the proper request type for positions
         let data = json!({
@@ -102,7 +286,7 @@ impl HyperliquidMonitor {
         });
         
         debug!("Sending request to API: {}", url);
-        debug!("Request body: {}", data.to_string());
+        debug!("Request body: {}", data);
         
         // Send POST request
         let response = self.client.post(url)
@@ -122,7 +306,7 @@ impl HyperliquidMonitor {
             .await
             .map_err(|e| anyhow!("Parsing response failed: {}", e))?;
         
-        debug!("Full API response: {}", json.to_string());
+        debug!("Full API response: {}", json);
         
         // Extract positions from assetPositions field
         let positions = if let Some(positions) = json.get("assetPositions") {
@@ -152,20 +336,20 @@ impl HyperliquidMonitor {
         
         for pos in positions {
             pos.asset.hash(&mut hasher);
-            format!("{:.2}", pos.size).hash(&mut hasher);
-            format!("{:.2}", pos.entry_price).hash(&mut hasher);
+            format_significant(pos.size, 2).hash(&mut hasher);
+            format_price(pos.entry_price).hash(&mut hasher);
             pos.position_type.hash(&mut hasher);
         }
         
         format!("{:x}", hasher.finish())
     }
     
-    /// Get user spot transaction history
-    async fn get_spot_trades(&self) -> Result<Value> {
-        debug!("Getting user spot transaction history: {}", self.address);
+    /// Get user fills, spot and perp alike, newest first
+    async fn get_fills(&self) -> Result<Vec<Value>> {
+        debug!("Getting user fills: {}", self.address);
         
         // API endpoint
-        let url = "https://api.hyperliquid.xyz/info";
+        let url = &self.api_url;
         
         // Create request body
         let data = json!({
@@ -191,24 +375,78 @@ impl HyperliquidMonitor {
             .await
             .map_err(|e| anyhow!("Parsing response failed: {}", e))?;
             
-        Ok(data)
+        match data {
+            Value::Array(fills) => Ok(fills),
+            _ => Err(anyhow!("API returned data format is incorrect")),
+        }
     }
     
-    /// Get user contract transaction history
-    async fn get_contract_trades(&self) -> Result<Value> {
-        debug!("Getting user contract transaction history: {}", self.address);
+    /// Get every user fill between `start` and `end` (ms), newest first, paging through `userFillsByTime`
+    async fn get_fills_in_range(&self, start: u64, end: u64) -> Result<Vec<Value>> {
+        debug!("Getting user fills from {} to {}: {}", start, end, self.address);
         
-        // API endpoint
-        let url = "https://api.hyperliquid.xyz/info";
+        let mut fills: Vec<Value> = Vec::new();
+        let mut seen_ids = HashSet::new();
+        let mut page_start = start;
+        loop {
+            let data = json!({
+                "type": "userFillsByTime",
+                "user": self.address,
+                "startTime": page_start,
+                "endTime": end
+            });
+            
+            let response = self.client.post(&self.api_url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .json(&data)
+                .send()
+                .await
+                .map_err(|e| anyhow!("API request failed: {}", e))?;
+                
+            let status = response.status();
+            if !status.is_success() {
+                return Err(anyhow!("API request failed, status code: {}", status));
+            }
+            
+            let page: Vec<Value> = match response.json().await.map_err(|e| anyhow!("Parsing response failed: {}", e))? {
+                Value::Array(page) => page,
+                _ => return Err(anyhow!("API returned data format is incorrect")),
+            };
+            let page_len = page.len();
+            let last_time = page.iter().filter_map(|fill| fill["time"].as_u64()).max();
+            
+            // Pages are oldest first, the next one starts at the last time seen so fills sharing it aren't lost
+            for fill in page {
+                if fill_id(&fill).is_none_or(|id| seen_ids.insert(id)) {
+                    fills.push(fill);
+                }
+            }
+            
+            match last_time {
+                Some(last_time) if page_len >= FILLS_PAGE_SIZE && last_time < end => {
+                    // A full page of one millisecond can't be paged by time, move past it
+                    page_start = if last_time > page_start { last_time } else { page_start + 1 };
+                }
+                _ => break,
+            }
+        }
+        
+        fills.sort_by_key(|fill| std::cmp::Reverse(fill["time"].as_u64().unwrap_or(0)));
+        debug!("Got {} fills in range", fills.len());
+        Ok(fills)
+    }
+    
+    /// Get user funding payments since `start_time` (ms), oldest first
+    async fn get_funding(&self, start_time: u64) -> Result<Value> {
+        debug!("Getting user funding history: {}", self.address);
         
-        // Create request body
         let data = json!({
-            "type": "userFills",
-            "user": self.address
+            "type": "userFunding",
+            "user": self.address,
+            "startTime": start_time
         });
         
-        // Send POST request
-        let response = self.client.post(url)
+        let response = self.client.post(&self.api_url)
             .header(header::CONTENT_TYPE, "application/json")
             .json(&data)
             .send()
@@ -220,103 +458,385 @@ impl HyperliquidMonitor {
             return Err(anyhow!("API request failed, status code: {}", status));
         }
         
-        // Parse response
-        let data: Value = response.json()
+        response.json()
             .await
-            .map_err(|e| anyhow!("Parsing response failed: {}", e))?;
-            
-        Ok(data)
+            .map_err(|e| anyhow!("Parsing response failed: {}", e))
     }
     
-    /// Check user spot transaction changes
-    async fn check_spot_trades(&mut self) -> Result<Option<Change>> {
-        if !self.monitor_spot {
+    /// Check for new funding payments
+    async fn check_funding(&mut self) -> Result<Option<Change>> {
+        if !self.monitor_funding {
             return Ok(None);
         }
         
-        let trades = self.get_spot_trades().await?;
+        // Funding is paid hourly, a day back is enough to find the latest payment on the first check
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let start_time = match self.last_funding_time {
+            Some(last_time) => last_time + 1,
+            None => now_ms.saturating_sub(24 * 60 * 60 * 1000),
+        };
         
-        // Check if there are transaction records
-        let trades_array = trades.as_array()
+        let payments = self.get_funding(start_time).await?;
+        let payments = payments.as_array()
             .ok_or_else(|| anyhow!("API returned data format is incorrect"))?;
+        
+        let latest_time = payments.iter().filter_map(|payment| payment["time"].as_u64()).max();
+        
+        // First check, only record the latest payment time
+        let Some(last_time) = self.last_funding_time else {
+            debug!("First time getting funding records: {} payments", payments.len());
+            self.last_funding_time = Some(latest_time.unwrap_or(now_ms));
+            return Ok(None);
+        };
+        
+        let new_payments: Vec<&Value> = payments.iter()
+            .filter(|payment| payment["time"].as_u64().is_some_and(|time| time > last_time))
+            .collect();
+        if new_payments.is_empty() {
+            return Ok(None);
+        }
+        
+        let mut lines = Vec::new();
+        let mut total = 0.0;
+        for payment in &new_payments {
+            let delta = &payment["delta"];
+            let coin = delta["coin"].as_str().unwrap_or("Unknown");
+            let amount: f64 = delta["usdc"].as_str().and_then(|usdc| usdc.parse().ok()).unwrap_or(0.0);
+            let rate: f64 = delta["fundingRate"].as_str().and_then(|rate| rate.parse().ok()).unwrap_or(0.0);
+            let time = format_timestamp(payment["time"].as_u64().unwrap_or(0));
+            total += amount;
+            lines.push(format!("{} {:+.4} USDC (rate {:.4}%) at {}", coin, amount, rate * 100.0, time));
+        }
+        self.session_funding += total;
+        self.last_funding_time = latest_time;
+        
+        Ok(Some(Change {
+            kind: ChangeKind::Funding,
+            message: format!("{} - Funding {:+.4} USDC ({} payments)", self.notes, total, new_payments.len()),
+            details: format!(
+                "Funding payments:\nUser: {}\n{}\n\nSession funding total: {:+.4} USDC\n\nNotes: {}",
+                self.address, lines.join("\n"), self.session_funding, self.notes
+            ),
+        }))
+    }
+    
+    /// Send an info request of the given type about the user
+    async fn post_info(&self, request_type: &str) -> Result<Value> {
+        let response = self.client.post(&self.api_url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .json(&json!({ "type": request_type, "user": self.address }))
+            .send()
+            .await
+            .map_err(|e| anyhow!("API request failed: {}", e))?;
             
-        if trades_array.is_empty() {
-            debug!("No spot transaction records found");
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("API request failed, status code: {}", status));
+        }
+        
+        response.json()
+            .await
+            .map_err(|e| anyhow!("Parsing response failed: {}", e))
+    }
+    
+    /// Current positions, open orders and account value, reported whether or not anything changed
+    pub async fn snapshot(&self) -> Result<Change> {
+        self.parse_address()?;
+        let state = self.post_info("clearinghouseState").await?;
+        let orders = self.post_info("openOrders").await?;
+        
+        let positions = match state.get("assetPositions") {
+            Some(positions) => parse_positions(positions)?,
+            None => Vec::new(),
+        };
+        let orders = describe_open_orders(orders.as_array().map(Vec::as_slice).unwrap_or_default());
+        let usd = |value: &Value| format_usd(value.as_str().and_then(|usd| usd.parse().ok()).unwrap_or(0.0));
+        let account_value = usd(&state["marginSummary"]["accountValue"]);
+        
+        let positions_text = if positions.is_empty() {
+            "none\n\n".to_string()
+        } else {
+            positions.iter().map(describe_position).collect()
+        };
+        let orders_text = if orders.is_empty() { "none".to_string() } else { orders.join("\n") };
+        
+        Ok(Change {
+            kind: ChangeKind::Snapshot,
+            message: format!("{} - Account value {}, {} positions, {} open orders", self.notes, account_value, positions.len(), orders.len()),
+            details: format!(
+                "Wallet snapshot:\nUser: {}\nAccount value: {}\nMargin used: {}\nWithdrawable: {}\n\nPositions:\n{}Open orders:\n{}\n\nNotes: {}",
+                self.address,
+                account_value,
+                usd(&state["marginSummary"]["totalMarginUsed"]),
+                usd(&state["withdrawable"]),
+                positions_text,
+                orders_text,
+                self.notes
+            ),
+        })
+    }
+    
+    /// Get the user's total balance of each spot token
+    async fn get_spot_balances(&self) -> Result<HashMap<String, f64>> {
+        debug!("Getting user spot balances: {}", self.address);
+        
+        let state = self.post_info("spotClearinghouseState").await?;
+        parse_spot_balances(&state)
+    }
+    
+    /// Get the user's equity (USD) in each vault they deposited into
+    async fn get_vault_equities(&self) -> Result<HashMap<String, f64>> {
+        debug!("Getting user vault equities: {}", self.address);
+        
+        let equities = self.post_info("userVaultEquities").await?;
+        parse_vault_equities(&equities)
+    }
+    
+    /// Check for vault deposits, withdrawals and equity changes
+    async fn check_vaults(&mut self) -> Result<Option<Change>> {
+        if !self.monitor_vaults {
+            return Ok(None);
+        }
+        
+        let equities = self.get_vault_equities().await?;
+        
+        // First check, only record the equities
+        let Some(reported) = self.vault_equities.as_mut() else {
+            debug!("First time getting vault equities: {} vaults", equities.len());
+            self.vault_equities = Some(equities);
+            return Ok(None);
+        };
+        
+        // Only reported vaults move their baseline, smaller moves keep adding up against it
+        let changes = vault_changes(reported, &equities, self.vault_change_threshold_pct);
+        for (vault, _, new) in &changes {
+            if *new == 0.0 {
+                reported.remove(vault);
+            } else {
+                reported.insert(vault.clone(), *new);
+            }
+        }
+        let describe = |old: f64, new: f64| {
+            let delta = new - old;
+            let delta = if delta >= 0.0 { format!("+{}", format_usd(delta)) } else { format_usd(delta) };
+            if old == 0.0 {
+                format!("deposited, equity {}", format_usd(new))
+            } else if new == 0.0 {
+                format!("withdrawn, equity was {}", format_usd(old))
+            } else {
+                format!("equity {} -> {} ({})", format_usd(old), format_usd(new), delta)
+            }
+        };
+        let lines: Vec<String> = changes
+            .iter()
+            .map(|(vault, old, new)| format!("Vault {}: {}", vault, describe(*old, *new)))
+            .collect();
+        let message = match changes.as_slice() {
+            [] => return Ok(None),
+            [(vault, old, new)] => format!("{} - Vault {}: {}", self.notes, vault, describe(*old, *new)),
+            changes => format!("{} - {} vaults changed", self.notes, changes.len()),
+        };
+        
+        Ok(Some(Change {
+            kind: ChangeKind::Balance,
+            message,
+            details: format!("Vault changes:\nUser: {}\n{}\n\nNotes: {}", self.address, lines.join("\n"), self.notes),
+        }))
+    }
+    
+    /// Check for spot token balance changes
+    async fn check_spot_balances(&mut self) -> Result<Option<Change>> {
+        if !self.monitor_spot_balance {
+            return Ok(None);
+        }
+        
+        let balances = self.get_spot_balances().await?;
+        
+        // First check, only record the balances
+        let Some(previous) = self.spot_balances.replace(balances.clone()) else {
+            debug!("First time getting spot balances: {} tokens", balances.len());
+            return Ok(None);
+        };
+        
+        let changes = balance_changes(&previous, &balances, 0.0);
+        let lines: Vec<String> = changes
+            .iter()
+            .map(|(token, old, new)| format!("{}: {} -> {} ({:+})", token, old, new, new - old))
+            .collect();
+        let message = match changes.as_slice() {
+            [] => return Ok(None),
+            [(token, old, new)] => format!("{} - {} balance {:+}", self.notes, token, new - old),
+            changes => format!("{} - {} spot balances changed", self.notes, changes.len()),
+        };
+        
+        Ok(Some(Change {
+            kind: ChangeKind::Balance,
+            message,
+            details: format!("Spot balance changes:\nUser: {}\n{}\n\nNotes: {}", self.address, lines.join("\n"), self.notes),
+        }))
+    }
+    
+    /// Fills of every market being monitored, newest first; none without spot or contract monitoring
+    async fn get_monitored_fills(&self) -> Result<Vec<Value>> {
+        if !self.monitor_spot && !self.monitor_contract {
+            return Ok(Vec::new());
+        }
+        let mut fills = self.get_fills().await?;
+        fills.retain(|fill| self.monitors_market(fill_market(fill)));
+        Ok(fills)
+    }
+    
+    /// Whether fills on a market are reported, spot fills with `monitor_spot` and perp fills with `monitor_contract`
+    fn monitors_market(&self, market: FillMarket) -> bool {
+        match market {
+            FillMarket::Spot => self.monitor_spot,
+            FillMarket::Perp => self.monitor_contract,
+        }
+    }
+    
+    /// Last detected transaction ID on a market
+    fn last_trade_id(&mut self, market: FillMarket) -> &mut Option<String> {
+        match market {
+            FillMarket::Spot => &mut self.last_spot_trade_id,
+            FillMarket::Perp => &mut self.last_perp_trade_id,
+        }
+    }
+    
+    /// Check user transaction changes on one market, given the user's latest fills
+    async fn check_trades(&mut self, fills: &[Value], market: FillMarket) -> Result<Option<Change>> {
+        if !self.monitors_market(market) {
             return Ok(None);
         }
         
         // Get latest transaction record
-        let latest_trade = &trades_array[0];
+        let Some(latest_trade) = fills.iter().find(|fill| fill_market(fill) == market) else {
+            debug!("No {} transaction records found", market.name());
+            return Ok(None);
+        };
         
         // Extract transaction ID
-        let trade_id = latest_trade["tid"].as_str()
-            .ok_or_else(|| anyhow!("Transaction ID format is incorrect"))?
-            .to_string();
-            
-        // Check if there are new transactions
-        if let Some(last_id) = &self.last_spot_trade_id {
-            if last_id != &trade_id {
-                // Extract transaction details
-                let asset = latest_trade["asset"].as_str().unwrap_or("Unknown");
-                let side = if latest_trade["side"].as_str().unwrap_or("") == "B" { "Buy" } else { "Sell" };
-                let price = latest_trade["px"].as_str().unwrap_or("0");
-                let size = latest_trade["sz"].as_str().unwrap_or("0");
-                let time = latest_trade["time"].as_u64().unwrap_or(0);
-                
-                // Format transaction time
-                let formatted_time = format_timestamp(time);
-                
-                // Create change description
-                let change_description = format!(
-                    "New {} {}: Asset:{}, Price:{}, Size:{}, Time:{}",
-                    asset, side, asset, price, size, formatted_time
-                );
-                
-                // Build change notification with notes
-                let change = Change {
-                    message: format!("{} - {}", self.notes, change_description),
-                    details: format!(
-                        "Changed content:\nUser: {}\nAsset: {}\nSide: {}\nPrice: {}\nSize: {}\nTime: {}\nTransaction ID: {}\n\nPrevious transaction ID: {}\n\nNotes: {}",
-                        self.address, asset, side, price, size, formatted_time, trade_id,
-                        last_id, self.notes
-                    ),
-                };
-                
-                // Update last transaction ID
-                self.last_spot_trade_id = Some(trade_id);
-                
-                return Ok(Some(change));
-            }
-        } else {
-            // First check, send initial notification
-            debug!("First time getting spot transaction records");
-            
-            // Extract transaction details
-            let asset = latest_trade["asset"].as_str().unwrap_or("Unknown");
-            let side = if latest_trade["side"].as_str().unwrap_or("") == "B" { "Buy" } else { "Sell" };
-            let price = latest_trade["px"].as_str().unwrap_or("0");
-            let size = latest_trade["sz"].as_str().unwrap_or("0");
-            let time = latest_trade["time"].as_u64().unwrap_or(0);
+        let trade_id = fill_id(latest_trade)
+            .ok_or_else(|| anyhow!("Transaction ID format is incorrect"))?;
+        
+        // Extract transaction details
+        let asset = fill_coin(latest_trade);
+        let side = if latest_trade["side"].as_str().unwrap_or("") == "B" { "Buy" } else { "Sell" };
+        let price = latest_trade["px"].as_str().unwrap_or("0");
+        let size = latest_trade["sz"].as_str().unwrap_or("0");
+        let time = latest_trade["time"].as_u64().unwrap_or(0);
+        
+        // Format transaction time
+        let formatted_time = format_timestamp(time);
             
-            // Format transaction time
-            let formatted_time = format_timestamp(time);
+        // First check, send initial notification
+        if !self.initialized {
+            debug!("First time getting {} transaction records", market.name());
             
             // Build initial notification with notes
             let change = Change {
+                kind: ChangeKind::Started,
                 message: format!("Started monitoring: {}", self.notes),
                 details: format!(
-                    "Initial monitoring data:\nUser: {}\nLatest transaction:\nAsset: {}\nSide: {}\nPrice: {}\nSize: {}\nTime: {}\nTransaction ID: {}\n\nNotes: {}",
-                    self.address, asset, side, price, size, formatted_time, trade_id, self.notes
+                    "Initial monitoring data:\nUser: {}\nLatest {} transaction:\nAsset: {}\nSide: {}\nPrice: {}\nSize: {}\nTime: {}\nTransaction ID: {}\n\nNotes: {}",
+                    self.address, market.name(), asset, side, price, size, formatted_time, trade_id, self.notes
                 ),
             };
             
             // Update last transaction ID
-            self.last_spot_trade_id = Some(trade_id);
+            *self.last_trade_id(market) = Some(trade_id);
             
             return Ok(Some(change));
         }
         
-        Ok(None)
+        // Check if there are new transactions, the first one may come after monitoring started
+        let previous_id = self.last_trade_id(market).replace(trade_id.clone());
+        if previous_id.as_ref() == Some(&trade_id) {
+            return Ok(None);
+        }
+        
+        // Create change description
+        let change_description = format!(
+            "New {} {}: Asset:{}, Price:{}, Size:{}, Time:{}",
+            asset, side, asset, price, size, formatted_time
+        );
+        
+        // Build change notification with notes
+        Ok(Some(Change {
+            kind: ChangeKind::Trade,
+            message: format!("{} - {}", self.notes, change_description),
+            details: format!(
+                "Changed content:\nUser: {}\nMarket: {}\nAsset: {}\nSide: {}\nPrice: {}\nSize: {}\nTime: {}\nTransaction ID: {}\n\nPrevious transaction ID: {}\n\nNotes: {}",
+                self.address, market.name(), asset, side, price, size, formatted_time, trade_id,
+                previous_id.as_deref().unwrap_or("none"), self.notes
+            ),
+        }))
+    }
+    
+    /// Change listing every fill of the monitored markets inside the backfill window, None when backfill is off or
+    /// the window is empty
+    async fn backfill(&self) -> Result<Option<Change>> {
+        if self.backfill_minutes == 0 || (!self.monitor_spot && !self.monitor_contract) {
+            return Ok(None);
+        }
+        
+        let now_ms = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let cutoff = now_ms.saturating_sub(self.backfill_minutes * 60_000);
+        let recent: Vec<String> = self.get_fills_in_range(cutoff, now_ms).await?
+            .iter()
+            .filter(|fill| self.monitors_market(fill_market(fill)))
+            .map(describe_fill)
+            .collect();
+        if recent.is_empty() {
+            debug!("No fills in the last {} minutes", self.backfill_minutes);
+            return Ok(None);
+        }
+        
+        Ok(Some(Change {
+            kind: ChangeKind::Trade,
+            message: format!("{} - {} fills in the last {} minutes", self.notes, recent.len(), self.backfill_minutes),
+            details: format!(
+                "Recent fills:\nUser: {}\n{}\n\nNotes: {}",
+                self.address, recent.join("\n"), self.notes
+            ),
+        }))
+    }
+    
+    /// First check: record the state of every enabled tracker at once, so a task starts with a single `Started`
+    /// change rather than one per tracker over its first checks
+    async fn initialize(&mut self) -> Result<Option<Change>> {
+        let fills = self.get_monitored_fills().await?;
+        let changes = [
+            self.check_trades(&fills, FillMarket::Spot).await?,
+            self.check_trades(&fills, FillMarket::Perp).await?,
+            self.check_contract_positions().await?,
+            self.check_spot_balances().await?,
+            self.check_vaults().await?,
+            self.check_funding().await?,
+            self.backfill().await?,
+        ];
+        self.initialized = true;
+        
+        // Anything besides a start, like backfilled fills, is reported as a change
+        let (started, reported): (Vec<Change>, Vec<Change>) = changes
+            .into_iter()
+            .flatten()
+            .partition(|change| change.kind == ChangeKind::Started);
+        let mut reported = reported.into_iter();
+        if let Some(mut change) = reported.next() {
+            for other in reported {
+                change.details = format!("{}\n\n{}", change.details, other.details);
+            }
+            return Ok(Some(change));
+        }
+        
+        if started.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Change {
+            kind: ChangeKind::Started,
+            message: format!("Started monitoring: {}", self.notes),
+            details: started.into_iter().map(|change| change.details).collect::<Vec<_>>().join("\n\n"),
+        }))
     }
     
     /// Check user contract positions changes
@@ -342,10 +862,11 @@ impl HyperliquidMonitor {
             let change = if positions.is_empty() {
                 debug!("Initial check with no positions");
                 Change {
+                    kind: ChangeKind::Started,
                     message: format!("Started monitoring: {}", self.notes),
                     details: format!(
-                        "Started monitoring user: {}\n\nNo active positions currently\n\nView more information: @https://hyperdash.info/trader/{}\n\nNotes: {}",
-                        self.address, self.address, self.notes
+                        "Started monitoring user: {}\n\nNo active positions currently\n\nView more information: {}\n\nNotes: {}",
+                        self.address, self.explorer_link(), self.notes
                     ),
                 }
             } else {
@@ -359,24 +880,20 @@ impl HyperliquidMonitor {
                 for pos in &positions {
                     // Prepare first position info for title
                     if position_info_for_title.is_empty() {
-                        position_info_for_title = format!("Asset:{} Lever:{:.0}x Type:{} Entry price:{:.2}",
-                            pos.asset, pos.leverage, pos.position_type, pos.entry_price);
+                        position_info_for_title = format!("Asset:{} Lever:{:.0}x Type:{} Entry price:{}",
+                            pos.asset, pos.leverage, pos.position_type, format_price(pos.entry_price));
                     }
                     
-                    position_details.push_str(&format!(
-                        "Asset: {}\nLeverage: {:.0}x\nType: {}\nEntry price: {:.2}\nMark price: {:.2}\nPosition size: {:.4}\nPosition value: ${:.2}\nPNL: {:.2}%\n\n",
-                        pos.asset, pos.leverage, pos.position_type, 
-                        pos.entry_price, pos.mark_price, pos.size, 
-                        pos.position_value, pos.pnl_percentage
-                    ));
+                    position_details.push_str(&describe_position(pos));
                 }
                 
                 // Build change notification with notes
                 Change {
+                    kind: ChangeKind::Started,
                     message: format!("Started monitoring: {}", self.notes),
                     details: format!(
-                        "User's current positions:\n\n{}\nView more information: @https://hyperdash.info/trader/{}\n\nNotes: {}",
-                        position_details.trim(), self.address, self.notes
+                        "User's current positions:\n\n{}\nView more information: {}\n\nNotes: {}",
+                        position_details.trim(), self.explorer_link(), self.notes
                     ),
                 }
             };
@@ -384,6 +901,8 @@ impl HyperliquidMonitor {
             // Update last positions hash and return the change
             let hash_clone = positions_hash.clone();
             self.last_positions_hash = Some(positions_hash);
+            self.pnl_baseline = positions.iter().map(|pos| (pnl_key(pos), pos.unrealized_pnl)).collect();
+            self.notified_positions = positions;
             debug!("Updated last positions hash on first check: {}", hash_clone);
             debug!("Returning change: {}", change.message);
             return Ok(Some(change));
@@ -396,14 +915,21 @@ impl HyperliquidMonitor {
             if *last_hash != positions_hash {
                 debug!("Position hash changed: {} -> {}", last_hash, positions_hash);
                 
+                if is_minor_position_change(&self.notified_positions, &positions, self.position_change_threshold_pct) {
+                    debug!("Position sizes moved less than {}%, not reporting", self.position_change_threshold_pct);
+                    self.last_positions_hash = Some(positions_hash);
+                    return Ok(self.check_pnl(&positions));
+                }
+                
                 // Positions have changed
                 let change = if positions.is_empty() {
                     debug!("Positions changed to empty");
                     Change {
+                        kind: ChangeKind::Position,
                         message: format!("No active positions - {}", self.notes),
                         details: format!(
-                            "User: {}\n\nNo active positions currently\n\nView more information: @https://hyperdash.info/trader/{}\n\nNotes: {}",
-                            self.address, self.address, self.notes
+                            "User: {}\n\nNo active positions currently\n\nView more information: {}\n\nNotes: {}",
+                            self.address, self.explorer_link(), self.notes
                         ),
                     }
                 } else {
@@ -416,27 +942,23 @@ impl HyperliquidMonitor {
                     // Format the first position for the title
                     if !positions.is_empty() {
                         let first_pos = &positions[0];
-                        title_parts.push(format!("Asset:{} Lever:{:.0}x Type:{} Entry price:{:.2}",
+                        title_parts.push(format!("Asset:{} Lever:{:.0}x Type:{} Entry price:{}",
                             first_pos.asset, first_pos.leverage, 
-                            first_pos.position_type, first_pos.entry_price));
+                            first_pos.position_type, format_price(first_pos.entry_price)));
                     }
                     
                     // Format all positions for details
                     for pos in &positions {
-                        position_details.push_str(&format!(
-                            "Asset: {}\nLeverage: {:.0}x\nType: {}\nEntry price: {:.2}\nMark price: {:.2}\nPosition size: {:.4}\nPosition value: ${:.2}\nPNL: {:.2}%\n\n",
-                            pos.asset, pos.leverage, pos.position_type, 
-                            pos.entry_price, pos.mark_price, pos.size, 
-                            pos.position_value, pos.pnl_percentage
-                        ));
+                        position_details.push_str(&describe_position(pos));
                     }
                     
                     // Build change notification with notes
                     Change {
+                        kind: ChangeKind::Position,
                         message: format!("{} - {}", self.notes, title_parts.join(" | ")),
                         details: format!(
-                            "User position changes:\n\n{}\nView more information: @https://hyperdash.info/trader/{}\n\nNotes: {}",
-                            position_details.trim(), self.address, self.notes
+                            "User position changes:\n\n{}\nView more information: {}\n\nNotes: {}",
+                            position_details.trim(), self.explorer_link(), self.notes
                         ),
                     }
                 };
@@ -444,66 +966,237 @@ impl HyperliquidMonitor {
                 // Update last positions hash
                 let hash_clone = positions_hash.clone();
                 self.last_positions_hash = Some(positions_hash);
+                self.pnl_baseline = positions.iter().map(|pos| (pnl_key(pos), pos.unrealized_pnl)).collect();
+                self.notified_positions = positions;
                 debug!("Updated positions hash after change: {}", hash_clone);
                 debug!("Returning change: {}", change.message);
                 return Ok(Some(change));
             } else {
                 debug!("No position changes detected, hash remained: {}", positions_hash);
+                return Ok(self.check_pnl(&positions));
             }
         }
         
         Ok(None)
     }
-    
-    /// Check user contract transaction changes (old code kept as backup)
-    async fn check_contract_trades(&mut self) -> Result<Option<Change>> {
-        // Check position changes first
-        if let Some(change) = self.check_contract_positions().await? {
-            return Ok(Some(change));
-        }
-        
-        if !self.monitor_contract {
-            return Ok(None);
+}
+
+impl HyperliquidMonitor {
+    /// Start building a monitor of spot and contract activity, checking every `DEFAULT_INTERVAL_SECS` unless set otherwise
+    pub fn builder(address: &str) -> HyperliquidMonitorBuilder {
+        HyperliquidMonitorBuilder {
+            monitor: HyperliquidMonitor::new(address, DEFAULT_INTERVAL_SECS, true, true),
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            error: None,
         }
-        
-        // Original code - Check transaction history changes
-        let trades = self.get_contract_trades().await?;
-        
-        // Check if there are transaction records
-        let trades_array = trades.as_array()
-            .ok_or_else(|| anyhow!("API returned data format is incorrect"))?;
-            
-        if trades_array.is_empty() {
-            debug!("No contract transaction records found");
-            return Ok(None);
+    }
+}
+
+/// Builds a `HyperliquidMonitor` with chained options, failing on `build` when one is invalid
+pub struct HyperliquidMonitorBuilder {
+    monitor: HyperliquidMonitor,
+    interval_secs: u64,
+    /// First invalid option, returned by `build`
+    error: Option<anyhow::Error>,
+}
+
+impl HyperliquidMonitorBuilder {
+    /// Notes/remarks shown in notifications
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.monitor.set_notes(notes);
+        self
+    }
+
+    /// Check interval (seconds), raised to `MIN_INTERVAL_SECS_HYPERLIQUID`
+    pub fn interval(mut self, interval_secs: u64) -> Self {
+        self.interval_secs = interval_secs;
+        self
+    }
+
+    /// Whether to monitor spot fills
+    pub fn spot(mut self, monitor_spot: bool) -> Self {
+        self.monitor.monitor_spot = monitor_spot;
+        self
+    }
+
+    /// Whether to monitor contract positions and perp fills
+    pub fn contract(mut self, monitor_contract: bool) -> Self {
+        self.monitor.monitor_contract = monitor_contract;
+        self
+    }
+
+    /// Whether to report funding payments
+    pub fn monitor_funding(mut self, monitor_funding: bool) -> Self {
+        self.monitor.set_monitor_funding(monitor_funding);
+        self
+    }
+
+    /// Whether to report spot balance changes
+    pub fn monitor_spot_balance(mut self, monitor_spot_balance: bool) -> Self {
+        self.monitor.set_monitor_spot_balance(monitor_spot_balance);
+        self
+    }
+
+    /// Whether to report vault deposits, withdrawals and equity changes
+    pub fn monitor_vaults(mut self, monitor_vaults: bool) -> Self {
+        self.monitor.set_monitor_vaults(monitor_vaults);
+        self
+    }
+
+    /// Fills from the last `minutes` reported on the first check
+    pub fn backfill_minutes(mut self, minutes: u64) -> Self {
+        self.monitor.set_backfill_minutes(minutes);
+        self
+    }
+
+    /// Smallest position size change reported, in percent
+    pub fn position_change_threshold(mut self, percent: f64) -> Self {
+        self.monitor.set_position_change_threshold(percent);
+        self
+    }
+
+    /// Smallest vault equity move reported, in percent
+    pub fn vault_change_threshold(mut self, percent: f64) -> Self {
+        self.monitor.set_vault_change_threshold(percent);
+        self
+    }
+
+    /// Unrealized PnL move (USD) that raises an alert
+    pub fn pnl_alert_threshold(mut self, usd: Option<f64>) -> Self {
+        self.monitor.set_pnl_alert_threshold(usd);
+        self
+    }
+
+    /// Info endpoint URL
+    pub fn api_url(mut self, api_url: &str) -> Self {
+        self.monitor.set_api_url(api_url);
+        self
+    }
+
+    /// Explorer linked in notifications, by name or as a URL template
+    pub fn explorer(mut self, explorer: &str) -> Self {
+        if let Err(e) = self.monitor.set_explorer(explorer) {
+            self.error.get_or_insert(e);
         }
-        
-        // Get latest transaction record
-        let latest_trade = &trades_array[0];
-        
-        // Extract transaction ID
-        let trade_id = latest_trade["tid"].as_str()
-            .ok_or_else(|| anyhow!("Transaction ID format is incorrect"))?
-            .to_string();
-            
-        // Check if there are new transactions
-        if let Some(last_id) = &self.last_contract_trade_id {
-            if last_id != &trade_id {
-                // Update last transaction ID
-                self.last_contract_trade_id = Some(trade_id);
-                
-                // Replaced by position monitoring, only update ID without sending notification
-                return Ok(None);
-            }
-        } else {
-            // First check, just record the ID
-            self.last_contract_trade_id = Some(trade_id);
-            // Replaced by position monitoring, only record ID without sending notification
-            return Ok(None);
+        self
+    }
+
+    /// Build the monitor, failing on an unknown explorer
+    pub fn build(mut self) -> Result<HyperliquidMonitor> {
+        if let Some(e) = self.error {
+            return Err(e);
         }
-        
-        Ok(None)
+        self.monitor.interval_secs = clamp_interval(self.interval_secs, MIN_INTERVAL_SECS_HYPERLIQUID, &self.monitor.address);
+        Ok(self.monitor)
+    }
+}
+
+/// Total balance of each token in a `spotClearinghouseState` response
+fn parse_spot_balances(data: &Value) -> Result<HashMap<String, f64>> {
+    let balances = data["balances"]
+        .as_array()
+        .ok_or_else(|| anyhow!("API returned data format is incorrect"))?;
+    Ok(balances
+        .iter()
+        .filter_map(|balance| {
+            let coin = balance["coin"].as_str()?;
+            let total: f64 = balance["total"].as_str()?.parse().ok()?;
+            Some((coin.to_string(), total))
+        })
+        .collect())
+}
+
+/// Equity of each vault in a `userVaultEquities` response, keyed by vault address
+fn parse_vault_equities(data: &Value) -> Result<HashMap<String, f64>> {
+    let vaults = data
+        .as_array()
+        .ok_or_else(|| anyhow!("API returned data format is incorrect"))?;
+    Ok(vaults
+        .iter()
+        .filter_map(|vault| {
+            let address = vault["vaultAddress"].as_str()?;
+            let equity: f64 = vault["equity"].as_str()?.parse().ok()?;
+            Some((address.to_string(), equity))
+        })
+        .collect())
+}
+
+/// Keys whose balance moved more than `min_delta` between two checks as (key, old, new), sorted by key;
+/// a key missing on one side has a zero balance there
+fn balance_changes(previous: &HashMap<String, f64>, current: &HashMap<String, f64>, min_delta: f64) -> Vec<(String, f64, f64)> {
+    let tokens: HashSet<&String> = previous.keys().chain(current.keys()).collect();
+    let mut changes: Vec<(String, f64, f64)> = tokens
+        .into_iter()
+        .map(|token| (token.clone(), previous.get(token).copied().unwrap_or(0.0), current.get(token).copied().unwrap_or(0.0)))
+        .filter(|(_, old, new)| (new - old).abs() > min_delta)
+        .collect();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    changes
+}
+
+/// Vaults whose equity moved at least `threshold_pct` percent between two checks as (vault, old, new),
+/// sorted by vault; deposits into and withdrawals from a vault are always included
+fn vault_changes(previous: &HashMap<String, f64>, current: &HashMap<String, f64>, threshold_pct: f64) -> Vec<(String, f64, f64)> {
+    balance_changes(previous, current, VAULT_EQUITY_EPSILON_USD)
+        .into_iter()
+        .filter(|(_, old, new)| *old == 0.0 || *new == 0.0 || ((new - old) / old).abs() * 100.0 >= threshold_pct)
+        .collect()
+}
+
+/// Price with cents, or four significant figures for tokens under $1
+fn format_price(price: f64) -> String {
+    format_significant(price, 2)
+}
+
+/// Position size with four decimals, more for tiny sizes
+fn format_size(size: f64) -> String {
+    format_significant(size, 4)
+}
+
+/// Details of a position, one field per line followed by a blank line
+fn describe_position(pos: &PositionInfo) -> String {
+    format!(
+        "Asset: {}\nLeverage: {:.0}x\nType: {}\nEntry price: {}\nMark price: {}\nPosition size: {}\nPosition value: {}\nPNL: {} ({})\n\n",
+        pos.asset, pos.leverage, pos.position_type,
+        format_price(pos.entry_price), format_price(pos.mark_price), format_size(pos.size),
+        format_usd(pos.position_value), format_usd(pos.unrealized_pnl), format_pct(pos.pnl_percentage)
+    )
+}
+
+/// One line per resting order from an `openOrders` response, e.g. `BTC Buy 0.1 @ 60000`
+fn describe_open_orders(orders: &[Value]) -> Vec<String> {
+    orders
+        .iter()
+        .map(|order| {
+            let side = if order["side"].as_str().unwrap_or("") == "B" { "Buy" } else { "Sell" };
+            format!(
+                "{} {} {} @ {}",
+                order["coin"].as_str().unwrap_or("Unknown"),
+                side,
+                order["sz"].as_str().unwrap_or("0"),
+                order["limitPx"].as_str().unwrap_or("0")
+            )
+        })
+        .collect()
+}
+
+/// Key of a position's PnL baseline, its asset and side
+fn pnl_key(pos: &PositionInfo) -> String {
+    format!("{} {}", pos.asset, pos.position_type)
+}
+
+/// Whether positions only changed size by less than `threshold_pct` percent each, with the same assets and sides
+fn is_minor_position_change(previous: &[PositionInfo], current: &[PositionInfo], threshold_pct: f64) -> bool {
+    if threshold_pct <= 0.0 || previous.len() != current.len() {
+        return false;
     }
+    
+    current.iter().all(|pos| {
+        previous
+            .iter()
+            .find(|old| old.asset == pos.asset && old.position_type == pos.position_type)
+            .is_some_and(|old| old.size > 0.0 && (pos.size - old.size).abs() / old.size * 100.0 < threshold_pct)
+    })
 }
 
 /// Helper function: Parse position data
@@ -565,6 +1258,13 @@ fn parse_positions(data: &Value) -> Result<Vec<PositionInfo>> {
                     .parse::<f64>()
                     .unwrap_or(0.0);
                 
+                // Parse return on equity, a fraction of the margin
+                let pnl_percentage = position.get("returnOnEquity")
+                    .and_then(|r| r.as_str())
+                    .unwrap_or("0")
+                    .parse::<f64>()
+                    .unwrap_or(0.0) * 100.0;
+                
                 // Parse leverage
                 let leverage = position.get("leverage")
                     .and_then(|l| l.get("value"))
@@ -579,10 +1279,12 @@ fn parse_positions(data: &Value) -> Result<Vec<PositionInfo>> {
                     leverage,
                     position_type,
                     entry_price,
-                    mark_price: 0.0, // Not available in the response
+                    // Not in the response, derived from the position value
+                    mark_price: position_value / size,
                     size,
                     position_value,
-                    pnl_percentage: unrealized_pnl,
+                    unrealized_pnl,
+                    pnl_percentage,
                 });
             } else {
                 debug!("Position field not found for {}", asset);
@@ -596,20 +1298,79 @@ fn parse_positions(data: &Value) -> Result<Vec<PositionInfo>> {
     Ok(positions)
 }
 
-/// Helper function: Format timestamp
-fn format_timestamp(timestamp: u64) -> String {
-    use chrono::{TimeZone, Local};
-    match Local.timestamp_opt(timestamp as i64 / 1000, 0) {
-        chrono::LocalResult::Single(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
-        _ => format!("{}(Invalid timestamp)", timestamp),
+/// Market a fill was made on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FillMarket {
+    /// Spot pair
+    Spot,
+    /// Perpetual contract
+    Perp,
+}
+
+impl FillMarket {
+    /// Lowercase name for messages
+    fn name(self) -> &'static str {
+        match self {
+            FillMarket::Spot => "spot",
+            FillMarket::Perp => "perp",
+        }
+    }
+}
+
+/// Helper function: Coin of a fill, e.g. `BTC` for perps, `@107` or `PURR/USDC` for spot
+fn fill_coin(fill: &Value) -> &str {
+    fill["coin"].as_str()
+        .or_else(|| fill["asset"].as_str())
+        .unwrap_or("Unknown")
+}
+
+/// Helper function: Classify a fill by its coin, spot pairs are named `@<index>` or `BASE/QUOTE`
+pub(crate) fn fill_market(fill: &Value) -> FillMarket {
+    let coin = fill_coin(fill);
+    if coin.starts_with('@') || coin.contains('/') {
+        FillMarket::Spot
+    } else {
+        FillMarket::Perp
+    }
+}
+
+/// Helper function: Transaction ID of a fill, sent as a number by the API
+fn fill_id(fill: &Value) -> Option<String> {
+    match &fill["tid"] {
+        Value::String(tid) => Some(tid.clone()),
+        Value::Number(tid) => Some(tid.to_string()),
+        _ => None,
     }
 }
 
+/// Helper function: One-line summary of a fill
+pub(crate) fn describe_fill(trade: &Value) -> String {
+    let asset = fill_coin(trade);
+    let side = if trade["side"].as_str().unwrap_or("") == "B" { "Buy" } else { "Sell" };
+    format!(
+        "{} {} {} @ {} at {} (ID {})",
+        side,
+        trade["sz"].as_str().unwrap_or("0"),
+        asset,
+        trade["px"].as_str().unwrap_or("0"),
+        format_timestamp(trade["time"].as_u64().unwrap_or(0)),
+        fill_id(trade).unwrap_or_else(|| "?".to_string())
+    )
+}
+
 #[async_trait::async_trait]
 impl Monitor for HyperliquidMonitor {
     async fn check(&mut self) -> Result<Option<Change>> {
-        // Check spot transactions
-        if let Some(change) = self.check_spot_trades().await? {
+        if !self.initialized {
+            return self.initialize().await;
+        }
+        
+        // Check spot and perp transactions
+        let fills = self.get_monitored_fills().await?;
+        if let Some(change) = self.check_trades(&fills, FillMarket::Spot).await? {
+            return Ok(Some(change));
+        }
+        if let Some(change) = self.check_trades(&fills, FillMarket::Perp).await? {
             return Ok(Some(change));
         }
         
@@ -618,7 +1379,15 @@ impl Monitor for HyperliquidMonitor {
             return Ok(Some(change));
         }
         
-        Ok(None)
+        if let Some(change) = self.check_spot_balances().await? {
+            return Ok(Some(change));
+        }
+        
+        if let Some(change) = self.check_vaults().await? {
+            return Ok(Some(change));
+        }
+        
+        self.check_funding().await
     }
     
     fn interval(&self) -> u64 {
@@ -632,4 +1401,79 @@ impl Monitor for HyperliquidMonitor {
     fn get_notes(&self) -> String {
         self.notes.clone()
     }
-} 
\ No newline at end of file
+    
+    fn id(&self) -> String {
+        let flags = [self.monitor_spot, self.monitor_contract, self.monitor_funding, self.monitor_spot_balance, self.monitor_vaults]
+            .map(|flag| if flag { "1" } else { "0" });
+        monitor_id("hyperliquid", &[&self.address.to_lowercase(), flags[0], flags[1], flags[2], flags[3], flags[4]])
+    }
+    
+    async fn preflight(&self) -> Result<()> {
+        self.parse_address()?;
+        
+        let response = self.client.post(&self.api_url)
+            .json(&json!({
+                "type": "clearinghouseState",
+                "user": self.address
+            }))
+            .send()
+            .await
+            .map_err(|e| anyhow!("{}", describe_error(&e)))?;
+        
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("Info endpoint returned status {}", status));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recorded response in `tests/fixtures/<name>.json`
+    fn fixture(name: &str) -> Value {
+        let path = format!("{}/tests/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn positions_skip_closed_ones_and_keep_their_side() {
+        let positions = parse_positions(&fixture("clearinghouse_state_mixed")["assetPositions"]).unwrap();
+        let summary: Vec<(&str, &str, f64, f64)> = positions
+            .iter()
+            .map(|pos| (pos.asset.as_str(), pos.position_type.as_str(), pos.size, pos.leverage))
+            .collect();
+        assert_eq!(summary, [("HYPE", "short", 50.0, 3.0), ("kPEPE", "long", 10_000.0, 10.0)]);
+
+        let short = &positions[0];
+        assert_eq!(short.mark_price, 1233.5 / 50.0);
+        assert!((short.pnl_percentage - 5.14).abs() < 1e-9);
+
+        assert!(parse_positions(&fixture("clearinghouse_state_flat")["assetPositions"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn explorers_are_named_or_given_as_templates() {
+        let mut monitor = HyperliquidMonitor::new("0xabc", 60, true, true);
+        assert_eq!(monitor.explorer_link(), "https://hyperdash.info/trader/0xabc");
+        monitor.set_explorer("HypurrScan").unwrap();
+        assert_eq!(monitor.explorer_link(), "https://hypurrscan.io/address/0xabc");
+        monitor.set_explorer("https://app.hyperliquid.xyz/explorer/address/{address}").unwrap();
+        assert_eq!(monitor.explorer_link(), "https://app.hyperliquid.xyz/explorer/address/0xabc");
+
+        assert!(monitor.set_explorer("etherscan").is_err());
+        assert!(monitor.set_explorer("https://example.com/trader").is_err());
+    }
+
+    #[test]
+    fn spot_balances_and_open_orders_parse_from_recorded_responses() {
+        let balances = parse_spot_balances(&fixture("spot_clearinghouse_state")).unwrap();
+        assert_eq!(balances.len(), 3);
+        assert_eq!(balances["HYPE"], 12.0);
+
+        let orders = describe_open_orders(fixture("open_orders").as_array().unwrap());
+        assert_eq!(orders, ["BTC Buy 0.05 @ 60000.0", "@107 Sell 8.0 @ 27.5"]);
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{Result, anyhow};
+use headless_chrome::{Browser, LaunchOptionsBuilder, Tab};
+use log::{debug, info};
+use std::time::{Duration, Instant};
+
+use crate::monitors::fetcher::ReqwestFetcher;
+use crate::monitors::{clamp_interval, monitor_id, MIN_INTERVAL_SECS_JS, probe_url, Change, ChangeKind, Monitor};
+
+/// How long rendered content may keep changing before it is taken as is
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval between DOM snapshots while waiting for the page to settle
+const SETTLE_POLL: Duration = Duration::from_millis(500);
+
+/// Longest content excerpt included in notifications
+const MAX_EXCERPT_CHARS: usize = 500;
+
+/// Monitor a page rendered by JavaScript, loaded in headless Chrome so content fetched client-side is seen
+pub struct JsMonitor {
+    /// Page URL
+    url: String,
+    /// CSS selector limiting the monitored text, empty for the whole page
+    selector: String,
+    /// Check interval (seconds)
+    interval_secs: u64,
+    /// Visible text from the last check
+    last_text: Option<String>,
+    /// User-provided notes/remarks
+    notes: String,
+}
+
+impl JsMonitor {
+    /// Create a new JS-rendered page monitor
+    pub fn new(url: &str, selector: &str, interval_secs: u64) -> Self {
+        Self {
+            url: url.to_string(),
+            selector: selector.trim().to_string(),
+            interval_secs: clamp_interval(interval_secs, MIN_INTERVAL_SECS_JS, url),
+            last_text: None,
+            notes: url.to_string(),
+        }
+    }
+
+    /// Create a new JS-rendered page monitor with notes
+    pub fn new_with_notes(url: &str, selector: &str, interval_secs: u64, notes: &str) -> Self {
+        let mut monitor = Self::new(url, selector, interval_secs);
+        if !notes.trim().is_empty() {
+            monitor.notes = notes.to_string();
+        }
+        monitor
+    }
+
+    /// Render the page and extract the visible text of the monitored part
+    async fn get_text(&self) -> Result<String> {
+        let selector = (!self.selector.is_empty()).then_some(self.selector.as_str());
+        let html = render_page(&self.url, selector, None).await?;
+        debug!("Rendered page: {} bytes", html.len());
+        visible_text(&html, selector)
+    }
+}
+
+/// Load a URL in headless Chrome and return the rendered DOM once it stops changing,
+/// waiting for `wait_for` to match first when given and sending `user_agent` instead of Chrome's own
+pub async fn render_page(url: &str, wait_for: Option<&str>, user_agent: Option<&str>) -> Result<String> {
+    let url = url.trim().to_string();
+    let wait_for = wait_for.map(str::to_string);
+    let user_agent = user_agent.map(str::to_string);
+    tokio::task::spawn_blocking(move || render_blocking(&url, wait_for.as_deref(), user_agent.as_deref()))
+        .await
+        .map_err(|e| anyhow!("Browser task failed: {}", e))?
+}
+
+/// Blocking part of `render_page`, the browser API is synchronous
+fn render_blocking(url: &str, wait_for: Option<&str>, user_agent: Option<&str>) -> Result<String> {
+    let options = LaunchOptionsBuilder::default()
+        .headless(true)
+        .build()
+        .map_err(|e| anyhow!("Invalid browser options: {}", e))?;
+    let browser = Browser::new(options)
+        .map_err(|e| anyhow!("Failed to launch Chrome/Chromium, is it installed? {}", e))?;
+    let tab = browser.wait_for_initial_tab()
+        .map_err(|e| anyhow!("Failed to open a browser tab: {}", e))?;
+    if let Some(user_agent) = user_agent {
+        tab.set_user_agent(user_agent, None, None)
+            .map_err(|e| anyhow!("Failed to set the User-Agent: {}", e))?;
+    }
+
+    tab.navigate_to(url)
+        .and_then(|tab| tab.wait_until_navigated())
+        .map_err(|e| anyhow!("Failed to load {}: {}", url, e))?;
+
+    if let Some(selector) = wait_for {
+        tab.wait_for_element(selector)
+            .map_err(|e| anyhow!("Selector {} did not match any elements: {}", selector, e))?;
+    }
+
+    // Client-side rendering has no clear end, so wait until the DOM stops changing
+    let started = Instant::now();
+    let mut html = snapshot(&tab)?;
+    while started.elapsed() < SETTLE_TIMEOUT {
+        std::thread::sleep(SETTLE_POLL);
+        let next = snapshot(&tab)?;
+        if next == html {
+            break;
+        }
+        html = next;
+    }
+    Ok(html)
+}
+
+/// Current DOM of the tab as HTML
+fn snapshot(tab: &Tab) -> Result<String> {
+    tab.evaluate("document.documentElement.outerHTML", false)
+        .map_err(|e| anyhow!("Failed to read the rendered page: {}", e))?
+        .value
+        .and_then(|value| value.as_str().map(str::to_string))
+        .ok_or_else(|| anyhow!("Rendered page has no document"))
+}
+
+/// Visible text of the elements matching a CSS selector, or of the whole body, one line per element
+pub fn visible_text(html: &str, selector: Option<&str>) -> Result<String> {
+    let selector = scraper::Selector::parse(selector.unwrap_or("body"))
+        .map_err(|e| anyhow!("Invalid CSS selector: {:?}", e))?;
+    let document = scraper::Html::parse_document(html);
+
+    let lines: Vec<String> = document
+        .select(&selector)
+        .map(|element| element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" "))
+        .collect();
+    if lines.is_empty() {
+        return Err(anyhow!("Selector did not match any elements"));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Start of a text for notifications
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(MAX_EXCERPT_CHARS) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+#[async_trait::async_trait]
+impl Monitor for JsMonitor {
+    async fn check(&mut self) -> Result<Option<Change>> {
+        info!("Rendering {}", self.url);
+        let text = self.get_text().await?;
+
+        match self.last_text.replace(text.clone()) {
+            None => Ok(Some(Change {
+                kind: ChangeKind::Started,
+                message: format!("start: {}", self.notes),
+                details: format!("URL: {}\n\nInitial content:\n{}", self.url, excerpt(&text)),
+            })),
+            Some(last_text) if last_text != text => Ok(Some(Change {
+                kind: ChangeKind::Updated,
+                message: format!("{} rendered content changed", self.notes),
+                details: format!(
+                    "URL: {}\n\nCurrent content:\n{}\n\nPrevious content:\n{}",
+                    self.url,
+                    excerpt(&text),
+                    excerpt(&last_text)
+                ),
+            })),
+            Some(_) => Ok(None),
+        }
+    }
+
+    fn interval(&self) -> u64 {
+        self.interval_secs
+    }
+
+    fn get_name(&self) -> String {
+        format!("JS-rendered page monitor for {}", self.url)
+    }
+
+    fn get_notes(&self) -> String {
+        self.notes.clone()
+    }
+
+    fn id(&self) -> String {
+        monitor_id("js", &[&self.url, &self.selector])
+    }
+
+    async fn preflight(&self) -> Result<()> {
+        probe_url(&ReqwestFetcher::new(), &self.url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_text_collapses_whitespace_per_element() {
+        let html = "<html><body><ul><li> BTC\n  <b>64,000</b> </li><li>ETH 3,100</li></ul><script></script></body></html>";
+        assert_eq!(visible_text(html, Some("li")).unwrap(), "BTC 64,000\nETH 3,100");
+        assert_eq!(visible_text(html, None).unwrap(), "BTC 64,000 ETH 3,100");
+        assert!(visible_text(html, Some("#missing")).is_err());
+    }
+}
@@ -1,13 +1,274 @@
 pub mod api_monitor;
 pub mod static_monitor;
 pub mod hyperliquid_monitor;
+pub mod xml_monitor;
+pub mod fetcher;
+pub mod js_monitor;
+pub mod stream_monitor;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use log::{info, warn};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fmt::Display;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use fetcher::{FetchRequest, HttpFetcher};
+
+/// User-Agent sent when a task doesn't set one, a desktop Chrome since WAFs often reject library defaults
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// User-Agent a task sends instead of the default one, None when unset or blank
+pub fn custom_user_agent(user_agent: Option<&str>) -> Option<&str> {
+    user_agent.map(str::trim).filter(|user_agent| !user_agent.is_empty())
+}
+
+/// Interval of monitors built without one, in seconds
+pub const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+/// Shortest interval of static webpage monitors, in seconds
+pub const MIN_INTERVAL_SECS_STATIC: u64 = 5;
+
+/// Shortest interval of API monitors, in seconds
+pub const MIN_INTERVAL_SECS_API: u64 = 5;
+
+/// Shortest interval of XML monitors, in seconds
+pub const MIN_INTERVAL_SECS_XML: u64 = 5;
+
+/// Shortest interval of JS-rendered page monitors, in seconds, each check starts a browser
+pub const MIN_INTERVAL_SECS_JS: u64 = 10;
+
+/// Shortest interval of Hyperliquid monitors, in seconds, the API rate limits per IP
+pub const MIN_INTERVAL_SECS_HYPERLIQUID: u64 = 10;
+
+/// Raise an interval to the monitor type's floor, warning when it had to be clamped
+pub fn clamp_interval(interval_secs: u64, min_interval_secs: u64, target: &str) -> u64 {
+    if interval_secs < min_interval_secs {
+        warn!("Interval of {}s for {} is below the {}s minimum, using {}s", interval_secs, target, min_interval_secs, min_interval_secs);
+    }
+    interval_secs.max(min_interval_secs)
+}
+
+/// Stable key of a monitor from its type and defining parameters, e.g. `api-3f2a9c0d1e4b5a68`;
+/// the same across runs and builds, so it can name persisted state and label metrics
+pub fn monitor_id(kind: &str, parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(kind.as_bytes());
+    for part in parts {
+        // Separate the parts so ("ab", "c") and ("a", "bc") differ
+        hasher.update([0]);
+        hasher.update(part.as_bytes());
+    }
+    format!("{}-{}", kind, &hex::encode(hasher.finalize())[..16])
+}
+
+/// HTTP client shared by the monitors: 30 second timeout, gzip/deflate/brotli bodies decoded transparently,
+/// cookies set by the server (e.g. while following a login redirect) kept for later requests
+pub fn http_client() -> Client {
+    http_client_with_user_agent(DEFAULT_USER_AGENT)
+}
+
+/// Monitor HTTP client sending a custom User-Agent
+pub fn http_client_with_user_agent(user_agent: &str) -> Client {
+    http_client_with_options(user_agent, false)
+}
+
+/// Monitor HTTP client sending a custom User-Agent, optionally accepting invalid TLS certificates
+/// (self-signed, expired or for another host), which lets anyone on the network path read and alter the traffic
+pub fn http_client_with_options(user_agent: &str, accept_invalid_certs: bool) -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent(user_agent)
+        .cookie_store(true)
+        .gzip(true)
+        .deflate(true)
+        .brotli(true)
+        .danger_accept_invalid_certs(accept_invalid_certs)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Kind of change detected by monitors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ChangeKind {
+    /// Initial state captured when monitoring starts
+    Started,
+    /// Monitored content or value changed
+    Updated,
+    /// New trade detected
+    Trade,
+    /// Position changed
+    Position,
+    /// Funding payment received or paid
+    Funding,
+    /// Token balance changed, e.g. a deposit, withdrawal or swap
+    Balance,
+    /// Current state reported on request, not a change
+    Snapshot,
+    /// Target started returning an HTTP error status
+    Down,
+    /// Target returns a success status again after being down
+    Recovered,
+    /// A measurement stayed over its alert threshold, e.g. slow responses
+    ThresholdCrossed,
+    /// Nothing changed for longer than the task's staleness window, the target may be frozen
+    Stale,
+    /// Task stopped by itself, e.g. at the end of its monitoring window
+    Stopped,
+    /// Target is failing or returned unusable data
+    Error,
+}
+
+impl ChangeKind {
+    /// Critical changes are delivered immediately, even during quiet hours
+    pub fn is_critical(&self) -> bool {
+        matches!(self, ChangeKind::Error | ChangeKind::Down)
+    }
+    
+    /// Whether the notification may wait in a digest, a task stopping goes out right away like critical ones
+    pub fn is_digested(&self) -> bool {
+        !self.is_critical() && *self != ChangeKind::Stopped
+    }
+}
+
+/// Slow responses in a row before a slow-response alert is raised
+pub const SLOW_CHECKS_BEFORE_ALERT: u32 = 3;
+
+/// Response time of a monitor's requests, raising an alert once they are persistently slow
+#[derive(Default)]
+pub struct ResponseTimer {
+    /// Responses slower than this count as slow, None to never alert
+    threshold: Option<Duration>,
+    /// Time taken by the last response
+    last: Option<Duration>,
+    /// Slow responses in a row
+    slow_checks: u32,
+    /// Alert waiting to be taken by the run loop
+    pending_alert: Option<Change>,
+}
+
+impl ResponseTimer {
+    /// Alert when responses stay slower than this, None to only measure
+    pub fn set_threshold(&mut self, threshold: Option<Duration>) {
+        self.threshold = threshold.filter(|threshold| !threshold.is_zero());
+    }
+    
+    /// Time taken by the last response
+    pub fn last(&self) -> Option<Duration> {
+        self.last
+    }
+    
+    /// Record the time a response took, queuing an alert when responses became persistently slow
+    pub fn record(&mut self, elapsed: Duration, target: &str) {
+        self.last = Some(elapsed);
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+        
+        if elapsed <= threshold {
+            if self.slow_checks >= SLOW_CHECKS_BEFORE_ALERT {
+                info!("Responses from {} are fast again: {}ms", target, elapsed.as_millis());
+            }
+            self.slow_checks = 0;
+            return;
+        }
+        
+        self.slow_checks += 1;
+        if self.slow_checks == SLOW_CHECKS_BEFORE_ALERT {
+            self.pending_alert = Some(Change {
+                kind: ChangeKind::ThresholdCrossed,
+                message: format!("{} is responding slowly ({}ms)", target, elapsed.as_millis()),
+                details: format!(
+                    "The last {} responses from {} took longer than {}ms, the latest {}ms.",
+                    SLOW_CHECKS_BEFORE_ALERT, target, threshold.as_millis(), elapsed.as_millis()
+                ),
+            });
+        }
+    }
+    
+    /// Take the slow-response alert raised by the last recorded response, if any
+    pub fn take_alert(&mut self) -> Option<Change> {
+        self.pending_alert.take()
+    }
+}
+
+/// Known-good content every check is compared with instead of the previous check, never replaced by what is seen,
+/// so content drifting away from it keeps alerting until it is restored
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    /// Expected content
+    content: String,
+    /// Whether a check has been compared yet
+    checked: bool,
+    /// Whether the last check differed from the baseline
+    deviating: bool,
+}
+
+impl Baseline {
+    /// Baseline expecting this content
+    pub fn new(content: &str) -> Self {
+        Self {
+            content: content.to_string(),
+            checked: false,
+            deviating: false,
+        }
+    }
+    
+    /// Compare content with the baseline: a change on every check that differs, and when it matches again.
+    /// The first check only reports a start when it matches
+    pub fn compare(&mut self, current: &str, notes: &str) -> Option<Change> {
+        let first = !self.checked;
+        self.checked = true;
+        let was_deviating = std::mem::replace(&mut self.deviating, current != self.content);
+        
+        if self.deviating {
+            let (expected, found) = first_difference(&self.content, current);
+            return Some(Change {
+                kind: ChangeKind::Updated,
+                message: format!("{} differs from the baseline", notes),
+                details: format!(
+                    "The content no longer matches the baseline, this is reported on every check until it is restored.\n\nFirst difference:\nBaseline: {}\nCurrent: {}\n\nBaseline length: {} bytes\nCurrent length: {} bytes",
+                    expected, found, self.content.len(), current.len()
+                ),
+            });
+        }
+        if first {
+            return Some(Change {
+                kind: ChangeKind::Started,
+                message: format!("start: {}", notes),
+                details: "The content matches the baseline".to_string(),
+            });
+        }
+        was_deviating.then(|| Change {
+            kind: ChangeKind::Recovered,
+            message: format!("{} matches the baseline again", notes),
+            details: "The content was restored to the baseline".to_string(),
+        })
+    }
+}
+
+/// First line that differs between two texts, as (expected, found); a missing line is shown as `(none)`
+fn first_difference<'a>(expected: &'a str, found: &'a str) -> (&'a str, &'a str) {
+    let mut expected_lines = expected.lines();
+    let mut found_lines = found.lines();
+    loop {
+        match (expected_lines.next(), found_lines.next()) {
+            (Some(a), Some(b)) if a == b => continue,
+            (None, None) => return ("(none)", "(none)"),
+            (a, b) => return (a.unwrap_or("(none)"), b.unwrap_or("(none)")),
+        }
+    }
+}
 
 /// Change information detected by monitors
 #[derive(Clone)]
 pub struct Change {
+    /// Kind of change
+    pub kind: ChangeKind,
     /// Change summary message
     pub message: String,
     /// Change detailed information
@@ -28,10 +289,425 @@ pub trait Monitor: Send + Sync {
 
     /// Get monitor notes or remarks
     fn get_notes(&self) -> String;
+    
+    /// Stable key derived from the parameters defining what is monitored, equal for identically configured monitors
+    fn id(&self) -> String;
+    
+    /// Time taken by the last response, for monitors that make a single HTTP request per check
+    fn last_response_time(&self) -> Option<Duration> {
+        None
+    }
+    
+    /// Alert raised by the last check besides its change, e.g. responses becoming persistently slow,
+    /// to be notified on its own
+    fn take_alert(&mut self) -> Option<Change> {
+        None
+    }
+    
+    /// One-shot connectivity check run before monitoring starts
+    async fn preflight(&self) -> Result<()> {
+        Ok(())
+    }
+    
+    /// Fetch the content a baseline is compared with, for monitors that support baselines
+    async fn current_content(&mut self) -> Result<String> {
+        Err(anyhow!("{} does not support baselines", self.get_name()))
+    }
+}
+
+/// Bounds how many checks run at once across all tasks, so tasks firing together at an interval
+/// boundary wait their turn instead of opening hundreds of connections at the same moment.
+/// Clones share the slots, so resizing one applies to every task holding a clone
+#[derive(Clone)]
+pub struct CheckLimiter {
+    permits: Arc<Semaphore>,
+    size: Arc<Mutex<LimiterSize>>,
+}
+
+#[derive(Default)]
+struct LimiterSize {
+    /// Checks allowed at a time
+    limit: usize,
+    /// Slots to take away once the checks holding them finish, after shrinking below the checks running
+    excess: usize,
+}
+
+impl CheckLimiter {
+    /// Allow up to `max_concurrent_checks` checks at a time, at least one
+    pub fn new(max_concurrent_checks: usize) -> Self {
+        let limit = max_concurrent_checks.max(1);
+        Self {
+            permits: Arc::new(Semaphore::new(limit)),
+            size: Arc::new(Mutex::new(LimiterSize { limit, excess: 0 })),
+        }
+    }
+    
+    /// Change how many checks may run at a time, at least one; running checks finish first when shrinking
+    pub fn resize(&self, max_concurrent_checks: usize) {
+        let limit = max_concurrent_checks.max(1);
+        let mut size = self.size.lock().unwrap();
+        if limit > size.limit {
+            let mut added = limit - size.limit;
+            let reclaimed = added.min(size.excess);
+            size.excess -= reclaimed;
+            added -= reclaimed;
+            self.permits.add_permits(added);
+        } else {
+            let removed = size.limit - limit;
+            size.excess += removed - self.permits.forget_permits(removed);
+        }
+        size.limit = limit;
+    }
+    
+    /// Run a check once a slot is free
+    pub async fn check<M: Monitor + ?Sized>(&self, monitor: &mut M) -> Result<Option<Change>> {
+        let permit = self.permits.acquire().await?;
+        let result = monitor.check().await;
+        let mut size = self.size.lock().unwrap();
+        if size.excess > 0 {
+            size.excess -= 1;
+            permit.forget();
+        }
+        result
+    }
+}
+
+/// Id the circuit breaker's notifications are queued under, they concern every task rather than one
+pub const CIRCUIT_BREAKER_ID: &str = "circuit-breaker";
+
+/// Pauses every task's polling for a cooldown once too many checks failed across all tasks within a window,
+/// so a misconfiguration can't turn into a request storm that gets the host banned
+#[derive(Clone, Default)]
+pub struct CircuitBreaker {
+    /// Failures within the window that open the circuit, zero to never open it
+    max_errors: u32,
+    window: Duration,
+    cooldown: Duration,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    /// Times of the failures within the window, oldest first
+    errors: VecDeque<Instant>,
+    /// End of the pause while the circuit is open
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Open after `max_errors` failed checks within `window`, pausing for `cooldown`; zero errors never opens
+    pub fn new(max_errors: u32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            max_errors,
+            window,
+            cooldown,
+            state: Arc::default(),
+        }
+    }
+    
+    /// Count a check's outcome, returns true when this failure opened the circuit
+    pub fn record(&self, failed: bool) -> bool {
+        if !failed || self.max_errors == 0 {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if state.open_until.is_some_and(|until| until > now) {
+            // Checks that were already running when it opened
+            return false;
+        }
+        
+        state.errors.push_back(now);
+        while state.errors.front().is_some_and(|time| now.duration_since(*time) > self.window) {
+            state.errors.pop_front();
+        }
+        if state.errors.len() < self.max_errors as usize {
+            return false;
+        }
+        state.errors.clear();
+        state.open_until = Some(now + self.cooldown);
+        true
+    }
+    
+    /// Time left before polling resumes, None while the circuit is closed
+    pub fn open_for(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.open_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|left| !left.is_zero())
+    }
+    
+    /// Cooldown a tripped circuit pauses polling for
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+    
+    /// Condition opening the circuit, for messages
+    pub fn threshold(&self) -> String {
+        format!("{} failed checks across all tasks within {}s", self.max_errors, self.window.as_secs())
+    }
+}
+
+/// Check that a URL resolves, connects and returns a success status
+pub async fn probe_url(fetcher: &dyn HttpFetcher, url: &str) -> Result<()> {
+    probe(fetcher, FetchRequest::get(url)).await
+}
+
+/// Send a request once and check that it returns a success status
+pub async fn probe(fetcher: &dyn HttpFetcher, request: FetchRequest) -> Result<()> {
+    let response = fetcher.fetch(request).await?;
+    
+    if !response.is_success() && response.status != 304 {
+        return Err(anyhow::anyhow!("HTTP status {}", response.status));
+    }
+    Ok(())
+}
+
+/// Describe a request error by its root cause, telling DNS, TLS and refused connections apart
+/// since an expired certificate or a lost domain is worth a clearer alert than a failed request
+pub fn describe_error(error: &reqwest::Error) -> String {
+    let causes: Vec<&(dyn std::error::Error + 'static)> = std::iter::successors(
+        Some(error as &(dyn std::error::Error + 'static)),
+        |cause| cause.source(),
+    ).collect();
+    let root = causes[causes.len() - 1];
+    
+    // The TLS error itself carries the verification result, e.g. "(certificate has expired)", its sources don't
+    if let Some(tls) = causes.iter().find_map(|cause| cause.downcast_ref::<native_tls::Error>()) {
+        let message = tls.to_string();
+        return if message.contains("certificate") {
+            format!("TLS certificate rejected: {}", message)
+        } else {
+            format!("TLS handshake failed: {}", message)
+        };
+    }
+    
+    let refused = causes.iter()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|e| e.kind() == std::io::ErrorKind::ConnectionRefused);
+    if error.is_timeout() {
+        format!("timed out: {}", root)
+    } else if refused {
+        format!("connection refused: {}", root)
+    } else if error.is_connect() && causes.iter().any(|cause| cause.to_string().starts_with("dns error")) {
+        format!("DNS lookup failed: {}", root)
+    } else if error.is_connect() {
+        format!("connection failed: {}", root)
+    } else {
+        root.to_string()
+    }
 }
 
 impl Display for Change {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.message)
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Response, Server};
+    use std::convert::Infallible;
+    use std::io::Write;
+
+    const JSON_BODY: &str = r#"{"data":{"price":"42.5"}}"#;
+
+    /// Serve `JSON_BODY` gzip-compressed on a random local port
+    fn serve_gzipped() -> String {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(JSON_BODY.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let make_service = make_service_fn(move |_| {
+            let compressed = compressed.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_req| {
+                    let response = Response::builder()
+                        .header("Content-Encoding", "gzip")
+                        .header("Content-Type", "application/json")
+                        .body(Body::from(compressed.clone()))
+                        .unwrap();
+                    async move { Ok::<_, Infallible>(response) }
+                }))
+            }
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let url = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+        url
+    }
+
+    #[test]
+    fn short_intervals_are_raised_to_the_type_floor() {
+        assert_eq!(static_monitor::StaticMonitor::new("http://page.test/", "", 1).interval(), MIN_INTERVAL_SECS_STATIC);
+        assert_eq!(static_monitor::StaticMonitor::new("http://page.test/", "", 30).interval(), 30);
+        let hyperliquid = hyperliquid_monitor::HyperliquidMonitor::new("0xc64cc00b46101bd40aa1c3121195e85c0b0918d8", 2, true, false);
+        assert_eq!(hyperliquid.interval(), MIN_INTERVAL_SECS_HYPERLIQUID);
+        assert_eq!(api_monitor::ApiMonitor::builder("http://api.test/", "$.price").interval(1).build().interval(), MIN_INTERVAL_SECS_API);
+        assert_eq!(hyperliquid_monitor::HyperliquidMonitor::builder("0xc64cc00b46101bd40aa1c3121195e85c0b0918d8").build().unwrap().interval(), DEFAULT_INTERVAL_SECS);
+        assert!(static_monitor::StaticMonitor::builder("http://page.test/", "").ignore_patterns(&["(".to_string()]).build().is_err());
+    }
+
+    #[test]
+    fn ids_depend_only_on_what_is_monitored() {
+        let api = |url: &str, selector: &str, notes: &str| api_monitor::ApiMonitor::builder(url, selector).notes(notes).build().id();
+        assert_eq!(api("http://api.test/", "$.price", "BTC"), api("http://api.test/", "$.price", "Bitcoin"));
+        assert_ne!(api("http://api.test/", "$.price", "BTC"), api("http://api.test/", "$.volume", "BTC"));
+        assert!(api("http://api.test/", "$.price", "").starts_with("api-"));
+
+        let address = "0xc64cc00b46101bd40aa1c3121195e85c0b0918d8";
+        let hyperliquid = hyperliquid_monitor::HyperliquidMonitor::builder(address).build().unwrap();
+        assert_eq!(hyperliquid.id(), hyperliquid_monitor::HyperliquidMonitor::builder(&address.to_uppercase()).interval(30).build().unwrap().id());
+        assert_ne!(hyperliquid.id(), hyperliquid_monitor::HyperliquidMonitor::builder(address).spot(false).build().unwrap().id());
+        assert_ne!(monitor_id("static", &["ab", "c"]), monitor_id("static", &["a", "bc"]));
+    }
+
+    /// Monitor whose checks take a while, counting how many run at once
+    struct SlowMonitor {
+        running: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Monitor for SlowMonitor {
+        async fn check(&mut self) -> Result<Option<Change>> {
+            use std::sync::atomic::Ordering;
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(None)
+        }
+
+        fn interval(&self) -> u64 {
+            60
+        }
+
+        fn get_name(&self) -> String {
+            "Slow monitor".to_string()
+        }
+
+        fn get_notes(&self) -> String {
+            String::new()
+        }
+
+        fn id(&self) -> String {
+            monitor_id("slow", &[])
+        }
+    }
+
+    #[tokio::test]
+    async fn limiter_bounds_checks_running_at_once() {
+        let limiter = CheckLimiter::new(2);
+        let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let checks: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let mut monitor = SlowMonitor { running: running.clone(), peak: peak.clone() };
+                tokio::spawn(async move { limiter.check(&mut monitor).await })
+            })
+            .collect();
+        for check in checks {
+            check.await.unwrap().unwrap();
+        }
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn resizing_applies_to_clones_already_handed_out() {
+        let limiter = CheckLimiter::new(4);
+        let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let task_limiter = limiter.clone();
+        limiter.resize(1);
+
+        let checks: Vec<_> = (0..4)
+            .map(|_| {
+                let limiter = task_limiter.clone();
+                let mut monitor = SlowMonitor { running: running.clone(), peak: peak.clone() };
+                tokio::spawn(async move { limiter.check(&mut monitor).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        limiter.resize(3);
+        for check in checks {
+            check.await.unwrap().unwrap();
+        }
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(limiter.permits.available_permits(), 3);
+
+        // Shrinking below the running checks takes their slots back as they finish
+        let checks: Vec<_> = (0..3)
+            .map(|_| {
+                let limiter = task_limiter.clone();
+                let mut monitor = SlowMonitor { running: running.clone(), peak: peak.clone() };
+                tokio::spawn(async move { limiter.check(&mut monitor).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        limiter.resize(1);
+        for check in checks {
+            check.await.unwrap().unwrap();
+        }
+        assert_eq!(limiter.permits.available_permits(), 1);
+    }
+
+    #[test]
+    fn breaker_opens_once_after_enough_failures_within_the_window() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60), Duration::from_millis(50));
+        assert!(!breaker.record(true));
+        assert!(!breaker.record(false));
+        assert!(!breaker.record(true));
+        assert!(breaker.record(true));
+        assert!(breaker.open_for().is_some());
+        assert!(!breaker.record(true), "failures while open don't count");
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(breaker.open_for(), None);
+        assert!(!breaker.record(true));
+        assert!(!CircuitBreaker::default().record(true));
+    }
+
+    #[tokio::test]
+    async fn gzipped_bodies_are_decoded() {
+        let url = serve_gzipped();
+
+        let mut static_monitor = static_monitor::StaticMonitor::new(&url, "", 60);
+        let change = static_monitor.check().await.unwrap().unwrap();
+        assert!(change.details.contains(&format!("{} bytes", JSON_BODY.len())), "{}", change.details);
+
+        let mut api_monitor = api_monitor::ApiMonitor::new(url, "$.data.price".to_string(), 60);
+        let change = api_monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::Started);
+        assert!(change.details.contains("Initial value: 42.5"), "{}", change.details);
+    }
+
+    #[test]
+    fn slow_alert_is_raised_once_responses_stay_slow() {
+        let mut timer = ResponseTimer::default();
+        timer.set_threshold(Some(Duration::from_millis(500)));
+        let slow = Duration::from_millis(900);
+
+        timer.record(slow, "http://page.test/");
+        timer.record(Duration::from_millis(100), "http://page.test/");
+        for _ in 0..SLOW_CHECKS_BEFORE_ALERT - 1 {
+            timer.record(slow, "http://page.test/");
+            assert!(timer.take_alert().is_none());
+        }
+        timer.record(slow, "http://page.test/");
+
+        let alert = timer.take_alert().unwrap();
+        assert_eq!(alert.kind, ChangeKind::ThresholdCrossed);
+        assert_eq!(alert.message, "http://page.test/ is responding slowly (900ms)");
+        assert_eq!(timer.last(), Some(slow));
+        assert!(timer.take_alert().is_none());
+
+        timer.record(slow, "http://page.test/");
+        assert!(timer.take_alert().is_none());
+    }
+}
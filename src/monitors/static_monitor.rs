@@ -1,38 +1,165 @@
 use anyhow::{Result, anyhow};
+use encoding_rs::{Encoding, UTF_8};
 use log::{debug, error};
-use reqwest::Client;
-use std::time::Duration;
+use regex::Regex;
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 
-use crate::monitors::{Monitor, Change};
+use crate::monitors::{clamp_interval, monitor_id, Baseline, ResponseTimer, DEFAULT_INTERVAL_SECS, MIN_INTERVAL_SECS_STATIC, probe, Monitor, Change, ChangeKind};
+use crate::monitors::fetcher::{FetchOptions, FetchRequest, FetchResponse, HttpFetcher, HttpMonitor, ResponseCache};
+use crate::monitors::js_monitor::render_page;
+use crate::utils::{format_pct, mask_secret};
+
+/// Largest page read by default, in bytes
+pub const DEFAULT_MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+
+/// Longest changed text quoted in a change description, in characters
+const CHANGED_TEXT_CHARS: usize = 200;
+
+/// Why a rendered page can't accept invalid certificates: the headless browser always verifies them
+pub const RENDER_JS_INVALID_CERTS: &str = "Accepting invalid certificates is not supported when rendering JavaScript";
+
+/// How the content of two checks is compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMode {
+    /// The whole page must match exactly, the selector is not used
+    #[default]
+    RawBytes,
+    /// Visible text of the page with whitespace collapsed, one line per block; markup, attributes, scripts and styles are ignored
+    #[serde(alias = "text_only")]
+    NormalizedText,
+    /// Visible text of each selected element, one per line, normalized like `NormalizedText`; needs a selector
+    Selector,
+    /// Only a SHA-256 of the content is kept, trading the change description for a tiny memory footprint
+    Hash,
+}
+
+impl CompareMode {
+    /// Every mode, in the order they are offered
+    pub const ALL: [CompareMode; 4] = [
+        CompareMode::RawBytes,
+        CompareMode::NormalizedText,
+        CompareMode::Selector,
+        CompareMode::Hash,
+    ];
+
+    /// Name shown in the GUI
+    pub fn label(&self) -> &'static str {
+        match self {
+            CompareMode::RawBytes => "Exact content",
+            CompareMode::NormalizedText => "Text only",
+            CompareMode::Selector => "Selected text",
+            CompareMode::Hash => "Hash only",
+        }
+    }
+}
+
+impl fmt::Display for CompareMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CompareMode::RawBytes => "raw_bytes",
+            CompareMode::NormalizedText => "normalized_text",
+            CompareMode::Selector => "selector",
+            CompareMode::Hash => "hash",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CompareMode {
+    type Err = anyhow::Error;
+
+    /// Parse a name such as `normalized_text`, dashes and a few short forms are accepted
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode.trim().to_lowercase().replace('-', "_").as_str() {
+            "raw_bytes" | "raw" => Ok(CompareMode::RawBytes),
+            "normalized_text" | "text" | "text_only" => Ok(CompareMode::NormalizedText),
+            "selector" => Ok(CompareMode::Selector),
+            "hash" => Ok(CompareMode::Hash),
+            _ => Err(anyhow!("Unknown compare mode {}, expected raw_bytes, normalized_text, selector or hash", mode.trim())),
+        }
+    }
+}
 
 /// Static webpage monitor, used to monitor webpage content changes
 pub struct StaticMonitor {
     /// Webpage URL to monitor
     url: String,
+    /// CSS selector of the compared elements in `CompareMode::Selector`
+    selector: String,
     /// Monitoring interval (seconds)
     interval_secs: u64,
     /// Last detected content
     last_content: Option<String>,
-    /// HTTP client
-    client: Client,
+    /// How the content is compared between checks
+    compare_mode: CompareMode,
+    /// Load the page in headless Chrome so client-side rendered content is seen
+    render_js: bool,
+    /// SHA-256 of the last detected content, used in `CompareMode::Hash`
+    last_hash: Option<[u8; 32]>,
+    /// HTTP status of the last response once the page has been seen up, to report it going down and recovering
+    last_status: Option<u16>,
+    /// HTTP status of the response in the current check, None when there was no response
+    response_status: Option<u16>,
+    /// `ETag` of the last response, sent back as `If-None-Match`
+    etag: Option<String>,
+    /// `Last-Modified` of the last response, sent back as `If-Modified-Since`
+    last_modified: Option<String>,
+    /// Raw `Cookie` header sent with every request, for pages behind a login
+    cookies: Option<String>,
+    /// Fragments removed from the content before comparing, e.g. counters or session IDs
+    ignore_patterns: Vec<Regex>,
+    /// A change is only reported when one of these matches the changed region, empty to report all
+    require_patterns: Vec<Regex>,
+    /// Regex capturing a number from the selected text, the page is then tracked as that number
+    numeric_regex: Option<Regex>,
+    /// Last extracted number
+    last_number: Option<f64>,
+    /// Lowest and highest number seen since monitoring started
+    number_range: Option<(f64, f64)>,
+    /// Known-good content every check is compared with instead of the previous check
+    baseline: Option<Baseline>,
+    /// Pages larger than this many bytes are not read, e.g. downloads or endless streams
+    max_body_bytes: usize,
+    /// Time taken by the downloads, with the slow-response alert
+    response_timer: ResponseTimer,
+    /// User-Agent, TLS and cache settings, with the fetcher performing the HTTP requests
+    fetch: FetchOptions,
     /// User-provided notes/remarks
     notes: String,
 }
 
 impl StaticMonitor {
     /// Create a new static webpage monitor
-    pub fn new(url: &str, _selector: &str, interval_secs: u64) -> Self {
-        // Create HTTP client with timeout
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .unwrap_or_default();
-            
+    pub fn new(url: &str, selector: &str, interval_secs: u64) -> Self {
         Self {
             url: url.to_string(),
-            interval_secs,
+            selector: selector.trim().to_string(),
+            interval_secs: clamp_interval(interval_secs, MIN_INTERVAL_SECS_STATIC, url),
             last_content: None,
-            client,
+            compare_mode: CompareMode::RawBytes,
+            render_js: false,
+            last_hash: None,
+            last_status: None,
+            response_status: None,
+            etag: None,
+            last_modified: None,
+            cookies: None,
+            ignore_patterns: Vec::new(),
+            require_patterns: Vec::new(),
+            numeric_regex: None,
+            last_number: None,
+            number_range: None,
+            baseline: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            response_timer: ResponseTimer::default(),
+            fetch: FetchOptions::new(),
             notes: url.to_string(), // Default to using URL as the note
         }
     }
@@ -46,6 +173,11 @@ impl StaticMonitor {
         monitor
     }
 
+    /// Fail checks whose page is larger than this many bytes instead of reading it, None for the default
+    pub fn set_max_body_bytes(&mut self, max_body_bytes: Option<usize>) {
+        self.max_body_bytes = max_body_bytes.filter(|max| *max > 0).unwrap_or(DEFAULT_MAX_BODY_BYTES);
+    }
+    
     /// Set notes/remarks
     pub fn set_notes(&mut self, notes: &str) {
         if !notes.trim().is_empty() {
@@ -53,28 +185,219 @@ impl StaticMonitor {
         }
     }
     
-    /// Get content of webpage
-    async fn get_content(&self) -> Result<String> {
+    /// Choose how the content is compared between checks
+    pub fn set_compare_mode(&mut self, compare_mode: CompareMode) {
+        self.compare_mode = compare_mode;
+    }
+    
+    /// Compare every check with this content, as selected and compared, instead of with the previous check; None to follow the page
+    pub fn set_baseline(&mut self, baseline: Option<&str>) {
+        self.baseline = baseline.map(Baseline::new);
+    }
+    
+    /// Render the page in headless Chrome instead of downloading it, for pages built client-side
+    pub fn set_render_js(&mut self, render_js: bool) {
+        self.render_js = render_js;
+    }
+    
+    /// Send a raw `Cookie` header (e.g. `session=abc; theme=dark`) with every request
+    pub fn set_cookies(&mut self, cookies: Option<String>) {
+        self.cookies = cookies
+            .map(|cookies| cookies.trim().to_string())
+            .filter(|cookies| !cookies.is_empty());
+        if let Some(cookies) = &self.cookies {
+            debug!("Sending cookies {} to {}", mask_secret(cookies), self.url);
+        }
+    }
+    
+    /// Alert once downloads are persistently slower than this, None to only measure them
+    pub fn set_slow_threshold(&mut self, threshold: Option<Duration>) {
+        self.response_timer.set_threshold(threshold);
+    }
+    
+    /// Strip every match of these regexes from the content before comparing
+    pub fn set_ignore_patterns(&mut self, patterns: &[String]) -> Result<()> {
+        self.ignore_patterns = compile_patterns(patterns)?;
+        Ok(())
+    }
+    
+    /// Only report changes where one of these regexes matches the added or removed text,
+    /// not applied in `CompareMode::Hash` since the previous content isn't kept
+    pub fn set_require_patterns(&mut self, patterns: &[String]) -> Result<()> {
+        self.require_patterns = compile_patterns(patterns)?;
+        Ok(())
+    }
+    
+    /// Track a number captured by this regex (its first group, or the whole match) instead of the content,
+    /// e.g. `Price:\s*([\d,.]+)`
+    pub fn set_numeric_regex(&mut self, pattern: Option<&str>) -> Result<()> {
+        self.numeric_regex = match pattern.map(str::trim).filter(|pattern| !pattern.is_empty()) {
+            Some(pattern) => Some(compile_patterns(&[pattern.to_string()])?.remove(0)),
+            None => None,
+        };
+        Ok(())
+    }
+    
+    /// Compare the number in the content with the previous one
+    fn check_number(&mut self, content: &str) -> Result<Option<Change>> {
+        let Some(pattern) = &self.numeric_regex else {
+            return Ok(None);
+        };
+        let number = extract_number(&html_text(content), pattern)
+            .ok_or_else(|| anyhow!("Numeric regex {} did not match a number", pattern))?;
+        
+        let (low, high) = self.number_range.map_or((number, number), |(low, high)| (low.min(number), high.max(number)));
+        self.number_range = Some((low, high));
+        
+        match self.last_number.replace(number) {
+            None => Ok(Some(Change {
+                kind: ChangeKind::Started,
+                message: format!("start: {}", self.notes),
+                details: format!("Initial value: {}", number),
+            })),
+            Some(last_number) if last_number != number => {
+                let delta = number - last_number;
+                let move_description = if last_number != 0.0 {
+                    format!("{:+} ({})", delta, format_pct(delta / last_number.abs() * 100.0))
+                } else {
+                    format!("{:+}", delta)
+                };
+                Ok(Some(Change {
+                    kind: ChangeKind::Updated,
+                    message: format!("{} {} -> {} {}", self.notes, last_number, number, move_description),
+                    details: format!(
+                        "Current value: {}\n\nPrevious value: {}\n\nChange: {}\n\nRange since start: {} - {}",
+                        number, last_number, move_description, low, high
+                    ),
+                }))
+            }
+            Some(_) => Ok(None),
+        }
+    }
+    
+    /// Content with the ignored fragments removed
+    fn strip_ignored(&self, content: String) -> String {
+        self.ignore_patterns
+            .iter()
+            .fold(content, |content, pattern| pattern.replace_all(&content, "").into_owned())
+    }
+    
+    /// Whether a change between two contents touches a required pattern
+    fn is_relevant_change(&self, old_content: &str, new_content: &str) -> bool {
+        if self.require_patterns.is_empty() {
+            return true;
+        }
+        let (removed, added) = changed_region(old_content, new_content);
+        self.require_patterns
+            .iter()
+            .any(|pattern| pattern.is_match(removed) || pattern.is_match(added))
+    }
+    
+    /// Compare the content hash with the previous one, used in `CompareMode::Hash`
+    fn check_hash(&mut self, content: &str) -> Option<Change> {
+        let hash: [u8; 32] = Sha256::digest(content.trim().as_bytes()).into();
+        
+        match self.last_hash.replace(hash) {
+            None => {
+                debug!("First content hash: {}", hex::encode(hash));
+                Some(Change {
+                    kind: ChangeKind::Started,
+                    message: format!("start: {}", self.notes),
+                    details: format!("Initial content hash: {}", hex::encode(hash)),
+                })
+            }
+            Some(last_hash) if last_hash != hash => Some(Change {
+                kind: ChangeKind::Updated,
+                message: format!("{} content changed (hash mismatch)", self.notes),
+                details: format!(
+                    "Current content hash: {}\n\nPrevious content hash: {}",
+                    hex::encode(hash),
+                    hex::encode(last_hash)
+                ),
+            }),
+            Some(_) => None,
+        }
+    }
+    
+    /// Download the page, conditional once we have validators
+    async fn download(&mut self) -> Result<Option<String>> {
         debug!("Getting entire webpage content: {}", self.url);
         
-        // Send HTTP request to get webpage content
-        let response = self.client.get(&self.url)
-            .send()
-            .await
-            .map_err(|e| anyhow!("Failed to get webpage content: {}", e))?;
+        let mut request = FetchRequest::get(&self.url)
+            .cookies(self.cookies.as_deref())
+            .max_body_bytes(self.max_body_bytes);
+        // A baseline keeps alerting on an unchanged page, so it needs the page on every check
+        if self.baseline.is_some() {
+            self.etag = None;
+            self.last_modified = None;
+        }
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH.as_str(), etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE.as_str(), last_modified);
+        }
+        
+        let started = Instant::now();
+        let response = self.fetch.fetcher().fetch(request).await?;
+        if !response.cached {
+            self.response_timer.record(started.elapsed(), &self.url);
+        }
+        self.response_status = Some(response.status);
             
-        let status = response.status();
-        if !status.is_success() {
-            return Err(anyhow!("HTTP request failed, status code: {}", status));
+        if response.status == 304 {
+            debug!("Webpage not modified since last request");
+            return Ok(None);
+        }
+        if !response.is_success() {
+            return Err(anyhow!("HTTP request failed, status code: {}", response.status));
         }
         
-        let html = response.text()
-            .await
-            .map_err(|e| anyhow!("Failed to read response content: {}", e))?;
+        // Remember validators for the next request, servers without them get a full download each time
+        self.etag = response.header(ETAG.as_str()).map(str::to_string);
+        self.last_modified = response.header(LAST_MODIFIED.as_str()).map(str::to_string);
+        
+        Ok(Some(decode_page(&response)))
+    }
+    
+    /// Get content of webpage, None when the server reports it unchanged since the last request
+    async fn get_content(&mut self) -> Result<Option<String>> {
+        let html = if self.render_js {
+            if self.fetch.accept_invalid_certs {
+                return Err(anyhow!(RENDER_JS_INVALID_CERTS));
+            }
+            debug!("Rendering webpage in headless Chrome: {}", self.url);
+            let started = Instant::now();
+            let html = render_page(&self.url, None, self.fetch.user_agent.as_deref()).await?;
+            self.response_timer.record(started.elapsed(), &self.url);
+            html
+        } else {
+            match self.download().await? {
+                Some(html) => html,
+                None => return Ok(None),
+            }
+        };
         
         debug!("Full webpage content retrieved: {} bytes", html.len());
         
-        Ok(html)
+        Ok(Some(match self.compare_mode {
+            CompareMode::RawBytes | CompareMode::Hash => html,
+            CompareMode::NormalizedText => normalize_text(&html),
+            CompareMode::Selector => self.selected_text(&html)?,
+        }))
+    }
+    
+    /// Text of each element matching the selector, one per line; no match is content too, so an element
+    /// disappearing is reported as a change
+    fn selected_text(&self, html: &str) -> Result<String> {
+        if self.selector.is_empty() {
+            return Err(anyhow!("Comparing selected text needs a CSS selector"));
+        }
+        let matches = select_html(html, &self.selector)?;
+        if matches.is_empty() {
+            debug!("Selector {} did not match any elements", self.selector);
+        }
+        Ok(matches.iter().map(|element| normalize_text(element)).collect::<Vec<_>>().join("\n"))
     }
     
     /// 生成更易读的变化描述
@@ -96,6 +419,13 @@ impl StaticMonitor {
             changes.push_str("内容长度相同，但内容已变化\n");
         }
         
+        // 文本比较时直接给出变化的文字
+        if matches!(self.compare_mode, CompareMode::NormalizedText | CompareMode::Selector) {
+            let (old_text, new_text) = changed_region(old_content, new_content);
+            let clip = |text: &str| text.chars().take(CHANGED_TEXT_CHARS).collect::<String>();
+            changes.push_str(&format!("文本变化: '{}' -> '{}'\n", clip(old_text), clip(new_text)));
+        }
+        
         // 尝试检测一些常见的HTML变化
         if old_content.contains("<title>") && new_content.contains("<title>") {
             // 提取标题
@@ -109,32 +439,72 @@ impl StaticMonitor {
         
         changes
     }
-}
-
-/// 辅助函数：提取两个标记之间的内容
-fn extract_between<'a>(content: &'a str, start_marker: &str, end_marker: &str) -> Option<&'a str> {
-    if let Some(start_idx) = content.find(start_marker) {
-        let content_after_start = &content[start_idx + start_marker.len()..];
-        if let Some(end_idx) = content_after_start.find(end_marker) {
-            return Some(&content_after_start[..end_idx]);
+    
+    /// Check the page, reporting it going down and recovering once it has been seen up
+    async fn check_availability(&mut self) -> Result<Option<Change>> {
+        self.response_status = None;
+        let result = self.check_content().await;
+        let Some(status) = self.response_status else {
+            return result;
+        };
+        
+        // Availability is tracked once the page has been seen up, a page down from the start stays an error
+        let Some(last_status) = self.last_status else {
+            if is_available(status) {
+                self.last_status = Some(status);
+            }
+            return result;
+        };
+        self.last_status = Some(status);
+        
+        match (is_available(last_status), is_available(status)) {
+            (_, false) if last_status == status => {
+                debug!("Webpage still down with HTTP status {}", status);
+                Ok(None)
+            }
+            (_, false) => Ok(Some(Change {
+                kind: ChangeKind::Down,
+                message: format!("{} site down (HTTP {} -> {})", self.notes, last_status, status),
+                details: format!("{} now returns HTTP status {}, it returned {} before.", self.url, status, last_status),
+            })),
+            (false, true) => {
+                let content_change = result?;
+                let mut details = format!("{} returns HTTP status {} again after {}.", self.url, status, last_status);
+                if let Some(change) = content_change.filter(|change| change.kind == ChangeKind::Updated) {
+                    details.push_str(&format!("\n\nThe content changed while it was down:\n{}", change.details));
+                }
+                Ok(Some(Change {
+                    kind: ChangeKind::Recovered,
+                    message: format!("{} site recovered (HTTP {} -> {})", self.notes, last_status, status),
+                    details,
+                }))
+            }
+            (true, true) => result,
         }
     }
-    None
-}
-
-#[async_trait::async_trait]
-impl Monitor for StaticMonitor {
-    async fn check(&mut self) -> Result<Option<Change>> {
-        match self.get_content().await {
-            Ok(current_content) => {
+    
+    /// Fetch the page and compare it with the previous check
+    async fn check_content(&mut self) -> Result<Option<Change>> {
+        match self.get_content().await.map(|content| content.map(|content| self.strip_ignored(content))) {
+            Ok(None) => Ok(None),
+            Ok(Some(current_content)) if self.baseline.is_some() => {
+                Ok(self.baseline.as_mut().and_then(|baseline| baseline.compare(&current_content, &self.notes)))
+            }
+            Ok(Some(current_content)) if self.numeric_regex.is_some() => self.check_number(&current_content),
+            Ok(Some(current_content)) if self.compare_mode == CompareMode::Hash => Ok(self.check_hash(&current_content)),
+            Ok(Some(current_content)) => {
                 // Check if content has changed
                 if let Some(last_content) = &self.last_content {
-                    if *last_content != current_content {
+                    if *last_content != current_content && !self.is_relevant_change(last_content, &current_content) {
+                        debug!("Content changed without matching a required pattern");
+                        self.last_content = Some(current_content);
+                    } else if *last_content != current_content {
                         // Content has changed
                         // Create more readable change description
                         let change_description = self.generate_change_description(last_content, &current_content);
                         
                         let change = Change {
+                            kind: ChangeKind::Updated,
                             message: format!("{} {}", self.notes, change_description),
                             details: format!(
                                 "Changes:\n{}\n\nCurrent content length: {} bytes\n\nPrevious content length: {} bytes", 
@@ -155,6 +525,7 @@ impl Monitor for StaticMonitor {
                     
                     // Create change for initial content
                     let change = Change {
+                        kind: ChangeKind::Started,
                         message: format!("start: {}", self.notes),
                         details: format!("Initial content length: {} bytes", current_content.len()),
                     };
@@ -173,6 +544,302 @@ impl Monitor for StaticMonitor {
             }
         }
     }
+}
+
+impl StaticMonitor {
+    /// Start building a static webpage monitor, checking every `DEFAULT_INTERVAL_SECS` unless set otherwise
+    pub fn builder(url: &str, selector: &str) -> StaticMonitorBuilder {
+        StaticMonitorBuilder {
+            monitor: StaticMonitor::new(url, selector, DEFAULT_INTERVAL_SECS),
+            interval_secs: DEFAULT_INTERVAL_SECS,
+            error: None,
+        }
+    }
+}
+
+/// Builds a `StaticMonitor` with chained options, failing on `build` when one is invalid
+pub struct StaticMonitorBuilder {
+    monitor: StaticMonitor,
+    interval_secs: u64,
+    /// First invalid option, returned by `build`
+    error: Option<anyhow::Error>,
+}
+
+impl StaticMonitorBuilder {
+    /// Notes/remarks shown in notifications
+    pub fn notes(mut self, notes: &str) -> Self {
+        self.monitor.set_notes(notes);
+        self
+    }
+
+    /// Check interval (seconds), raised to `MIN_INTERVAL_SECS_STATIC`
+    pub fn interval(mut self, interval_secs: u64) -> Self {
+        self.interval_secs = interval_secs;
+        self
+    }
+
+    /// Largest page read, None for the default
+    pub fn max_body_bytes(mut self, max_body_bytes: Option<usize>) -> Self {
+        self.monitor.set_max_body_bytes(max_body_bytes);
+        self
+    }
+
+    /// Custom User-Agent
+    pub fn user_agent(mut self, user_agent: Option<&str>) -> Self {
+        self.monitor.set_user_agent(user_agent);
+        self
+    }
+
+    /// Accept invalid TLS certificates, insecure
+    pub fn accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.monitor.set_accept_invalid_certs(accept_invalid_certs);
+        self
+    }
+
+    /// How the content is compared between checks
+    pub fn compare_mode(mut self, compare_mode: CompareMode) -> Self {
+        self.monitor.set_compare_mode(compare_mode);
+        self
+    }
+
+    /// Content every check is compared with
+    pub fn baseline(mut self, baseline: Option<&str>) -> Self {
+        self.monitor.set_baseline(baseline);
+        self
+    }
+
+    /// Render the page in headless Chrome
+    pub fn render_js(mut self, render_js: bool) -> Self {
+        self.monitor.set_render_js(render_js);
+        self
+    }
+
+    /// Raw `Cookie` header sent with every request
+    pub fn cookies(mut self, cookies: Option<String>) -> Self {
+        self.monitor.set_cookies(cookies);
+        self
+    }
+
+    /// Slow-response alert threshold
+    pub fn slow_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.monitor.set_slow_threshold(threshold);
+        self
+    }
+
+    /// Regexes stripped from the content before comparing
+    pub fn ignore_patterns(mut self, patterns: &[String]) -> Self {
+        let result = self.monitor.set_ignore_patterns(patterns);
+        self.keep_error(result)
+    }
+
+    /// Regexes a change must match to be reported
+    pub fn require_patterns(mut self, patterns: &[String]) -> Self {
+        let result = self.monitor.set_require_patterns(patterns);
+        self.keep_error(result)
+    }
+
+    /// Track the number captured by this regex instead of the content
+    pub fn numeric_regex(mut self, pattern: Option<&str>) -> Self {
+        let result = self.monitor.set_numeric_regex(pattern);
+        self.keep_error(result)
+    }
+
+    /// HTTP fetcher, e.g. with canned responses in tests
+    pub fn fetcher(mut self, fetcher: Arc<dyn HttpFetcher>) -> Self {
+        self.monitor.set_fetcher(fetcher);
+        self
+    }
+
+    /// Responses shared with the other monitors using the cache, None for no cache
+    pub fn response_cache(mut self, cache: Option<&ResponseCache>) -> Self {
+        if let Some(cache) = cache {
+            self.monitor.set_response_cache(cache);
+        }
+        self
+    }
+
+    /// Remember the first failed option
+    fn keep_error(mut self, result: Result<()>) -> Self {
+        if let Err(e) = result {
+            self.error.get_or_insert(e);
+        }
+        self
+    }
+
+    /// Build the monitor, failing on the first invalid regex or when a rendered page should accept invalid certificates
+    pub fn build(mut self) -> Result<StaticMonitor> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if self.monitor.render_js && self.monitor.fetch.accept_invalid_certs {
+            return Err(anyhow!(RENDER_JS_INVALID_CERTS));
+        }
+        self.monitor.interval_secs = clamp_interval(self.interval_secs, MIN_INTERVAL_SECS_STATIC, &self.monitor.url);
+        Ok(self.monitor)
+    }
+}
+
+/// Bytes at the start of a page searched for a `<meta charset>` tag
+const CHARSET_SNIFF_BYTES: usize = 2048;
+
+/// Decode a page in the charset named by its `Content-Type` header or `<meta>` tag, UTF-8 otherwise
+fn decode_page(response: &FetchResponse) -> String {
+    let encoding = response
+        .header(CONTENT_TYPE.as_str())
+        .and_then(charset_label)
+        .or_else(|| {
+            let head = String::from_utf8_lossy(&response.body[..response.body.len().min(CHARSET_SNIFF_BYTES)]).into_owned();
+            charset_label(&head)
+        })
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+    
+    if encoding != UTF_8 {
+        debug!("Decoding webpage as {}", encoding.name());
+    }
+    encoding.decode(&response.body).0.into_owned()
+}
+
+/// Charset named in a `Content-Type` value or an HTML head, e.g. `gbk` in `text/html; charset=gbk`
+fn charset_label(text: &str) -> Option<String> {
+    static CHARSET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?i)charset\s*=\s*["']?([\w.:-]+)"#).expect("valid charset regex"));
+    CHARSET.captures(text).map(|captures| captures[1].to_string())
+}
+
+/// Statuses below 400 mean the page is up, including 304 Not Modified
+fn is_available(status: u16) -> bool {
+    status < 400
+}
+
+/// Outer HTML of every element matching a CSS selector
+pub fn select_html(html: &str, selector: &str) -> Result<Vec<String>> {
+    let selector = scraper::Selector::parse(selector)
+        .map_err(|e| anyhow!("Invalid CSS selector {}: {:?}", selector, e))?;
+    let document = scraper::Html::parse_document(html);
+    Ok(document.select(&selector).map(|element| element.html()).collect())
+}
+
+/// Elements whose text is never displayed
+const HIDDEN_ELEMENTS: [&str; 4] = ["script", "style", "noscript", "template"];
+
+/// Elements starting a new line of text
+const BLOCK_ELEMENTS: [&str; 28] = [
+    "address", "article", "aside", "blockquote", "br", "dd", "div", "dl", "dt", "figcaption", "footer", "form", "h1",
+    "h2", "h3", "h4", "h5", "h6", "header", "hr", "li", "main", "nav", "p", "pre", "section", "title", "tr",
+];
+
+/// Visible text of an HTML document or fragment, leaving out scripts and styles and breaking lines at blocks
+fn html_text(html: &str) -> String {
+    let document = scraper::Html::parse_document(html);
+    let mut text = String::new();
+    push_text(document.root_element(), &mut text);
+    text
+}
+
+/// Append the visible text of an element and its descendants
+fn push_text(element: scraper::ElementRef<'_>, text: &mut String) {
+    let name = element.value().name();
+    if HIDDEN_ELEMENTS.contains(&name) {
+        return;
+    }
+    let breaks = BLOCK_ELEMENTS.contains(&name);
+    if breaks {
+        text.push('\n');
+    }
+    for child in element.children() {
+        if let Some(child) = scraper::ElementRef::wrap(child) {
+            push_text(child, text);
+        } else if let scraper::Node::Text(content) = child.value() {
+            text.push_str(content);
+        }
+    }
+    if breaks {
+        text.push('\n');
+    } else if matches!(name, "td" | "th") {
+        text.push(' ');
+    }
+}
+
+/// Visible text of an HTML document or fragment, one line per block with each run of whitespace collapsed to one space
+fn normalize_text(html: &str) -> String {
+    html_text(html)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// First number matched by a regex, from its first group or the whole match, ignoring thousands separators
+fn extract_number(text: &str, pattern: &Regex) -> Option<f64> {
+    let captures = pattern.captures(text)?;
+    let matched = captures.get(1).or_else(|| captures.get(0))?.as_str();
+    let digits: String = matched
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+'))
+        .collect();
+    digits.parse().ok()
+}
+
+/// Compile regexes, skipping blank lines
+pub fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| Regex::new(pattern).map_err(|e| anyhow!("Invalid regex {}: {}", pattern, e)))
+        .collect()
+}
+
+/// The differing middle of two strings once their common prefix and suffix are removed, as (old, new)
+fn changed_region<'a>(old_content: &'a str, new_content: &'a str) -> (&'a str, &'a str) {
+    let prefix: usize = old_content
+        .chars()
+        .zip(new_content.chars())
+        .take_while(|(old, new)| old == new)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let (old_rest, new_rest) = (&old_content[prefix..], &new_content[prefix..]);
+    let suffix: usize = old_rest
+        .chars()
+        .rev()
+        .zip(new_rest.chars().rev())
+        .take_while(|(old, new)| old == new)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (&old_rest[..old_rest.len() - suffix], &new_rest[..new_rest.len() - suffix])
+}
+
+/// 辅助函数：提取两个标记之间的内容
+fn extract_between<'a>(content: &'a str, start_marker: &str, end_marker: &str) -> Option<&'a str> {
+    if let Some(start_idx) = content.find(start_marker) {
+        let content_after_start = &content[start_idx + start_marker.len()..];
+        if let Some(end_idx) = content_after_start.find(end_marker) {
+            return Some(&content_after_start[..end_idx]);
+        }
+    }
+    None
+}
+
+impl HttpMonitor for StaticMonitor {
+    fn fetch_options(&mut self) -> &mut FetchOptions {
+        &mut self.fetch
+    }
+}
+
+#[async_trait::async_trait]
+impl Monitor for StaticMonitor {
+    async fn check(&mut self) -> Result<Option<Change>> {
+        self.check_availability().await
+    }
+    
+    fn last_response_time(&self) -> Option<Duration> {
+        self.response_timer.last()
+    }
+    
+    fn take_alert(&mut self) -> Option<Change> {
+        self.response_timer.take_alert()
+    }
     
     fn interval(&self) -> u64 {
         self.interval_secs
@@ -185,4 +852,246 @@ impl Monitor for StaticMonitor {
     fn get_notes(&self) -> String {
         self.notes.clone()
     }
-} 
\ No newline at end of file
+    
+    fn id(&self) -> String {
+        monitor_id("static", &[&self.url, &self.selector])
+    }
+    
+    async fn preflight(&self) -> Result<()> {
+        probe(self.fetch.fetcher(), FetchRequest::get(&self.url).cookies(self.cookies.as_deref())).await
+    }
+    
+    async fn current_content(&mut self) -> Result<String> {
+        self.etag = None;
+        self.last_modified = None;
+        let content = self.get_content().await?.ok_or_else(|| anyhow!("Webpage returned no content"))?;
+        Ok(self.strip_ignored(content))
+    }
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitors::fetcher::{FetchResponse, MockFetcher};
+
+    #[tokio::test]
+    async fn detects_started_unchanged_and_updated() {
+        let mut monitor = StaticMonitor::new("http://page.test/", "", 60);
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .respond(200, "<title>One</title>")
+            .respond(200, "<title>One</title>")
+            .respond(200, "<title>Two</title>")));
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::Updated);
+        assert!(change.message.contains("'One' -> 'Two'"), "{}", change.message);
+    }
+
+    #[tokio::test]
+    async fn slow_alerts_are_kept_when_the_check_reports_a_change() {
+        let mut monitor = StaticMonitor::builder("http://page.test/", "")
+            .slow_threshold(Some(Duration::from_nanos(1)))
+            .fetcher(Arc::new(MockFetcher::default()
+                .respond(200, "<title>One</title>")
+                .respond(200, "<title>Two</title>")
+                .respond(200, "<title>Three</title>")))
+            .build()
+            .unwrap();
+
+        monitor.check().await.unwrap();
+        monitor.check().await.unwrap();
+        assert!(monitor.take_alert().is_none());
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+        assert_eq!(monitor.take_alert().unwrap().kind, ChangeKind::ThresholdCrossed);
+    }
+
+    #[tokio::test]
+    async fn injected_fetchers_are_kept_when_the_fetch_options_change() {
+        let fetcher = Arc::new(MockFetcher::default().respond(200, "<title>One</title>"));
+        let mut monitor = StaticMonitor::builder("http://page.test/", "")
+            .fetcher(fetcher.clone())
+            .user_agent(Some("probe/1.0"))
+            .accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert_eq!(fetcher.requests().len(), 1);
+
+        let rendered = StaticMonitor::builder("http://page.test/", "").render_js(true).accept_invalid_certs(true).build();
+        assert!(rendered.is_err());
+    }
+
+    #[tokio::test]
+    async fn baseline_alerts_on_every_check_until_restored() {
+        let mut monitor = StaticMonitor::builder("http://page.test/", "")
+            .baseline(Some("<p>Welcome</p>"))
+            .fetcher(Arc::new(MockFetcher::default()
+                .respond(200, "<p>Welcome</p>")
+                .respond(200, "<p>Hacked</p>")
+                .respond(200, "<p>Hacked</p>")
+                .respond(200, "<p>Welcome</p>")))
+            .build()
+            .unwrap();
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        for _ in 0..2 {
+            let change = monitor.check().await.unwrap().unwrap();
+            assert_eq!(change.kind, ChangeKind::Updated);
+            assert!(change.details.contains("Baseline: <p>Welcome</p>\nCurrent: <p>Hacked</p>"), "{}", change.details);
+        }
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Recovered);
+    }
+
+    #[tokio::test]
+    async fn only_the_selector_mode_narrows_the_page() {
+        let pages = || MockFetcher::default()
+            .respond(200, "<p>Price</p><aside>Ad 1</aside>")
+            .respond(200, "<p>Price</p><aside>Ad 2</aside>")
+            .respond(200, "<aside>Ad 2</aside>");
+
+        // Exact comparison keeps comparing the whole body, as before selectors existed
+        let mut exact = StaticMonitor::builder("http://page.test/", "p").fetcher(Arc::new(pages())).build().unwrap();
+        assert_eq!(exact.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert_eq!(exact.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+
+        // A selected element that disappears is a change, not a failure
+        let mut selected = StaticMonitor::builder("http://page.test/", "p")
+            .compare_mode(CompareMode::Selector)
+            .fetcher(Arc::new(pages()))
+            .build()
+            .unwrap();
+        assert_eq!(selected.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert!(selected.check().await.unwrap().is_none());
+        assert_eq!(selected.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+    }
+
+    #[tokio::test]
+    async fn text_only_ignores_attributes_and_scripts() {
+        let mut monitor = StaticMonitor::builder("http://page.test/", "")
+            .compare_mode(CompareMode::NormalizedText)
+            .fetcher(Arc::new(MockFetcher::default()
+                .respond(200, r#"<div class="product a"><h2>Lamp</h2><p>Sold out</p><script>var t = 1;</script></div>"#)
+                .respond(200, r#"<div id="p1" class="a product"><h2 data-v="2">Lamp</h2>
+                    <p>Sold   out</p><script>var t = 2;</script></div>"#)
+                .respond(200, r#"<div class="product"><h2>Lamp</h2><p>In stock</p></div>"#)))
+            .build()
+            .unwrap();
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert!(change.message.contains("'Sold out' -> 'In stock'"), "{}", change.message);
+        assert_eq!(normalize_text("<table><tr><td>BTC</td><td>42</td></tr></table><p>a<br>b</p>"), "BTC 42\na\nb");
+    }
+
+    #[tokio::test]
+    async fn sends_validators_and_treats_not_modified_as_unchanged() {
+        let fetcher = Arc::new(MockFetcher::default()
+            .respond_with(FetchResponse {
+                status: 200,
+                headers: [("etag".to_string(), "\"v1\"".to_string())].into(),
+                body: b"page".to_vec(),
+                ..FetchResponse::default()
+            })
+            .respond(304, ""));
+        let mut monitor = StaticMonitor::new("http://page.test/", "", 60);
+        monitor.set_fetcher(fetcher.clone());
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let requests = fetcher.requests();
+        assert!(requests[0].headers.is_empty());
+        assert_eq!(requests[1].headers, vec![("if-none-match".to_string(), "\"v1\"".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn sends_session_cookies() {
+        let fetcher = Arc::new(MockFetcher::default().respond(200, "page"));
+        let mut monitor = StaticMonitor::new("http://page.test/", "", 60);
+        monitor.set_fetcher(fetcher.clone());
+        monitor.set_cookies(Some(" session=abc123 ".to_string()));
+
+        monitor.check().await.unwrap();
+        assert_eq!(fetcher.requests()[0].headers, vec![("cookie".to_string(), "session=abc123".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn failures_are_errors() {
+        let mut monitor = StaticMonitor::new("http://page.test/", "", 60);
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .fail("connection refused")
+            .respond(503, "")));
+
+        assert!(monitor.check().await.is_err());
+        assert!(monitor.check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn ignored_and_unrequired_changes_are_not_reported() {
+        let mut monitor = StaticMonitor::new("http://page.test/", "", 60);
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .respond(200, "Price: 10 <span>visitors 100</span>")
+            .respond(200, "Price: 10 <span>visitors 250</span>")
+            .respond(200, "Price: 10 <span>visitors 250</span> Sponsored")
+            .respond(200, "Price: 12 <span>visitors 300</span> Sponsored")));
+        monitor.set_ignore_patterns(&[r"visitors \d+".to_string()]).unwrap();
+        monitor.set_require_patterns(&[r"^\d+$".to_string(), "".to_string()]).unwrap();
+
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert!(monitor.check().await.unwrap().is_none());
+        assert!(monitor.check().await.unwrap().is_none());
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+
+        assert!(monitor.set_require_patterns(&["(".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn tracks_the_number_captured_from_the_page() {
+        let mut monitor = StaticMonitor::new_with_notes("http://page.test/", ".stock", 60, "Stock");
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .respond(200, r#"<p class="stock">In stock: <b>1,200</b> units</p>"#)
+            .respond(200, r#"<p class="stock" data-x="1">In stock: <b>1,200</b> units</p>"#)
+            .respond(200, r#"<p class="stock">In stock: <b>900</b> units</p>"#)
+            .respond(200, r#"<p class="stock">Sold out</p>"#)));
+        monitor.set_numeric_regex(Some(r"In stock:\s*([\d,]+)")).unwrap();
+
+        assert!(monitor.check().await.unwrap().unwrap().details.contains("Initial value: 1200"));
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.message, "Stock 1200 -> 900 -300 (-25.00%)");
+        assert!(change.details.contains("Range since start: 900 - 1200"), "{}", change.details);
+
+        assert!(monitor.check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn status_transitions_are_reported_as_down_and_recovered() {
+        let mut monitor = StaticMonitor::new_with_notes("http://page.test/", "", 60, "Shop");
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .respond(503, "")
+            .respond(200, "<title>One</title>")
+            .respond(503, "")
+            .respond(503, "")
+            .respond(404, "")
+            .respond(200, "<title>Two</title>")));
+
+        // Down from the start is an error, not a transition
+        assert!(monitor.check().await.is_err());
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+
+        let down = monitor.check().await.unwrap().unwrap();
+        assert_eq!(down.kind, ChangeKind::Down);
+        assert_eq!(down.message, "Shop site down (HTTP 200 -> 503)");
+        assert!(monitor.check().await.unwrap().is_none());
+        assert_eq!(monitor.check().await.unwrap().unwrap().message, "Shop site down (HTTP 503 -> 404)");
+
+        let recovered = monitor.check().await.unwrap().unwrap();
+        assert_eq!(recovered.kind, ChangeKind::Recovered);
+        assert!(recovered.details.contains("'One' -> 'Two'"), "{}", recovered.details);
+    }
+}
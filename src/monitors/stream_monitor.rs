@@ -0,0 +1,278 @@
+use anyhow::{Result, anyhow};
+use futures::{SinkExt, StreamExt};
+use log::debug;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::monitors::hyperliquid_monitor::{describe_fill, fill_market, FillMarket};
+use crate::monitors::{monitor_id, Change, ChangeKind};
+use crate::utils::Backoff;
+
+/// Hyperliquid WebSocket endpoint
+pub const HYPERLIQUID_WS_URL: &str = "wss://api.hyperliquid.xyz/ws";
+
+/// Hyperliquid closes connections that were silent for a minute, so ping before that
+const PING_INTERVAL: Duration = Duration::from_secs(50);
+
+/// Delay before reconnecting after a stream dropped, doubled after each failure
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between reconnection attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Spread of the reconnection delays, in percent
+const RECONNECT_JITTER_PERCENT: u8 = 20;
+
+/// Reconnection policy shared by stream monitors
+pub fn reconnect_backoff() -> Backoff {
+    Backoff::new(RECONNECT_DELAY, MAX_RECONNECT_DELAY, RECONNECT_JITTER_PERCENT)
+}
+
+/// Monitor fed by a push stream, waiting for events instead of polling on an interval
+#[async_trait::async_trait]
+pub trait StreamMonitor: Send {
+    /// Wait for the next event, connecting first if needed; None when the event had nothing to report,
+    /// e.g. a reconnection that missed nothing. An error means the connection was lost, the next call reconnects
+    async fn next_change(&mut self) -> Result<Option<Change>>;
+
+    /// Get monitor name or description for notifications
+    fn get_name(&self) -> String;
+
+    /// Get monitor notes or remarks
+    fn get_notes(&self) -> String;
+
+    /// Stable key derived from the parameters defining what is streamed, like `Monitor::id`
+    fn id(&self) -> String;
+}
+
+/// Fills of a Hyperliquid user pushed over the `userFills` WebSocket subscription as they happen
+pub struct HyperliquidFillStream {
+    /// Wallet address to monitor
+    address: String,
+    /// Whether to report spot fills
+    monitor_spot: bool,
+    /// Whether to report perpetual fills
+    monitor_contract: bool,
+    /// WebSocket endpoint URL
+    ws_url: String,
+    /// Open connection, None until connected or after it dropped
+    connection: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    /// Time (ms) of the newest fill seen, fills after it in a reconnection snapshot were missed while disconnected
+    last_fill_time: Option<u64>,
+    /// Whether the first snapshot was handled, later ones come from reconnections
+    snapshot_seen: bool,
+    /// User-provided notes/remarks
+    notes: String,
+}
+
+impl HyperliquidFillStream {
+    /// Create a fill stream for the given address
+    pub fn new(address: &str, monitor_spot: bool, monitor_contract: bool) -> Self {
+        Self {
+            address: address.to_string(),
+            monitor_spot,
+            monitor_contract,
+            ws_url: HYPERLIQUID_WS_URL.to_string(),
+            connection: None,
+            last_fill_time: None,
+            snapshot_seen: false,
+            notes: address.to_string(),
+        }
+    }
+
+    /// Create a fill stream with notes
+    pub fn new_with_notes(address: &str, monitor_spot: bool, monitor_contract: bool, notes: &str) -> Self {
+        let mut stream = Self::new(address, monitor_spot, monitor_contract);
+        if !notes.trim().is_empty() {
+            stream.notes = notes.to_string();
+        }
+        stream
+    }
+
+    /// Use a different WebSocket endpoint, e.g. the testnet or a local mock server
+    pub fn set_ws_url(&mut self, ws_url: &str) {
+        self.ws_url = ws_url.to_string();
+    }
+
+    /// Connect and subscribe to the user's fills
+    async fn connect(&mut self) -> Result<()> {
+        let (mut connection, _) = tokio_tungstenite::connect_async(self.ws_url.as_str())
+            .await
+            .map_err(|e| anyhow!("Failed to connect to {}: {}", self.ws_url, e))?;
+
+        let subscribe = json!({
+            "method": "subscribe",
+            "subscription": { "type": "userFills", "user": self.address }
+        });
+        connection.send(Message::Text(subscribe.to_string()))
+            .await
+            .map_err(|e| anyhow!("Failed to subscribe to fills: {}", e))?;
+
+        debug!("Subscribed to fills of {}", self.address);
+        self.connection = Some(connection);
+        Ok(())
+    }
+
+    /// Handle a message pushed by the server, returns a change for new fills
+    fn handle_message(&mut self, text: &str) -> Result<Option<Change>> {
+        let message: Value = serde_json::from_str(text)
+            .map_err(|e| anyhow!("Failed to parse stream message: {}", e))?;
+
+        match message["channel"].as_str().unwrap_or("") {
+            "userFills" => {}
+            "error" => return Err(anyhow!("Stream error: {}", message["data"])),
+            channel => {
+                debug!("Ignoring {} message", channel);
+                return Ok(None);
+            }
+        }
+
+        let data = &message["data"];
+        let fills = data["fills"].as_array().cloned().unwrap_or_default();
+        let newest = fills.iter().filter_map(|fill| fill["time"].as_u64()).max();
+        let previous = self.last_fill_time;
+        self.last_fill_time = self.last_fill_time.max(newest);
+
+        // The first message of a subscription is a snapshot of recent fills
+        let snapshot = data["isSnapshot"].as_bool().unwrap_or(false);
+        if snapshot && !self.snapshot_seen {
+            self.snapshot_seen = true;
+            let latest = fills.iter().max_by_key(|fill| fill["time"].as_u64().unwrap_or(0));
+            return Ok(Some(Change {
+                kind: ChangeKind::Started,
+                message: format!("Started streaming fills: {}", self.notes),
+                details: format!(
+                    "Streaming fills:\nUser: {}\nLatest fill: {}\n\nNotes: {}",
+                    self.address,
+                    latest.map(describe_fill).unwrap_or_else(|| "none".to_string()),
+                    self.notes
+                ),
+            }));
+        }
+
+        let new_fills: Vec<String> = fills
+            .iter()
+            .filter(|fill| !snapshot || fill["time"].as_u64().unwrap_or(0) > previous.unwrap_or(0))
+            .filter(|fill| match fill_market(fill) {
+                FillMarket::Spot => self.monitor_spot,
+                FillMarket::Perp => self.monitor_contract,
+            })
+            .map(describe_fill)
+            .collect();
+
+        let message = match new_fills.as_slice() {
+            [] => return Ok(None),
+            [fill] => format!("{} - New fill: {}", self.notes, fill),
+            fills if snapshot => format!("{} - {} fills while disconnected", self.notes, fills.len()),
+            fills => format!("{} - {} new fills", self.notes, fills.len()),
+        };
+        Ok(Some(Change {
+            kind: ChangeKind::Trade,
+            message,
+            details: format!("New fills:\nUser: {}\n{}\n\nNotes: {}", self.address, new_fills.join("\n"), self.notes),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl StreamMonitor for HyperliquidFillStream {
+    async fn next_change(&mut self) -> Result<Option<Change>> {
+        loop {
+            if self.connection.is_none() {
+                self.connect().await?;
+            }
+            let Some(connection) = self.connection.as_mut() else {
+                continue;
+            };
+
+            let next = tokio::time::timeout(PING_INTERVAL, connection.next()).await;
+            let message = match next {
+                Err(_) => {
+                    if let Err(e) = connection.send(Message::Text(json!({ "method": "ping" }).to_string())).await {
+                        self.connection = None;
+                        return Err(anyhow!("Failed to ping the stream: {}", e));
+                    }
+                    continue;
+                }
+                Ok(None) => {
+                    self.connection = None;
+                    return Err(anyhow!("Stream closed by the server"));
+                }
+                Ok(Some(Err(e))) => {
+                    self.connection = None;
+                    return Err(anyhow!("Stream connection lost: {}", e));
+                }
+                Ok(Some(Ok(message))) => message,
+            };
+
+            match message {
+                Message::Text(text) => return self.handle_message(&text),
+                Message::Close(_) => {
+                    self.connection = None;
+                    return Err(anyhow!("Stream closed by the server"));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn get_name(&self) -> String {
+        format!("Hyperliquid fill stream for {}", self.address)
+    }
+
+    fn get_notes(&self) -> String {
+        self.notes.clone()
+    }
+
+    fn id(&self) -> String {
+        let flags = [self.monitor_spot, self.monitor_contract].map(|flag| if flag { "1" } else { "0" });
+        monitor_id("hyperliquid-fills", &[&self.address.to_lowercase(), flags[0], flags[1]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fills_message(snapshot: bool, fills: &[(&str, u64)]) -> String {
+        let fills: Vec<Value> = fills
+            .iter()
+            .map(|(coin, time)| json!({ "coin": coin, "side": "B", "px": "100", "sz": "1", "time": time, "tid": time }))
+            .collect();
+        json!({ "channel": "userFills", "data": { "isSnapshot": snapshot, "user": "0xabc", "fills": fills } }).to_string()
+    }
+
+    #[test]
+    fn snapshots_only_report_fills_missed_while_disconnected() {
+        let mut stream = HyperliquidFillStream::new_with_notes("0xabc", true, false, "Whale");
+
+        let started = stream.handle_message(&fills_message(true, &[("@107", 1_000), ("BTC", 2_000)])).unwrap().unwrap();
+        assert_eq!(started.kind, ChangeKind::Started);
+        assert!(stream.handle_message(r#"{"channel":"subscriptionResponse","data":{}}"#).unwrap().is_none());
+
+        // Perp fills are left out when only spot is monitored
+        assert!(stream.handle_message(&fills_message(false, &[("ETH", 3_000)])).unwrap().is_none());
+        let fill = stream.handle_message(&fills_message(false, &[("PURR/USDC", 4_000)])).unwrap().unwrap();
+        assert_eq!(fill.kind, ChangeKind::Trade);
+        assert!(fill.message.starts_with("Whale - New fill: Buy 1 PURR/USDC @ 100"), "{}", fill.message);
+
+        // After a reconnection the snapshot repeats known fills
+        assert!(stream.handle_message(&fills_message(true, &[("@107", 1_000), ("PURR/USDC", 4_000)])).unwrap().is_none());
+        let missed = stream.handle_message(&fills_message(true, &[("PURR/USDC", 4_000), ("@1", 5_000), ("@2", 6_000)])).unwrap().unwrap();
+        assert_eq!(missed.message, "Whale - 2 fills while disconnected");
+    }
+
+    #[test]
+    fn an_empty_first_snapshot_starts_only_once() {
+        let mut stream = HyperliquidFillStream::new("0xabc", true, false);
+
+        let started = stream.handle_message(&fills_message(true, &[])).unwrap().unwrap();
+        assert_eq!(started.kind, ChangeKind::Started);
+        assert!(stream.handle_message(&fills_message(true, &[])).unwrap().is_none());
+        let missed = stream.handle_message(&fills_message(true, &[("@107", 1_000)])).unwrap().unwrap();
+        assert_eq!(missed.kind, ChangeKind::Trade);
+    }
+}
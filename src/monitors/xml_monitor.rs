@@ -0,0 +1,226 @@
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use sxd_xpath::{Factory, Value};
+
+use crate::monitors::{clamp_interval, monitor_id, MIN_INTERVAL_SECS_XML, probe_url, Change, ChangeKind, Monitor};
+use crate::monitors::fetcher::{FetchOptions, FetchRequest, HttpMonitor};
+
+/// Monitor a value selected by XPath from an XML document (sitemaps, feeds, XHTML)
+pub struct XmlMonitor {
+    /// Document URL
+    url: String,
+    /// XPath expression
+    xpath: String,
+    /// Last selected value
+    last_value: Option<String>,
+    /// Check interval (seconds)
+    interval_secs: u64,
+    /// User-Agent, TLS and cache settings, with the fetcher performing the HTTP requests
+    fetch: FetchOptions,
+    /// User-provided notes/remarks
+    notes: String,
+}
+
+impl XmlMonitor {
+    /// Create a new XML monitor
+    pub fn new(url: &str, xpath: &str, interval_secs: u64) -> Self {
+        Self {
+            url: url.to_string(),
+            xpath: xpath.trim().to_string(),
+            last_value: None,
+            interval_secs: clamp_interval(interval_secs, MIN_INTERVAL_SECS_XML, url),
+            fetch: FetchOptions::new(),
+            notes: url.to_string(),
+        }
+    }
+
+    /// Create a new XML monitor with notes
+    pub fn new_with_notes(url: &str, xpath: &str, interval_secs: u64, notes: &str) -> Self {
+        let mut monitor = Self::new(url, xpath, interval_secs);
+        if !notes.trim().is_empty() {
+            monitor.notes = notes.to_string();
+        }
+        monitor
+    }
+
+    /// Download the document
+    async fn get_document(&self) -> Result<String> {
+        debug!("Getting XML document: {}", self.url);
+
+        let response = self.fetch.fetcher().fetch(FetchRequest::get(&self.url))
+            .await
+            .map_err(|e| anyhow!("Failed to get XML document: {}", e))?;
+
+        if !response.is_success() {
+            return Err(anyhow!("HTTP request failed, status code: {}", response.status));
+        }
+
+        Ok(response.text())
+    }
+}
+
+/// Check that an XPath expression compiles
+pub fn validate_xpath(xpath: &str) -> Result<()> {
+    match Factory::new().build(xpath.trim()) {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(anyhow!("XPath must not be empty")),
+        Err(e) => Err(anyhow!("{}", e)),
+    }
+}
+
+/// Evaluate an XPath against a document, returning the text of the selected nodes
+pub fn select(document: &str, xpath: &str) -> Result<Option<String>> {
+    let package = sxd_document::parser::parse(document)
+        .map_err(|e| anyhow!("Failed to parse XML document: {:?}", e))?;
+    let document = package.as_document();
+
+    let value = sxd_xpath::evaluate_xpath(&document, xpath)
+        .map_err(|e| anyhow!("XPath error: {}", e))?;
+
+    match value {
+        Value::Nodeset(nodes) => {
+            let values: Vec<String> = nodes.document_order()
+                .iter()
+                .map(|node| node.string_value().trim().to_string())
+                .collect();
+            match values.len() {
+                0 => Ok(None),
+                1 => Ok(values.into_iter().next()),
+                _ => Ok(Some(format!("[{}]", values.join(", ")))),
+            }
+        }
+        value => Ok(Some(value.string())),
+    }
+}
+
+impl HttpMonitor for XmlMonitor {
+    fn fetch_options(&mut self) -> &mut FetchOptions {
+        &mut self.fetch
+    }
+}
+
+#[async_trait::async_trait]
+impl Monitor for XmlMonitor {
+    async fn check(&mut self) -> Result<Option<Change>> {
+        info!("Checking XML document at {}", self.url);
+
+        let document = self.get_document().await?;
+        let result = select(&document, &self.xpath)?;
+
+        match (&self.last_value, result) {
+            (None, Some(new_value)) => {
+                debug!("First check, recording initial value: {}", new_value);
+                let change = Change {
+                    kind: ChangeKind::Started,
+                    message: format!("start: {}", self.notes),
+                    details: format!("XPath: {}\nInitial value: {}", self.xpath, new_value),
+                };
+                self.last_value = Some(new_value);
+                Ok(Some(change))
+            }
+            (None, None) => {
+                debug!("Could not extract initial data using XPath: {}", self.xpath);
+                Ok(Some(Change {
+                    kind: ChangeKind::Started,
+                    message: format!("start: {}", self.notes),
+                    details: format!("URL: {}\nXPath: {}\n\nThe XPath did not match any nodes. Please check if your expression is correct.",
+                        self.url, self.xpath),
+                }))
+            }
+            (Some(old_value), Some(new_value)) => {
+                if *old_value == new_value {
+                    debug!("No change detected");
+                    return Ok(None);
+                }
+
+                info!("Detected change in XML document");
+                let change = Change {
+                    kind: ChangeKind::Updated,
+                    message: format!("{} value changed", self.notes),
+                    details: format!("XPath: {}\n\nCurrent value:\n{}\n\nPrevious value:\n{}",
+                        self.xpath, new_value, old_value),
+                };
+                self.last_value = Some(new_value);
+                Ok(Some(change))
+            }
+            (Some(_), None) => {
+                debug!("Could not extract data using XPath: {}", self.xpath);
+                Ok(Some(Change {
+                    kind: ChangeKind::Error,
+                    message: format!("{} - Data extraction failed", self.notes),
+                    details: format!("URL: {}\nXPath: {}\n\nThe XPath did not match any nodes after a previous successful match. The document structure may have changed.",
+                        self.url, self.xpath),
+                }))
+            }
+        }
+    }
+
+    fn interval(&self) -> u64 {
+        self.interval_secs
+    }
+
+    fn get_name(&self) -> String {
+        format!("XML monitor for {}", self.url)
+    }
+
+    fn get_notes(&self) -> String {
+        self.notes.clone()
+    }
+
+    fn id(&self) -> String {
+        monitor_id("xml", &[&self.url, &self.xpath])
+    }
+    
+    async fn preflight(&self) -> Result<()> {
+        probe_url(self.fetch.fetcher(), &self.url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitors::fetcher::MockFetcher;
+    use std::sync::Arc;
+
+    fn feed(title: &str) -> String {
+        format!("<rss><channel><item><title>{}</title></item></channel></rss>", title)
+    }
+
+    #[tokio::test]
+    async fn reports_the_initial_value_then_changes() {
+        let mut monitor = XmlMonitor::new_with_notes("http://feed.test/rss", "/rss/channel/item[1]/title", 60, "Feed");
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .respond(200, &feed("First post"))
+            .respond(200, &feed("First post"))
+            .respond(200, &feed("Second post"))));
+
+        let started = monitor.check().await.unwrap().unwrap();
+        assert_eq!(started.kind, ChangeKind::Started);
+        assert!(started.details.ends_with("Initial value: First post"), "{}", started.details);
+        assert!(monitor.check().await.unwrap().is_none());
+
+        let change = monitor.check().await.unwrap().unwrap();
+        assert_eq!(change.kind, ChangeKind::Updated);
+        assert_eq!(change.message, "Feed value changed");
+        assert!(change.details.contains("Current value:\nSecond post\n\nPrevious value:\nFirst post"), "{}", change.details);
+    }
+
+    #[tokio::test]
+    async fn an_xpath_matching_nothing_is_reported() {
+        let mut monitor = XmlMonitor::new_with_notes("http://feed.test/rss", "/rss/channel/item[1]/title", 60, "Feed");
+        monitor.set_fetcher(Arc::new(MockFetcher::default()
+            .respond(200, "<rss><channel/></rss>")
+            .respond(200, &feed("First post"))
+            .respond(200, "<rss><channel/></rss>")));
+
+        // Nothing to record at first, the start explains why
+        let started = monitor.check().await.unwrap().unwrap();
+        assert_eq!(started.kind, ChangeKind::Started);
+        assert!(started.details.contains("did not match any nodes"), "{}", started.details);
+
+        monitor.check().await.unwrap();
+        let failed = monitor.check().await.unwrap().unwrap();
+        assert_eq!(failed.kind, ChangeKind::Error);
+        assert_eq!(failed.message, "Feed - Data extraction failed");
+    }
+}
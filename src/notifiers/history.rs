@@ -0,0 +1,153 @@
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::read_from_file;
+
+/// Default location of the notification history
+pub const HISTORY_PATH: &str = "notifications.jsonl";
+
+/// Size the history file may reach before it is rotated
+pub const HISTORY_MAX_BYTES: u64 = 1024 * 1024;
+
+/// A sent, failed or muted notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unix timestamp in seconds
+    pub time: u64,
+    /// Name of the task that raised it
+    pub task: String,
+    /// Title
+    pub title: String,
+    /// Content
+    pub content: String,
+    /// Whether the notifier accepted it
+    pub delivered: bool,
+    /// Why delivery failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Suppressed by a mute rather than sent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub muted: bool,
+}
+
+impl HistoryEntry {
+    /// Entry for a notification sent now, with the result of sending it
+    pub fn new(task: &str, title: &str, content: &str, result: &Result<()>) -> Self {
+        Self {
+            time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            task: task.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            delivered: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            muted: false,
+        }
+    }
+
+    /// Entry for a notification the mute kept from being sent
+    pub fn muted(task: &str, title: &str, content: &str) -> Self {
+        Self {
+            delivered: false,
+            muted: true,
+            ..Self::new(task, title, content, &Ok(()))
+        }
+    }
+}
+
+/// Notification history kept as JSON lines, the file is moved to `<path>.1` once it passes the size cap
+#[derive(Debug, Clone)]
+pub struct NotificationHistory {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl NotificationHistory {
+    /// History stored at the given path, rotated at `HISTORY_MAX_BYTES`
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            max_bytes: HISTORY_MAX_BYTES,
+        }
+    }
+
+    /// Rotate once the file passes this many bytes
+    pub fn set_max_bytes(&mut self, max_bytes: u64) {
+        self.max_bytes = max_bytes.max(1);
+    }
+
+    /// File the previous entries are moved to on rotation
+    fn rotated_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".1");
+        PathBuf::from(path)
+    }
+
+    /// Append an entry, rotating the file first if it is full
+    pub fn record(&self, entry: &HistoryEntry) -> Result<()> {
+        if fs::metadata(&self.path).is_ok_and(|metadata| metadata.len() >= self.max_bytes) {
+            fs::rename(&self.path, self.rotated_path())?;
+        }
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Entries from the rotated and current files, oldest first; unreadable lines are skipped
+    pub fn load(&self) -> Vec<HistoryEntry> {
+        [self.rotated_path(), self.path.clone()]
+            .iter()
+            .filter_map(|path| read_from_file(path).ok())
+            .flat_map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| match serde_json::from_str(line) {
+                        Ok(entry) => Some(entry),
+                        Err(e) => {
+                            warn!("Skipping unreadable notification history line: {}", e);
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_rotates_and_loads_oldest_first() {
+        let path = std::env::temp_dir().join(format!("monitor-history-{}.jsonl", std::process::id()));
+        let mut history = NotificationHistory::new(&path);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(history.rotated_path());
+
+        let sent = HistoryEntry::new("BTC", "Price changed", "42", &Ok(()));
+        let line_len = serde_json::to_string(&sent).unwrap().len() as u64 + 1;
+        history.set_max_bytes(line_len * 2);
+        history.record(&sent).unwrap();
+        history.record(&HistoryEntry::new("News", "Page changed", "", &Err(anyhow::anyhow!("quota")))).unwrap();
+        history.record(&HistoryEntry::new("BTC", "Price changed again", "43", &Ok(()))).unwrap();
+
+        let entries = history.load();
+        let titles: Vec<&str> = entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["Price changed", "Page changed", "Price changed again"]);
+        assert!(!entries[1].delivered);
+        assert_eq!(entries[1].error.as_deref(), Some("quota"));
+        assert_eq!(read_from_file(&path).unwrap().lines().count(), 1);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(history.rotated_path()).unwrap();
+    }
+}
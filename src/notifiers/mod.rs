@@ -1,9 +1,341 @@
 pub mod server_chan;
+pub mod queue;
+pub mod history;
+pub mod pending;
 
 use anyhow::Result;
+use chrono::Timelike;
+use futures::future::join_all;
+use log::{debug, error};
+use std::time::{Duration, Instant};
+
+use crate::config::{NotificationConfig, TaskConfig};
+use crate::monitors::ChangeKind;
+use server_chan::ServerChanNotifier;
 
 /// Notifier trait, all types of notification services need to implement this trait
+#[async_trait::async_trait]
 pub trait Notifier: Send + Sync {
     /// Send notification
     async fn send(&self, title: &str, content: &str) -> Result<()>;
-} 
\ No newline at end of file
+    
+    /// Send content that is already Markdown, e.g. a digest, without escaping it as plain text
+    async fn send_markdown(&self, title: &str, content: &str) -> Result<()> {
+        self.send(title, content).await
+    }
+} 
+
+/// Notification services that can be configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifierKind {
+    /// ServerChan push to WeChat
+    ServerChan,
+}
+
+impl NotifierKind {
+    /// Every notification service, in the order they are built
+    pub const ALL: &'static [NotifierKind] = &[NotifierKind::ServerChan];
+    
+    /// Name used in task configurations
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotifierKind::ServerChan => "serverchan",
+        }
+    }
+    
+    /// Display name
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotifierKind::ServerChan => "ServerChan",
+        }
+    }
+    
+    /// Look up a notifier by its name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace(['-', '_', ' '], "");
+        Self::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+    
+    /// Build this notifier from the configuration, None when it isn't set up;
+    /// `server_chan_key` replaces the configured ServerChan keys, e.g. with a task's own key
+    fn build(&self, config: &NotificationConfig, server_chan_key: Option<&str>) -> Option<Box<dyn Notifier>> {
+        match self {
+            NotifierKind::ServerChan => {
+                let mut keys: Vec<String> = match server_chan_key.map(str::trim).filter(|key| !key.is_empty()) {
+                    Some(key) => vec![key.to_string()],
+                    None => config.server_chan_keys.iter().map(|key| key.trim().to_string()).filter(|key| !key.is_empty()).collect(),
+                };
+                if keys.is_empty() {
+                    keys.extend(std::env::var("SERVER_CHAN_KEY").ok().filter(|key| !key.trim().is_empty()));
+                }
+                
+                // A dry run logs notifications, so it works without keys
+                if keys.is_empty() && !config.dry_run {
+                    return None;
+                }
+                let mut notifier = ServerChanNotifier::new_with_keys(&keys);
+                config.apply_to(&mut notifier);
+                Some(Box::new(notifier))
+            }
+        }
+    }
+}
+
+/// Build every notifier the configuration sets up, empty when none is
+pub fn build_notifiers(config: &NotificationConfig) -> Vec<Box<dyn Notifier>> {
+    NotifierKind::ALL
+        .iter()
+        .filter_map(|kind| kind.build(config, None))
+        .collect()
+}
+
+/// Build the notifiers a task selected, all configured ones when it selected none,
+/// using the task's own ServerChan key if it has one
+pub fn build_task_notifiers(config: &NotificationConfig, task: &TaskConfig) -> Vec<Box<dyn Notifier>> {
+    NotifierKind::ALL
+        .iter()
+        .filter(|kind| task.notifiers.is_empty() || task.notifiers.iter().any(|name| NotifierKind::from_name(name) == Some(**kind)))
+        .filter_map(|kind| kind.build(config, task.server_chan_key.as_deref()))
+        .collect()
+}
+
+/// Sends every notification through several notifiers at once
+pub struct CompositeNotifier {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl CompositeNotifier {
+    /// Combine notifiers, none at all drops every notification
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        Self { notifiers }
+    }
+    
+    /// Whether there is no notifier to send through
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+}
+
+#[async_trait::async_trait]
+impl Notifier for CompositeNotifier {
+    /// Succeeds when any notifier delivered, otherwise returns the first error so it can be inspected
+    async fn send(&self, title: &str, content: &str) -> Result<()> {
+        if self.notifiers.is_empty() {
+            debug!("No notifiers configured, dropped notification: {}", title);
+            return Ok(());
+        }
+        combine(join_all(self.notifiers.iter().map(|notifier| notifier.send(title, content))).await)
+    }
+    
+    async fn send_markdown(&self, title: &str, content: &str) -> Result<()> {
+        if self.notifiers.is_empty() {
+            debug!("No notifiers configured, dropped notification: {}", title);
+            return Ok(());
+        }
+        combine(join_all(self.notifiers.iter().map(|notifier| notifier.send_markdown(title, content))).await)
+    }
+}
+
+/// Outcome of sending through several notifiers: any delivery is a success, logging the other failures
+fn combine(results: Vec<Result<()>>) -> Result<()> {
+    if results.iter().any(Result::is_ok) {
+        for e in results.iter().filter_map(|result| result.as_ref().err()) {
+            error!("A notifier failed: {}", e);
+        }
+        return Ok(());
+    }
+    results.into_iter().find_map(Result::err).map_or(Ok(()), Err)
+}
+
+/// Whether the hour falls inside a quiet-hours window of (start, end) hours, which may wrap past midnight
+pub fn in_quiet_hours(quiet_hours: Option<(u8, u8)>, hour: u32) -> bool {
+    match quiet_hours {
+        Some((start, end)) if start < end => hour >= start as u32 && hour < end as u32,
+        Some((start, end)) if start > end => hour >= start as u32 || hour < end as u32,
+        _ => false,
+    }
+}
+
+/// Whether a notification of this kind should be held back until quiet hours end (local time)
+pub fn should_hold(quiet_hours: Option<(u8, u8)>, kind: ChangeKind) -> bool {
+    !kind.is_critical() && in_quiet_hours(quiet_hours, chrono::Local::now().hour())
+}
+
+/// Counts consecutive failed checks so a broken task alerts once instead of on every retry
+#[derive(Debug)]
+pub struct FailureTracker {
+    threshold: u32,
+    consecutive: u32,
+    failing: bool,
+}
+
+impl FailureTracker {
+    /// Create a tracker that reports failing after `threshold` failures in a row
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            consecutive: 0,
+            failing: false,
+        }
+    }
+
+    /// Record a failed check, returns true when the task has just started failing
+    pub fn record_failure(&mut self) -> bool {
+        self.consecutive += 1;
+        if !self.failing && self.consecutive >= self.threshold {
+            self.failing = true;
+            return true;
+        }
+        false
+    }
+
+    /// Record a successful check, returns true when a failing task has recovered
+    pub fn record_success(&mut self) -> bool {
+        self.consecutive = 0;
+        std::mem::replace(&mut self.failing, false)
+    }
+
+    /// Number of failed checks in a row
+    pub fn consecutive(&self) -> u32 {
+        self.consecutive
+    }
+}
+
+/// Tracks the time since the last detected change so a feed that stopped updating alerts once
+#[derive(Debug)]
+pub struct StaleTracker {
+    after: Option<Duration>,
+    last_change: Instant,
+    stale: bool,
+}
+
+impl StaleTracker {
+    /// Create a tracker that reports stale after `after` without a change, None never does
+    pub fn new(after: Option<Duration>) -> Self {
+        Self {
+            after,
+            last_change: Instant::now(),
+            stale: false,
+        }
+    }
+
+    /// Record a detected change, restarting the window
+    pub fn record_change(&mut self, now: Instant) {
+        self.last_change = now;
+        self.stale = false;
+    }
+
+    /// Leave time the task spent paused, e.g. outside its active schedule, out of the window
+    pub fn pause(&mut self, paused: Duration) {
+        self.last_change += paused;
+    }
+
+    /// Record a check without change, returns true when the window has just elapsed since the last change
+    pub fn record_unchanged(&mut self, now: Instant) -> bool {
+        let Some(after) = self.after else {
+            return false;
+        };
+        if self.stale || now.saturating_duration_since(self.last_change) < after {
+            return false;
+        }
+        self.stale = true;
+        true
+    }
+
+    /// Whole minutes of the window, at least one
+    pub fn window_minutes(&self) -> u64 {
+        self.after.map_or(0, |after| (after.as_secs() / 60).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failure_tracker_alerts_once_and_recovers() {
+        let mut failures = FailureTracker::new(3);
+        assert!(!failures.record_failure());
+        assert!(!failures.record_failure());
+        assert!(failures.record_failure());
+        assert!(!failures.record_failure());
+        assert_eq!(failures.consecutive(), 4);
+
+        assert!(failures.record_success());
+        assert!(!failures.record_success());
+
+        // A single failure after recovering does not alert again
+        assert!(!failures.record_failure());
+    }
+
+    #[test]
+    fn quiet_hours_may_wrap_past_midnight() {
+        assert!(in_quiet_hours(Some((9, 17)), 9));
+        assert!(!in_quiet_hours(Some((9, 17)), 17));
+        assert!(!in_quiet_hours(Some((9, 17)), 3));
+
+        let night = Some((22, 7));
+        assert!(in_quiet_hours(night, 23));
+        assert!(in_quiet_hours(night, 0));
+        assert!(in_quiet_hours(night, 6));
+        assert!(!in_quiet_hours(night, 7));
+        assert!(!in_quiet_hours(night, 21));
+
+        assert!(!in_quiet_hours(Some((8, 8)), 8));
+        assert!(!in_quiet_hours(None, 3));
+    }
+
+    #[test]
+    fn stale_tracker_alerts_once_per_quiet_window() {
+        let start = Instant::now();
+        let minutes = |count: u64| start + Duration::from_secs(count * 60);
+        let mut stale = StaleTracker::new(Some(Duration::from_secs(600)));
+        stale.record_change(start);
+
+        assert!(!stale.record_unchanged(minutes(5)));
+        assert!(stale.record_unchanged(minutes(10)));
+        assert!(!stale.record_unchanged(minutes(20)));
+        assert_eq!(stale.window_minutes(), 10);
+
+        // A change restarts the window
+        stale.record_change(minutes(21));
+        assert!(!stale.record_unchanged(minutes(30)));
+        assert!(stale.record_unchanged(minutes(31)));
+
+        // Hours outside the active schedule don't count
+        stale.record_change(minutes(40));
+        assert!(!stale.record_unchanged(minutes(45)));
+        stale.pause(Duration::from_secs(60 * 60));
+        assert!(!stale.record_unchanged(minutes(105)));
+        assert!(stale.record_unchanged(minutes(110)));
+
+        assert!(!StaleTracker::new(None).record_unchanged(minutes(1000)));
+    }
+
+    /// Notifier that always fails with the given error
+    struct Failing(server_chan::ServerChanError);
+
+    #[async_trait::async_trait]
+    impl Notifier for Failing {
+        async fn send(&self, _title: &str, _content: &str) -> Result<()> {
+            Err(self.0.clone().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn composite_succeeds_when_any_notifier_delivers() {
+        let invalid_key = server_chan::ServerChanError::InvalidKey("bad key".to_string());
+        let mut dry_run = ServerChanNotifier::new("");
+        dry_run.set_dry_run(true);
+
+        let composite = CompositeNotifier::new(vec![Box::new(Failing(invalid_key.clone())), Box::new(dry_run)]);
+        assert!(composite.send("title", "content").await.is_ok());
+
+        // The error of a lone failing notifier is passed through, so it can still be inspected
+        let composite = CompositeNotifier::new(vec![Box::new(Failing(invalid_key.clone()))]);
+        let e = composite.send("title", "content").await.unwrap_err();
+        assert_eq!(e.downcast_ref::<server_chan::ServerChanError>(), Some(&invalid_key));
+
+        assert!(CompositeNotifier::new(Vec::new()).send("title", "content").await.is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::monitors::ChangeKind;
+use crate::utils::{read_from_file, write_to_file};
+
+/// Default location of the notifications waiting for redelivery
+pub const PENDING_PATH: &str = "pending_notifications.jsonl";
+
+/// Age after which a kept notification is dropped, e.g. because its task was deleted and never runs again
+pub const PENDING_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A notification that could not be delivered, kept to be sent again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingNotification {
+    /// Unix timestamp in seconds of the failed delivery
+    pub time: u64,
+    /// Name of the task that raised it
+    pub task: String,
+    /// Monitor id of the task that raised it, empty in entries kept before ids were stored
+    #[serde(default)]
+    pub task_id: String,
+    /// Kind of change, None in entries kept before kinds were stored
+    #[serde(default)]
+    pub kind: Option<ChangeKind>,
+    /// Title
+    pub title: String,
+    /// Content
+    pub content: String,
+    /// Whether the content is already Markdown rather than plain text
+    #[serde(default)]
+    pub markdown: bool,
+    /// Whether it is dropped last when the queue is full
+    pub critical: bool,
+}
+
+impl PendingNotification {
+    /// Notification of the given task that just failed to be delivered
+    pub fn new(task: &str, task_id: &str, kind: ChangeKind, title: &str, content: &str) -> Self {
+        Self {
+            time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            task: task.to_string(),
+            task_id: task_id.to_string(),
+            kind: Some(kind),
+            title: title.to_string(),
+            content: content.to_string(),
+            markdown: false,
+            critical: kind.is_critical(),
+        }
+    }
+
+    /// Whether it was kept longer than `PENDING_MAX_AGE` ago
+    fn is_expired(&self) -> bool {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        now.saturating_sub(self.time) > PENDING_MAX_AGE.as_secs()
+    }
+}
+
+/// Undelivered notifications kept as JSON lines until they can be sent again, surviving restarts.
+/// Cheap to clone, clones share the file
+#[derive(Debug, Clone)]
+pub struct PendingNotifications {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl PendingNotifications {
+    /// Pending notifications stored at the given path
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Keep a notification for later
+    pub fn push(&self, notification: &PendingNotification) -> Result<()> {
+        let mut line = serde_json::to_string(notification)?;
+        line.push('\n');
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Remove and return the notifications `accept` selects, oldest first; unreadable and expired ones are dropped
+    pub fn take<F: Fn(&PendingNotification) -> bool>(&self, accept: F) -> Result<Vec<PendingNotification>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let Ok(content) = read_from_file(&self.path) else {
+            return Ok(Vec::new());
+        };
+
+        let mut expired = 0;
+        let (taken, kept): (Vec<PendingNotification>, Vec<PendingNotification>) = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(notification) => Some(notification),
+                Err(e) => {
+                    warn!("Dropping unreadable pending notification: {}", e);
+                    None
+                }
+            })
+            .filter(|notification: &PendingNotification| {
+                if !notification.is_expired() {
+                    return true;
+                }
+                warn!("Dropping expired pending notification of {}: {}", notification.task, notification.title);
+                expired += 1;
+                false
+            })
+            .partition(|notification| accept(notification));
+        if taken.is_empty() && expired == 0 {
+            return Ok(taken);
+        }
+
+        if kept.is_empty() {
+            fs::remove_file(&self.path)?;
+        } else {
+            let mut lines = String::new();
+            for notification in &kept {
+                lines.push_str(&serde_json::to_string(notification)?);
+                lines.push('\n');
+            }
+            write_to_file(&self.path, &lines)?;
+        }
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taking_leaves_the_other_tasks_notifications() {
+        let path = std::env::temp_dir().join(format!("monitor-pending-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let pending = PendingNotifications::new(&path);
+        assert!(pending.take(|_| true).unwrap().is_empty());
+
+        pending.push(&PendingNotification::new("BTC", "btc", ChangeKind::Error, "Task failing", "timeout")).unwrap();
+        pending.push(&PendingNotification::new("News", "news", ChangeKind::Updated, "Page changed", "")).unwrap();
+        pending.push(&PendingNotification::new("BTC", "btc", ChangeKind::Updated, "Price changed", "42")).unwrap();
+        let mut expired = PendingNotification::new("Deleted", "deleted", ChangeKind::Updated, "Old change", "");
+        expired.time -= PENDING_MAX_AGE.as_secs() + 1;
+        pending.push(&expired).unwrap();
+
+        let btc = pending.take(|notification| notification.task_id == "btc").unwrap();
+        let titles: Vec<&str> = btc.iter().map(|notification| notification.title.as_str()).collect();
+        assert_eq!(titles, ["Task failing", "Price changed"]);
+        assert!(btc[0].critical);
+        assert_eq!(btc[0].kind, Some(ChangeKind::Error));
+
+        let rest = pending.take(|_| true).unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].task, "News");
+        assert!(!path.exists());
+    }
+}
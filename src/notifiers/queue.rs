@@ -0,0 +1,907 @@
+use anyhow::Result;
+use chrono::Timelike;
+use log::error;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+use tokio::time::Instant;
+
+use crate::monitors::ChangeKind;
+use crate::notifiers::{in_quiet_hours, should_hold, Notifier};
+use crate::notifiers::history::{HistoryEntry, NotificationHistory};
+use crate::notifiers::pending::{PendingNotification, PendingNotifications};
+use crate::notifiers::server_chan::{markdown_escape, ServerChanError};
+use crate::utils::TaskLogger;
+use crate::utils::events::EventKind;
+
+/// Notifications waiting to be sent before the oldest ones are dropped
+pub const QUEUE_CAPACITY: usize = 100;
+
+/// Attempts per notification, including the first, before it is kept for later
+const MAX_ATTEMPTS: u32 = 2;
+
+/// Delay before the first retry, doubled after every failed attempt
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Notifications collected in a task's digest before it is sent without waiting for the interval
+const DIGEST_CAPACITY: usize = 20;
+
+/// How long to wait for the queue to be sent before exiting, enough for a notification's retry
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often `drain` checks whether the queue was sent
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the worker queues again the notifications kept after failing
+const PENDING_RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How long notifications are muted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mute {
+    /// Muted until this time, then sent again
+    Until(Instant),
+    /// Muted until unmuted by hand
+    Indefinitely,
+}
+
+impl Mute {
+    /// Time left before the mute expires, None when it doesn't expire
+    pub fn remaining(&self) -> Option<Duration> {
+        match self {
+            Mute::Until(until) => Some(until.saturating_duration_since(Instant::now())),
+            Mute::Indefinitely => None,
+        }
+    }
+    
+    /// Whether the mute is over
+    fn is_expired(&self) -> bool {
+        self.remaining().is_some_and(|remaining| remaining.is_zero())
+    }
+}
+
+/// A notification waiting to be sent
+pub struct Notification {
+    /// Notifier of the task that raised it
+    pub notifier: Arc<dyn Notifier>,
+    /// Title
+    pub title: String,
+    /// Content
+    pub content: String,
+    /// Whether the content is already Markdown, like a digest, rather than plain text to escape
+    pub markdown: bool,
+    /// Kind of change, `Updated` for digests
+    pub kind: ChangeKind,
+    /// Critical notifications are dropped last when the queue is full
+    pub critical: bool,
+    /// Logger of the task that raised it
+    pub logger: TaskLogger,
+    /// Monitor id of the task that raised it, unlike its name unique among tasks
+    pub task_id: String,
+}
+
+/// Non-critical notifications of one task collected for a digest
+struct Digest {
+    notifier: Arc<dyn Notifier>,
+    logger: TaskLogger,
+    task_id: String,
+    entries: Vec<(String, String)>,
+}
+
+impl Digest {
+    /// Consolidate the collected notifications into one Markdown message with every entry escaped,
+    /// a single entry is sent unchanged
+    fn into_notification(mut self) -> Notification {
+        let markdown = self.entries.len() > 1;
+        let (title, content) = if markdown {
+            let title = format!("{}: {} changes", self.logger.name(), self.entries.len());
+            let content = self.entries
+                .iter()
+                .map(|(title, content)| format!("### {}\n\n{}", markdown_escape(title), markdown_escape(content)))
+                .collect::<Vec<_>>()
+                .join("\n\n---\n\n");
+            (title, content)
+        } else {
+            self.entries.remove(0)
+        };
+        Notification {
+            notifier: self.notifier,
+            title,
+            content,
+            markdown,
+            kind: ChangeKind::Updated,
+            critical: false,
+            logger: self.logger,
+            task_id: self.task_id,
+        }
+    }
+}
+
+/// Notifier and logger of each task a sender was created for, by monitor id
+type TaskSenders = HashMap<String, (Arc<dyn Notifier>, TaskLogger)>;
+
+/// Bounded queue drained by a background worker, so a slow or failing notifier never delays a check
+///
+/// When the queue is full the oldest non-critical notification is dropped to make room,
+/// or the oldest one if all of them are critical. With a digest interval set, the worker
+/// collects non-critical notifications per task and sends one message per task when it elapses.
+/// With a history set, every delivery attempt's outcome is appended to it. While muted,
+/// notifications are logged and recorded in the history as muted but never queued; critical
+/// ones still go out unless `set_mute_critical` is on. With a pending file set,
+/// notifications still failing after the retry are kept in it and queued again every few minutes,
+/// as soon as another notification of their task is delivered, or when a sender for their task is
+/// created, e.g. on the next startup; those of tasks that never come back expire.
+/// Call `drain` before exiting so queued notifications are sent rather than lost.
+#[derive(Clone)]
+pub struct NotificationQueue {
+    pending: Arc<Mutex<VecDeque<Notification>>>,
+    ready: Arc<Notify>,
+    capacity: usize,
+    digest_interval: Arc<Mutex<Option<Duration>>>,
+    history: Arc<Mutex<Option<NotificationHistory>>>,
+    muted: Arc<Mutex<Option<Mute>>>,
+    mute_critical: Arc<AtomicBool>,
+    title_prefixes: Arc<Mutex<BTreeMap<ChangeKind, String>>>,
+    undelivered: Arc<Mutex<Option<PendingNotifications>>>,
+    /// Monitor ids of the tasks with notifications kept since their last delivery
+    kept: Arc<Mutex<HashSet<String>>>,
+    senders: Arc<Mutex<TaskSenders>>,
+    unsent: Arc<AtomicUsize>,
+}
+
+impl NotificationQueue {
+    /// Create an empty queue, call `spawn` to start delivering
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(VecDeque::new())),
+            ready: Arc::new(Notify::new()),
+            capacity: capacity.max(1),
+            digest_interval: Arc::new(Mutex::new(None)),
+            history: Arc::new(Mutex::new(None)),
+            muted: Arc::new(Mutex::new(None)),
+            mute_critical: Arc::new(AtomicBool::new(false)),
+            title_prefixes: Arc::new(Mutex::new(BTreeMap::new())),
+            undelivered: Arc::new(Mutex::new(None)),
+            kept: Arc::new(Mutex::new(HashSet::new())),
+            senders: Arc::new(Mutex::new(HashMap::new())),
+            unsent: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Collect non-critical notifications into a digest per task over the interval, None to send each one
+    pub fn set_digest_interval(&self, interval: Option<Duration>) {
+        *self.digest_interval.lock().unwrap() = interval.filter(|interval| !interval.is_zero());
+        // Wake the worker so turning digests off flushes what was collected
+        self.ready.notify_one();
+    }
+
+    /// Record sent and failed notifications to a history file, None to keep no history
+    pub fn set_history(&self, history: Option<NotificationHistory>) {
+        *self.history.lock().unwrap() = history;
+    }
+
+    /// Keep notifications that could not be delivered to send them again later, None to drop them
+    pub fn set_pending(&self, pending: Option<PendingNotifications>) {
+        *self.undelivered.lock().unwrap() = pending;
+    }
+    
+    /// Put a prefix, e.g. an emoji, before the title of each kind of notification; kinds left out get none
+    pub fn set_title_prefixes(&self, prefixes: BTreeMap<ChangeKind, String>) {
+        *self.title_prefixes.lock().unwrap() = prefixes;
+    }
+    
+    /// Title with the prefix of its kind, if any
+    fn prefixed_title(&self, kind: ChangeKind, title: &str) -> String {
+        match self.title_prefixes.lock().unwrap().get(&kind).map(|prefix| prefix.trim()).filter(|prefix| !prefix.is_empty()) {
+            Some(prefix) => format!("{} {}", prefix, title),
+            None => title.to_string(),
+        }
+    }
+    
+    /// Suppress notifications, None to unmute
+    pub fn set_mute(&self, mute: Option<Mute>) {
+        *self.muted.lock().unwrap() = mute;
+    }
+
+    /// Whether a mute also suppresses critical notifications, off by default so errors and outages still go out
+    pub fn set_mute_critical(&self, mute_critical: bool) {
+        self.mute_critical.store(mute_critical, Ordering::Relaxed);
+    }
+    
+    /// Current mute, None when not muted or the mute expired
+    pub fn mute(&self) -> Option<Mute> {
+        let mut muted = self.muted.lock().unwrap();
+        if muted.is_some_and(|mute| mute.is_expired()) {
+            *muted = None;
+        }
+        *muted
+    }
+    
+    /// Queue a notification, returns the one dropped to make room, if any
+    pub fn push(&self, notification: Notification) -> Option<Notification> {
+        let mut pending = self.pending.lock().unwrap();
+        let dropped = if pending.len() >= self.capacity {
+            let index = pending.iter().position(|queued| !queued.critical).unwrap_or(0);
+            pending.remove(index)
+        } else {
+            None
+        };
+        pending.push_back(notification);
+        drop(pending);
+
+        if let Some(dropped) = &dropped {
+            dropped.logger.error(&format!("Notification queue full, dropped: {}", dropped.title));
+        }
+        self.ready.notify_one();
+        dropped
+    }
+
+    /// Number of notifications waiting to be sent
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Whether nothing is waiting to be sent
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Handle for the task with the given monitor id pushing notifications to be sent through its own notifier
+    pub fn sender(&self, task_id: &str, notifier: Arc<dyn Notifier>, logger: TaskLogger) -> NotificationSender {
+        self.senders.lock().unwrap().insert(task_id.to_string(), (notifier.clone(), logger.clone()));
+        self.requeue_pending(|id| id == task_id);
+        NotificationSender {
+            queue: self.clone(),
+            notifier,
+            logger,
+            task_id: task_id.to_string(),
+        }
+    }
+    
+    /// Queue again the kept notifications of the tasks whose monitor id `accept` selects among those with a sender
+    fn requeue_pending<F: Fn(&str) -> bool>(&self, accept: F) {
+        let Some(pending) = self.undelivered.lock().unwrap().clone() else {
+            return;
+        };
+        let senders = self.senders.lock().unwrap().clone();
+        // Entries kept before monitor ids were stored only name their task
+        let task_id = |notification: &PendingNotification| -> Option<String> {
+            if !notification.task_id.is_empty() {
+                return Some(notification.task_id.clone());
+            }
+            senders.iter().find(|(_, (_, logger))| logger.name() == notification.task).map(|(id, _)| id.clone())
+        };
+        let taken = match pending.take(|notification| task_id(notification).is_some_and(|id| senders.contains_key(&id) && accept(&id))) {
+            Ok(taken) => taken,
+            Err(e) => {
+                error!("Failed to read pending notifications: {}", e);
+                return;
+            }
+        };
+        for notification in taken {
+            let Some(task_id) = task_id(&notification) else {
+                continue;
+            };
+            let (notifier, logger) = senders[&task_id].clone();
+            logger.info(&format!("Sending again: {}", notification.title));
+            let kind = notification.kind.unwrap_or(if notification.critical { ChangeKind::Error } else { ChangeKind::Updated });
+            self.push(Notification {
+                notifier,
+                title: notification.title,
+                content: notification.content,
+                markdown: notification.markdown,
+                kind,
+                critical: kind.is_critical(),
+                logger,
+                task_id,
+            });
+        }
+    }
+
+    /// Start the worker sending queued notifications, retrying with backoff. Each notifier sends its
+    /// notifications in order, concurrently with the others; `on_failure` is called for notifications
+    /// that could not be delivered
+    pub fn spawn<F>(&self, on_failure: F) -> JoinHandle<()>
+    where
+        F: Fn(&Notification, &anyhow::Error) + Send + Sync + 'static,
+    {
+        let queue = self.clone();
+        let mut deliveries = Deliveries {
+            queue: self.clone(),
+            on_failure: Arc::new(on_failure),
+            waiting: HashMap::new(),
+            sending: JoinSet::new(),
+        };
+        tokio::spawn(async move {
+            let mut digests: Vec<Digest> = Vec::new();
+            let mut flush_at: Option<Instant> = None;
+            let mut retry_at = Instant::now() + PENDING_RETRY_INTERVAL;
+            loop {
+                deliveries.start_next();
+
+                if retry_at <= Instant::now() {
+                    queue.requeue_pending(|_| true);
+                    retry_at = Instant::now() + PENDING_RETRY_INTERVAL;
+                }
+                
+                let digest_interval = *queue.digest_interval.lock().unwrap();
+                let due = flush_at.is_some_and(|at| at <= Instant::now());
+                if due || (digest_interval.is_none() && !digests.is_empty()) {
+                    for digest in digests.drain(..) {
+                        deliveries.send_digest(digest);
+                    }
+                    flush_at = None;
+                }
+                
+                let next = queue.pending.lock().unwrap().pop_front();
+                if next.is_some() {
+                    queue.unsent.fetch_add(1, Ordering::SeqCst);
+                }
+                match (next, digest_interval) {
+                    (Some(notification), Some(interval)) if notification.kind.is_digested() => {
+                        if let Some(full) = collect(&mut digests, notification) {
+                            deliveries.send_digest(full);
+                        }
+                        flush_at.get_or_insert_with(|| Instant::now() + interval);
+                    }
+                    (Some(notification), _) => deliveries.send(notification),
+                    (None, _) => {
+                        let flush = async {
+                            match flush_at {
+                                Some(at) => tokio::time::sleep_until(at).await,
+                                None => std::future::pending().await,
+                            }
+                        };
+                        tokio::select! {
+                            _ = queue.ready.notified() => {}
+                            _ = flush => {}
+                            _ = tokio::time::sleep_until(retry_at) => {}
+                            _ = deliveries.finished() => {}
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Wait until every queued notification was sent or given up on, for at most `timeout`, sending collected
+    /// digests right away; returns false when some were still unsent. The worker must be running
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.set_digest_interval(None);
+        tokio::time::timeout(timeout, async {
+            // Checked after a first pause so the notifications of tasks stopped just before are included
+            loop {
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+                if self.is_empty() && self.unsent() == 0 {
+                    break;
+                }
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Number of notifications taken by the worker and not sent or given up on yet
+    pub fn unsent(&self) -> usize {
+        self.unsent.load(Ordering::SeqCst)
+    }
+
+    /// Send a notification, recording the outcome in the history; a transient failure keeps it for later,
+    /// a delivery queues again what its task kept since the notifier works again
+    async fn deliver_and_record(&self, notification: &Notification) -> Result<()> {
+        let result = deliver(notification).await;
+        self.record(notification, &result);
+        if result.as_ref().is_err_and(is_retryable) {
+            self.keep_pending(notification);
+        } else if result.is_ok() && self.kept.lock().unwrap().remove(&notification.task_id) {
+            self.requeue_pending(|id| id == notification.task_id);
+        }
+        result
+    }
+    
+    /// Keep an undelivered notification for later, if a pending file is set
+    fn keep_pending(&self, notification: &Notification) {
+        let Some(pending) = self.undelivered.lock().unwrap().clone() else {
+            return;
+        };
+        let mut entry = PendingNotification::new(
+            notification.logger.name(),
+            &notification.task_id,
+            notification.kind,
+            &notification.title,
+            &notification.content,
+        );
+        entry.markdown = notification.markdown;
+        entry.critical = notification.critical;
+        match pending.push(&entry) {
+            Ok(()) => {
+                self.kept.lock().unwrap().insert(notification.task_id.clone());
+                notification.logger.info(&format!("Kept for redelivery: {}", notification.title));
+            }
+            Err(e) => notification.logger.error(&format!("Failed to keep undelivered notification: {}", e)),
+        }
+    }
+    
+    /// Append the outcome of a notification to the history, if one is kept, and to the task's event log
+    fn record(&self, notification: &Notification, result: &Result<()>) {
+        match result {
+            Ok(()) => notification.logger.event(EventKind::NotificationSent, &notification.title),
+            Err(e) => notification.logger.event(EventKind::NotificationFailed, &format!("{}: {}", notification.title, e)),
+        }
+        let history = self.history.lock().unwrap().clone();
+        if let Some(history) = history {
+            let entry = HistoryEntry::new(notification.logger.name(), &notification.title, &notification.content, result);
+            if let Err(e) = history.record(&entry) {
+                notification.logger.error(&format!("Failed to record notification history: {}", e));
+            }
+        }
+    }
+
+    /// Log a notification suppressed by the mute and add it to the history, if one is kept
+    fn record_muted(&self, notification: &Notification) {
+        notification.logger.info(&format!("Notifications muted, not sent: {}", notification.title));
+        let history = self.history.lock().unwrap().clone();
+        if let Some(history) = history {
+            let entry = HistoryEntry::muted(notification.logger.name(), &notification.title, &notification.content);
+            if let Err(e) = history.record(&entry) {
+                notification.logger.error(&format!("Failed to record notification history: {}", e));
+            }
+        }
+    }
+}
+
+/// Called with notifications that could not be delivered
+type OnFailure = Arc<dyn Fn(&Notification, &anyhow::Error) + Send + Sync>;
+
+/// Notifications the worker is sending, one at a time per notifier so each keeps its order
+/// while a slow or failing notifier doesn't hold up the others
+struct Deliveries {
+    queue: NotificationQueue,
+    on_failure: OnFailure,
+    /// Notifications waiting for the one their notifier is sending, by notifier
+    waiting: HashMap<usize, VecDeque<Notification>>,
+    /// Deliveries in progress, each returning its notifier
+    sending: JoinSet<usize>,
+}
+
+impl Deliveries {
+    /// Send a notification once the earlier ones of its notifier are sent
+    fn send(&mut self, notification: Notification) {
+        let lane = Arc::as_ptr(&notification.notifier) as *const () as usize;
+        match self.waiting.get_mut(&lane) {
+            Some(waiting) => waiting.push_back(notification),
+            None => {
+                self.waiting.insert(lane, VecDeque::new());
+                self.start(lane, notification);
+            }
+        }
+    }
+
+    /// Send a digest as one notification
+    fn send_digest(&mut self, digest: Digest) {
+        // Its entries were counted as unsent one by one
+        self.queue.unsent.fetch_sub(digest.entries.len() - 1, Ordering::SeqCst);
+        self.send(digest.into_notification());
+    }
+
+    fn start(&mut self, lane: usize, notification: Notification) {
+        let (queue, on_failure) = (self.queue.clone(), self.on_failure.clone());
+        self.sending.spawn(async move {
+            if let Err(e) = queue.deliver_and_record(&notification).await {
+                on_failure(&notification, &e);
+            }
+            queue.unsent.fetch_sub(1, Ordering::SeqCst);
+            lane
+        });
+    }
+
+    /// Start the next notification of every notifier that is done sending
+    fn start_next(&mut self) {
+        while let Some(done) = self.sending.try_join_next() {
+            self.done(done);
+        }
+    }
+
+    /// Wait until a delivery is done and start the next one of its notifier, never returns when none is in progress
+    async fn finished(&mut self) {
+        match self.sending.join_next().await {
+            Some(done) => self.done(done),
+            None => std::future::pending().await,
+        }
+    }
+
+    fn done(&mut self, done: Result<usize, JoinError>) {
+        match done {
+            Ok(lane) => match self.waiting.get_mut(&lane).and_then(VecDeque::pop_front) {
+                Some(next) => self.start(lane, next),
+                None => {
+                    self.waiting.remove(&lane);
+                }
+            },
+            Err(e) => error!("Notification delivery stopped: {}", e),
+        }
+    }
+}
+
+/// A task's handle on the notification queue
+#[derive(Clone)]
+pub struct NotificationSender {
+    queue: NotificationQueue,
+    notifier: Arc<dyn Notifier>,
+    logger: TaskLogger,
+    task_id: String,
+}
+
+impl NotificationSender {
+    /// Queue a notification of the given kind for sending in the background, critical kinds are dropped last;
+    /// returns false when the mute suppressed it
+    pub fn send(&self, kind: ChangeKind, title: &str, content: &str) -> bool {
+        let notification = Notification {
+            notifier: self.notifier.clone(),
+            title: self.queue.prefixed_title(kind, title),
+            content: content.to_string(),
+            markdown: false,
+            kind,
+            critical: kind.is_critical(),
+            logger: self.logger.clone(),
+            task_id: self.task_id.clone(),
+        };
+        let muted = self.queue.mute().is_some()
+            && (!notification.critical || self.queue.mute_critical.load(Ordering::Relaxed));
+        if muted {
+            self.queue.record_muted(&notification);
+            return false;
+        }
+        self.queue.push(notification);
+        true
+    }
+}
+
+/// A task's notifications held back during quiet hours, sent once they end or when the task stops
+pub struct HeldNotifications {
+    sender: Option<NotificationSender>,
+    quiet_hours: Option<(u8, u8)>,
+    held: Vec<(ChangeKind, String, String)>,
+}
+
+impl HeldNotifications {
+    /// Hold notifications for the sender during the (start, end) local hours, None never holds
+    pub fn new(sender: Option<NotificationSender>, quiet_hours: Option<(u8, u8)>) -> Self {
+        Self { sender, quiet_hours, held: Vec::new() }
+    }
+
+    /// Hold a non-critical notification during quiet hours, returns false when it should be sent now
+    pub fn hold(&mut self, kind: ChangeKind, title: &str, content: &str) -> bool {
+        if !should_hold(self.quiet_hours, kind) {
+            return false;
+        }
+        self.held.push((kind, title.to_string(), content.to_string()));
+        true
+    }
+
+    /// Send the held notifications once quiet hours are over, returns how many were sent
+    pub fn release(&mut self) -> usize {
+        if in_quiet_hours(self.quiet_hours, chrono::Local::now().hour()) {
+            return 0;
+        }
+        self.flush()
+    }
+
+    /// Whether no notification is held
+    pub fn is_empty(&self) -> bool {
+        self.held.is_empty()
+    }
+
+    fn flush(&mut self) -> usize {
+        let count = self.held.len();
+        for (kind, title, content) in self.held.drain(..) {
+            if let Some(sender) = &self.sender {
+                sender.send(kind, &title, &content);
+            }
+        }
+        count
+    }
+}
+
+impl Drop for HeldNotifications {
+    /// A stopped task sends what it held instead of losing it
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Add a notification to the digest of its task, returns the digest once it is full so it is sent right away
+fn collect(digests: &mut Vec<Digest>, notification: Notification) -> Option<Digest> {
+    let entry = (notification.title, notification.content);
+    let index = match digests.iter().position(|digest| digest.task_id == notification.task_id) {
+        Some(index) => {
+            digests[index].entries.push(entry);
+            index
+        }
+        None => {
+            digests.push(Digest {
+                notifier: notification.notifier,
+                logger: notification.logger,
+                task_id: notification.task_id,
+                entries: vec![entry],
+            });
+            digests.len() - 1
+        }
+    };
+    (digests[index].entries.len() >= DIGEST_CAPACITY).then(|| digests.remove(index))
+}
+
+/// Send a notification, retrying failures that may go away
+async fn deliver(notification: &Notification) -> Result<()> {
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let sent = if notification.markdown {
+            notification.notifier.send_markdown(&notification.title, &notification.content).await
+        } else {
+            notification.notifier.send(&notification.title, &notification.content).await
+        };
+        match sent {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < MAX_ATTEMPTS && is_retryable(&e) => {
+                notification.logger.debug(&format!("Notification attempt {} failed, retrying in {:?}: {}", attempt, delay, e));
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Network errors and transient ServerChan errors are retried, rejected keys and quota are not
+fn is_retryable(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<ServerChanError>().is_none_or(ServerChanError::is_retryable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifiers::server_chan::ServerChanNotifier;
+
+    fn notification(title: &str, critical: bool) -> Notification {
+        task_notification("test", title, critical)
+    }
+
+    fn task_notification(task: &str, title: &str, critical: bool) -> Notification {
+        Notification {
+            notifier: Arc::new(ServerChanNotifier::new("")),
+            title: title.to_string(),
+            content: format!("{} details", title),
+            markdown: false,
+            kind: if critical { ChangeKind::Error } else { ChangeKind::Updated },
+            critical,
+            logger: TaskLogger::new(task),
+            task_id: task.to_lowercase(),
+        }
+    }
+
+    #[test]
+    fn full_queue_drops_oldest_non_critical() {
+        let queue = NotificationQueue::new(2);
+        assert!(queue.push(notification("failing", true)).is_none());
+        assert!(queue.push(notification("changed 1", false)).is_none());
+
+        let dropped = queue.push(notification("changed 2", false)).unwrap();
+        assert_eq!(dropped.title, "changed 1");
+        assert_eq!(queue.len(), 2);
+
+        queue.push(notification("recovered", true));
+        let dropped = queue.push(notification("failing again", true)).unwrap();
+        assert_eq!(dropped.title, "failing");
+    }
+
+    #[test]
+    fn muted_notifications_are_not_queued_until_the_mute_expires() {
+        let queue = NotificationQueue::new(10);
+        let sender = queue.sender("test", Arc::new(ServerChanNotifier::new("")), TaskLogger::new("test"));
+
+        queue.set_mute(Some(Mute::Until(Instant::now() + Duration::from_secs(60))));
+        assert!(!sender.send(ChangeKind::Updated, "changed", ""));
+        queue.set_mute(Some(Mute::Indefinitely));
+        assert!(!sender.send(ChangeKind::Stale, "stale", ""));
+        assert!(queue.is_empty());
+        assert!(sender.send(ChangeKind::Error, "failing", ""));
+        assert_eq!(queue.len(), 1);
+        queue.set_mute_critical(true);
+        assert!(!sender.send(ChangeKind::Down, "down", ""));
+        assert_eq!(queue.len(), 1);
+
+        queue.set_mute(Some(Mute::Until(Instant::now())));
+        assert_eq!(queue.mute(), None);
+        assert!(sender.send(ChangeKind::Updated, "changed again", ""));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn kept_notifications_are_queued_again_once_their_task_has_a_sender() {
+        let path = std::env::temp_dir().join(format!("monitor-queue-pending-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let pending = PendingNotifications::new(&path);
+        pending.push(&PendingNotification::new("BTC", "api-btc", ChangeKind::Down, "Task failing", "timeout")).unwrap();
+        pending.push(&PendingNotification::new("News", "static-news", ChangeKind::Updated, "Page changed", "")).unwrap();
+
+        // Found by monitor id after the task was renamed
+        let queue = NotificationQueue::new(10);
+        queue.set_pending(Some(pending.clone()));
+        queue.sender("api-btc", Arc::new(ServerChanNotifier::new("")), TaskLogger::new("Bitcoin"));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pending.lock().unwrap()[0].kind, ChangeKind::Down);
+        assert!(queue.pending.lock().unwrap()[0].critical);
+
+        // Other tasks' notifications wait for their own sender
+        let rest = pending.take(|_| true).unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].task, "News");
+    }
+
+    #[test]
+    fn titles_get_the_prefix_of_their_kind() {
+        let queue = NotificationQueue::new(10);
+        let sender = queue.sender("test", Arc::new(ServerChanNotifier::new("")), TaskLogger::new("test"));
+        queue.set_title_prefixes(BTreeMap::from([(ChangeKind::Error, "🔴".to_string()), (ChangeKind::Updated, " ".to_string())]));
+
+        sender.send(ChangeKind::Error, "[BTC] Task failing", "");
+        sender.send(ChangeKind::Updated, "[BTC] Price changed", "");
+        sender.send(ChangeKind::Trade, "[Whale] New trade", "");
+        let pending = queue.pending.lock().unwrap();
+        let titles: Vec<&str> = pending.iter().map(|notification| notification.title.as_str()).collect();
+        assert_eq!(titles, ["🔴 [BTC] Task failing", "[BTC] Price changed", "[Whale] New trade"]);
+        assert!(pending[0].critical);
+    }
+
+    #[test]
+    fn held_notifications_are_sent_when_the_task_stops() {
+        let queue = NotificationQueue::new(10);
+        let sender = queue.sender("test", Arc::new(ServerChanNotifier::new("")), TaskLogger::new("test"));
+
+        // Quiet all day long
+        let mut held = HeldNotifications::new(Some(sender), Some((0, 24)));
+        assert!(held.hold(ChangeKind::Updated, "[BTC] Price changed", ""));
+        assert!(!held.hold(ChangeKind::Error, "[BTC] Task failing", ""));
+        assert_eq!(held.release(), 0);
+        assert!(queue.is_empty());
+
+        drop(held);
+        assert_eq!(queue.len(), 1);
+    }
+
+    /// Records the titles it sends after a delay
+    struct Recording {
+        delay: Duration,
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Notifier for Recording {
+        async fn send(&self, title: &str, _content: &str) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            self.sent.lock().unwrap().push(title.to_string());
+            Ok(())
+        }
+    }
+
+    /// Fails its first sends with a transient error, then records the titles
+    struct Flaky {
+        failures: Mutex<u32>,
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Notifier for Flaky {
+        async fn send(&self, title: &str, _content: &str) -> Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(anyhow::anyhow!("connection reset"));
+            }
+            self.sent.lock().unwrap().push(title.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn a_delivery_sends_what_its_task_kept_after_a_single_retry() {
+        let path = std::env::temp_dir().join(format!("monitor-queue-flush-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let queue = NotificationQueue::new(10);
+        queue.set_pending(Some(PendingNotifications::new(&path)));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let flaky = Flaky { failures: Mutex::new(MAX_ATTEMPTS), sent: sent.clone() };
+        let sender = queue.sender("api-btc", Arc::new(flaky), TaskLogger::new("BTC"));
+        queue.spawn(|_, _| {});
+
+        sender.send(ChangeKind::Updated, "Price changed", "");
+        tokio::time::sleep(RETRY_DELAY + Duration::from_millis(200)).await;
+        assert!(sent.lock().unwrap().is_empty());
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+        sender.send(ChangeKind::Updated, "Price changed again", "");
+        assert!(queue.drain(Duration::from_secs(5)).await);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(*sent.lock().unwrap(), ["Price changed again", "Price changed"]);
+    }
+
+    #[tokio::test]
+    async fn slow_notifiers_do_not_hold_up_the_others_and_are_drained_on_exit() {
+        let queue = NotificationQueue::new(10);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let slow = Recording { delay: Duration::from_millis(300), sent: sent.clone() };
+        let fast = Recording { delay: Duration::ZERO, sent: sent.clone() };
+        let slow = queue.sender("slow", Arc::new(slow), TaskLogger::new("slow"));
+        let fast = queue.sender("fast", Arc::new(fast), TaskLogger::new("fast"));
+        queue.spawn(|_, _| {});
+
+        slow.send(ChangeKind::Updated, "slow 1", "");
+        slow.send(ChangeKind::Updated, "slow 2", "");
+        fast.send(ChangeKind::Updated, "fast", "");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(*sent.lock().unwrap(), ["fast"]);
+
+        assert!(!queue.drain(Duration::from_millis(100)).await);
+        assert!(queue.drain(Duration::from_secs(5)).await);
+        assert_eq!(*sent.lock().unwrap(), ["fast", "slow 1", "slow 2"]);
+        assert_eq!(queue.unsent(), 0);
+    }
+
+    #[tokio::test]
+    async fn a_task_stopping_skips_the_digest() {
+        let queue = NotificationQueue::new(10);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sender = queue.sender("btc", Arc::new(Recording { delay: Duration::ZERO, sent: sent.clone() }), TaskLogger::new("BTC"));
+        queue.set_title_prefixes(BTreeMap::from([(ChangeKind::Stopped, "⏹️".to_string())]));
+        queue.set_digest_interval(Some(Duration::from_secs(3600)));
+        queue.spawn(|_, _| {});
+
+        sender.send(ChangeKind::Updated, "Price changed", "");
+        sender.send(ChangeKind::Stopped, "Monitoring window ended", "");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(*sent.lock().unwrap(), ["⏹️ Monitoring window ended"]);
+    }
+
+    #[test]
+    fn digests_consolidate_per_task() {
+        let mut digests = Vec::new();
+        collect(&mut digests, task_notification("BTC", "Price changed", false));
+        collect(&mut digests, task_notification("News", "Page changed", false));
+        collect(&mut digests, task_notification("BTC", "Price is $5", false));
+        assert_eq!(digests.len(), 2);
+
+        // A task of the same name gets its own digest
+        let mut other = task_notification("BTC", "Other price changed", false);
+        other.task_id = "btc-2".to_string();
+        collect(&mut digests, other);
+        assert_eq!(digests.len(), 3);
+        digests.pop();
+
+        // Entries are escaped, the digest's own headings and separators are not
+        let mut digests = digests.into_iter().map(Digest::into_notification);
+        let btc = digests.next().unwrap();
+        assert_eq!(btc.title, "BTC: 2 changes");
+        assert_eq!(btc.content, "### Price changed\n\nPrice changed details\n\n---\n\n### Price is \\$5\n\nPrice is \\$5 details");
+        assert!(btc.markdown);
+
+        let news = digests.next().unwrap();
+        assert_eq!(news.title, "Page changed");
+        assert_eq!(news.content, "Page changed details");
+        assert!(!news.markdown);
+    }
+
+    #[test]
+    fn full_digests_are_sent_right_away() {
+        let mut digests = Vec::new();
+        for index in 1..DIGEST_CAPACITY {
+            assert!(collect(&mut digests, task_notification("BTC", &format!("Change {}", index), false)).is_none());
+        }
+        let full = collect(&mut digests, task_notification("BTC", "One too many", false)).unwrap();
+        assert_eq!(full.entries.len(), DIGEST_CAPACITY);
+        assert!(digests.is_empty());
+    }
+}
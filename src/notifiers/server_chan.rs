@@ -1,18 +1,73 @@
 use anyhow::{Result, anyhow};
-use log::{debug, error};
+use futures::future::join_all;
+use log::{debug, error, info};
 use reqwest::Client;
 use reqwest::header::{CONTENT_TYPE, CONTENT_LENGTH};
 use serde_json::Value;
 use serde_urlencoded;
 use regex::Regex;
 use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
 
 use crate::notifiers::Notifier;
+use crate::utils::{mask_secret, redact_secret};
+
+/// Failure reported by ServerChan in the `code` field of its response
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ServerChanError {
+    /// The key does not exist or was revoked, retrying will not help
+    #[error("ServerChan rejected the key, check your ServerChan key ({0})")]
+    InvalidKey(String),
+    /// Too many messages in a short time, worth retrying later
+    #[error("ServerChan rate limit hit ({0})")]
+    RateLimited(String),
+    /// Daily message quota used up, resets the next day
+    #[error("ServerChan daily quota exceeded ({0})")]
+    QuotaExceeded(String),
+    /// Any other non-zero code
+    #[error("ServerChan error {code}: {message}")]
+    Other { code: i64, message: String },
+}
+
+impl ServerChanError {
+    /// Map a non-zero response code and its message to an error
+    pub fn from_response(code: i64, message: &str) -> Self {
+        let lower = message.to_lowercase();
+        let message = message.to_string();
+        match code {
+            40001 | 40003 => Self::InvalidKey(message),
+            _ if lower.contains("pushkey") || lower.contains("sendkey") || message.contains("密钥") => Self::InvalidKey(message),
+            _ if lower.contains("quota") || message.contains("当天") || message.contains("今日") => Self::QuotaExceeded(message),
+            _ if lower.contains("limit") || lower.contains("too many") || message.contains("频繁") => Self::RateLimited(message),
+            _ => Self::Other { code, message },
+        }
+    }
+
+    /// Whether sending again later can succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited(_) | Self::Other { .. })
+    }
+
+    /// Whether the key should no longer be used
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, Self::InvalidKey(_))
+    }
+}
 
 /// ServerChan notification service, used to send notifications to WeChat
 pub struct ServerChanNotifier {
     /// ServerChan's SCKEYs
     keys: HashSet<String>,
+    /// Keys ServerChan rejected as invalid, skipped until the notifier is rebuilt
+    rejected_keys: Mutex<HashSet<String>>,
+    /// Delivery channels, e.g. `9|66`, None for the channels configured on the ServerChan site
+    channel: Option<String>,
+    /// Message tags
+    tags: Option<Vec<String>>,
+    /// Log notifications instead of sending them
+    dry_run: bool,
+    /// Send content as written instead of escaping it for ServerChan's Markdown rendering
+    plain_text: bool,
     /// HTTP client
     client: Client,
 }
@@ -26,6 +81,11 @@ impl ServerChanNotifier {
         }
         Self {
             keys,
+            rejected_keys: Mutex::new(HashSet::new()),
+            channel: None,
+            tags: None,
+            dry_run: false,
+            plain_text: false,
             client: Client::new(),
         }
     }
@@ -40,6 +100,11 @@ impl ServerChanNotifier {
         }
         Self {
             keys: key_set,
+            rejected_keys: Mutex::new(HashSet::new()),
+            channel: None,
+            tags: None,
+            dry_run: false,
+            plain_text: false,
             client: Client::new(),
         }
     }
@@ -61,9 +126,40 @@ impl ServerChanNotifier {
         self.keys.iter().cloned().collect()
     }
     
+    /// Route messages to specific delivery channels, e.g. `9|66`
+    pub fn set_channel(&mut self, channel: Option<String>) {
+        self.channel = channel
+            .map(|channel| channel.trim().to_string())
+            .filter(|channel| !channel.is_empty());
+    }
+    
+    /// Tag messages
+    pub fn set_tags(&mut self, tags: Option<Vec<String>>) {
+        self.tags = tags
+            .map(|tags| tags.iter().map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect::<Vec<_>>())
+            .filter(|tags| !tags.is_empty());
+    }
+    
+    /// Log the rendered notifications at INFO level instead of sending them
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+    
+    /// Send content as written, e.g. content already in Markdown, instead of escaping it
+    pub fn set_plain_text(&mut self, plain_text: bool) {
+        self.plain_text = plain_text;
+    }
+    
     /// Send notification using the sc_send method provided by FangTang
     async fn sc_send(&self, text: &str, desp: &str, key: &str) -> Result<String> {
-        let params = [("text", text), ("desp", desp)];
+        let tags = self.tags.as_ref().map(|tags| tags.join("|"));
+        let mut params = vec![("text", text), ("desp", desp)];
+        if let Some(channel) = &self.channel {
+            params.push(("channel", channel));
+        }
+        if let Some(tags) = &tags {
+            params.push(("tags", tags));
+        }
         let post_data = serde_urlencoded::to_string(params)
             .map_err(|e| anyhow!("Failed to encode request parameters: {}", e))?;
             
@@ -82,7 +178,7 @@ impl ServerChanNotifier {
             format!("https://sctapi.ftqq.com/{}.send", key)
         };
         
-        debug!("Sending notification to: {}", url);
+        debug!("Sending notification to key: {}", mask_secret(key));
         
         let res = self.client.post(&url)
             .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
@@ -90,7 +186,7 @@ impl ServerChanNotifier {
             .body(post_data)
             .send()
             .await
-            .map_err(|e| anyhow!("Failed to send notification request: {}", e))?;
+            .map_err(|e| anyhow!("Failed to send notification request: {}", e.without_url()))?;
             
         let status = res.status();
         if !status.is_success() {
@@ -98,16 +194,51 @@ impl ServerChanNotifier {
         }
             
         let data = res.text().await
-            .map_err(|e| anyhow!("Failed to read response content: {}", e))?;
+            .map_err(|e| anyhow!("Failed to read response content: {}", e.without_url()))?;
             
-        debug!("Notification response: {}", data);
+        debug!("Notification response: {}", redact_secret(&data, key));
         
         Ok(data)
     }
 }
 
+impl ServerChanNotifier {
+    /// Send to a single key and check the response code
+    async fn send_to_key(&self, title: &str, content: &str, key: &str) -> Result<()> {
+        let response = self.sc_send(title, content, key).await?;
+        
+        // Parse response
+        let data: Value = serde_json::from_str(&response)
+            .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
+            
+        // Check if successful
+        let code = data["code"].as_i64().unwrap_or(-1);
+        if code != 0 {
+            let message = redact_secret(data["message"].as_str().unwrap_or("Unknown error"), key);
+            return Err(ServerChanError::from_response(code, &message).into());
+        }
+        
+        debug!("Notification sent successfully to key: {}", mask_secret(key));
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
 impl Notifier for ServerChanNotifier {
     async fn send(&self, title: &str, content: &str) -> Result<()> {
+        if self.plain_text {
+            self.send_markdown(title, content).await
+        } else {
+            self.send_markdown(title, &markdown_escape(content)).await
+        }
+    }
+    
+    async fn send_markdown(&self, title: &str, content: &str) -> Result<()> {
+        if self.dry_run {
+            info!("[DRY RUN] Would send to {} key(s): {}\n{}", self.keys.len(), title, content);
+            return Ok(());
+        }
+        
         if self.keys.is_empty() {
             error!("No ServerChan keys configured, cannot send notification");
             return Err(anyhow!("No ServerChan keys configured"));
@@ -115,40 +246,104 @@ impl Notifier for ServerChanNotifier {
         
         debug!("Sending ServerChan notification to {} keys: {}", self.keys.len(), title);
         
+        // Send to all keys that have not been rejected concurrently, so one slow key doesn't delay the rest
+        let active: Vec<&String> = {
+            let rejected_keys = self.rejected_keys.lock().unwrap();
+            self.keys.iter().filter(|key| !rejected_keys.contains(*key)).collect()
+        };
+        let mut rejected = self.keys.len() - active.len();
+        let results = join_all(active.iter().map(|key| self.send_to_key(title, content, key))).await;
+        
         let mut errors = Vec::new();
         let mut success = false;
-        
-        // Send notification to all configured keys
-        for key in &self.keys {
-            match self.sc_send(title, content, key).await {
-                Ok(response) => {
-                    // Parse response
-                    let data: Value = serde_json::from_str(&response)
-                        .map_err(|e| anyhow!("Failed to parse response: {}", e))?;
-                        
-                    // Check if successful
-                    let code = data["code"].as_i64().unwrap_or(-1);
-                    if code != 0 {
-                        let message = data["message"].as_str().unwrap_or("Unknown error");
-                        errors.push(format!("Failed to send notification to key {}: {}", key, message));
-                    } else {
-                        debug!("Notification sent successfully to key: {}", key);
-                        success = true;
-                    }
-                },
+        for (key, result) in active.into_iter().zip(results) {
+            match result {
+                Ok(()) => success = true,
                 Err(e) => {
-                    errors.push(format!("Failed to send notification to key {}: {}", key, e));
+                    if e.downcast_ref::<ServerChanError>().is_some_and(ServerChanError::is_permanent) {
+                        error!("Disabling ServerChan key {}: {}", mask_secret(key), e);
+                        self.rejected_keys.lock().unwrap().insert(key.clone());
+                        rejected += 1;
+                    }
+                    errors.push(format!("Failed to send notification to key {}: {}", mask_secret(key), e));
                 }
             }
         }
         
         // If at least one notification was successful, we consider it a success
         if success {
+            if !errors.is_empty() {
+                error!("Some notifications failed: {}", errors.join("; "));
+            }
             Ok(())
+        } else if rejected == self.keys.len() {
+            Err(ServerChanError::InvalidKey(format!("all {} keys rejected", rejected)).into())
         } else if !errors.is_empty() {
             Err(anyhow!("All notifications failed: {}", errors.join("; ")))
         } else {
             Ok(())
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Characters with a meaning in Markdown or in WeChat's renderer, e.g. `$` starting LaTeX or `@` a mention
+const MARKDOWN_SPECIAL: &str = "\\`*_[]<>#|~$@";
+
+/// Escape text for ServerChan's Markdown rendering so it shows as written: special characters are
+/// backslash-escaped, URLs become autolinks, and line breaks are kept instead of joining lines
+pub fn markdown_escape(text: &str) -> String {
+    static URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://[^\s<>]+").expect("valid URL regex"));
+    let escape = |part: &str| {
+        let mut escaped = String::with_capacity(part.len());
+        for c in part.chars() {
+            if MARKDOWN_SPECIAL.contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    };
+    
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let mut escaped = String::new();
+            let mut rest = 0;
+            for link in URL.find_iter(line) {
+                escaped.push_str(&escape(&line[rest..link.start()]));
+                escaped.push_str(&format!("<{}>", link.as_str()));
+                rest = link.end();
+            }
+            escaped.push_str(&escape(&line[rest..]));
+            escaped
+        })
+        .collect();
+    // Two trailing spaces make a hard line break
+    lines.join("  \n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_codes_map_to_typed_errors() {
+        assert!(ServerChanError::from_response(40001, "bad pushkey").is_permanent());
+        assert!(ServerChanError::from_response(20001, "bad pushkey").is_permanent());
+        assert_eq!(
+            ServerChanError::from_response(40002, "rate limit exceeded"),
+            ServerChanError::RateLimited("rate limit exceeded".to_string())
+        );
+        assert!(matches!(ServerChanError::from_response(40002, "超过当天的发送次数限制"), ServerChanError::QuotaExceeded(_)));
+        assert!(ServerChanError::from_response(50000, "server error").is_retryable());
+    }
+
+    #[test]
+    fn markdown_escape_keeps_prices_mentions_and_links_literal() {
+        assert_eq!(
+            markdown_escape("Position value: $6,425.00\nBuy 1 PURR/USDC @ 0.19\nSee https://app.hyperliquid.xyz/explorer?a=1_2"),
+            "Position value: \\$6,425.00  \nBuy 1 PURR/USDC \\@ 0.19  \nSee <https://app.hyperliquid.xyz/explorer?a=1_2>"
+        );
+        assert_eq!(markdown_escape("*bold* [x]"), "\\*bold\\* \\[x\\]");
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+
+use crate::config::{TaskConfig, TaskType};
+use crate::monitors::{api_monitor, custom_user_agent, describe_error, http_client_with_options, js_monitor, static_monitor, xml_monitor, DEFAULT_USER_AGENT};
+
+/// Longest match shown by the selector tester
+const MAX_MATCH_CHARS: usize = 300;
+
+/// Largest part of a response body shown in the preview
+const MAX_PREVIEW_BYTES: usize = 4096;
+
+/// Fetch the task URL once with the task's User-Agent and certificate setting and return the response body
+async fn fetch(task: &TaskConfig) -> Result<String> {
+    let user_agent = custom_user_agent(task.user_agent.as_deref()).unwrap_or(DEFAULT_USER_AGENT);
+    let client = http_client_with_options(user_agent, task.accept_invalid_certs);
+    let response = client
+        .get(task.url.trim())
+        .send()
+        .await
+        .map_err(|e| anyhow!("Request failed: {}", describe_error(&e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow!("HTTP status {}", status));
+    }
+
+    response.text()
+        .await
+        .map_err(|e| anyhow!("Failed to read response: {}", e))
+}
+
+/// Fetch the task URL once, rendering it in headless Chrome when the task does
+async fn fetch_page(task: &TaskConfig) -> Result<String> {
+    if task.task_type == TaskType::JsRendered {
+        js_monitor::render_page(&task.url, None, None).await
+    } else if task.task_type == TaskType::Static && task.render_js {
+        if task.accept_invalid_certs {
+            return Err(anyhow!("{}", static_monitor::RENDER_JS_INVALID_CERTS));
+        }
+        js_monitor::render_page(&task.url, None, custom_user_agent(task.user_agent.as_deref())).await
+    } else {
+        fetch(task).await
+    }
+}
+
+/// Fetch the task URL once and run its selector, describing what matched
+pub async fn test_selector(task: &TaskConfig) -> Result<String> {
+    match task.task_type {
+        TaskType::Api => test_json(&fetch(task).await?, &task.selector),
+        TaskType::Static | TaskType::JsRendered => test_css(&fetch_page(task).await?, task.selector.trim()),
+        TaskType::Xml => test_xpath(&fetch(task).await?, task.selector.trim()),
+        TaskType::Hyperliquid => Err(anyhow!("Hyperliquid tasks have no selector")),
+    }
+}
+
+/// Fetch the task URL once and return the start of the body, JSON pretty-printed for API tasks
+pub async fn preview_body(task: &TaskConfig) -> Result<String> {
+    if task.task_type == TaskType::Hyperliquid {
+        return Err(anyhow!("Hyperliquid tasks have no URL to preview"));
+    }
+
+    let body = fetch_page(task).await?;
+    let total = body.len();
+
+    let body = match task.task_type {
+        TaskType::Api => serde_json::from_str::<Value>(&body)
+            .and_then(|json| serde_json::to_string_pretty(&json))
+            .unwrap_or(body),
+        _ => body,
+    };
+
+    if body.len() <= MAX_PREVIEW_BYTES {
+        return Ok(body);
+    }
+
+    let mut end = MAX_PREVIEW_BYTES;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    Ok(format!("{}\n\n… truncated to the first {} bytes, the response is {} bytes", &body[..end], end, total))
+}
+
+/// Run each JSONPath selector (one per line) against a JSON body
+fn test_json(body: &str, selectors: &str) -> Result<String> {
+    let json: Value = serde_json::from_str(body)
+        .map_err(|e| anyhow!("Response is not JSON: {}", e))?;
+
+    let mut lines = Vec::new();
+    for selector in api_monitor::parse_selectors(selectors) {
+        let value = api_monitor::extract(&json, &selector)?;
+        lines.push(format!("{} => {}", selector, value.as_deref().map(truncate).unwrap_or_else(|| "no match".to_string())));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Run a CSS selector against an HTML body
+fn test_css(body: &str, selector: &str) -> Result<String> {
+    if selector.is_empty() {
+        return Ok(format!("No selector, the whole page is monitored ({} bytes)", body.len()));
+    }
+
+    let matches = static_monitor::select_html(body, selector)?;
+    if matches.is_empty() {
+        return Ok("no match".to_string());
+    }
+
+    let mut lines = vec![format!("{} element(s) matched", matches.len())];
+    lines.extend(matches.iter().map(|html| truncate(html)));
+    Ok(lines.join("\n"))
+}
+
+/// Run an XPath against an XML body
+fn test_xpath(body: &str, xpath: &str) -> Result<String> {
+    Ok(xml_monitor::select(body, xpath)?
+        .map(|value| truncate(&value))
+        .unwrap_or_else(|| "no match".to_string()))
+}
+
+/// Shorten long matches for display
+fn truncate(value: &str) -> String {
+    match value.char_indices().nth(MAX_MATCH_CHARS) {
+        Some((index, _)) => format!("{}…", &value[..index]),
+        None => value.to_string(),
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::{Result, anyhow};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use log::info;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::metrics::Metrics;
+
+/// Outcome of the most recent check of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckOutcome {
+    /// Not checked yet
+    Pending,
+    /// Checked, nothing changed
+    Unchanged,
+    /// Checked, a change was detected
+    Changed,
+    /// The check failed
+    Error,
+}
+
+/// Runtime status of a single task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskReport {
+    /// Task name
+    pub name: String,
+    /// Stable key of the task's monitor
+    pub id: String,
+    /// Time of the last check (RFC 3339, local time)
+    pub last_check: Option<String>,
+    /// Outcome of the last check
+    pub last_status: CheckOutcome,
+    /// Number of failed checks in a row
+    pub consecutive_errors: u32,
+    /// Time taken by the last response, for monitors that measure it
+    pub last_response_ms: Option<u64>,
+}
+
+/// Status of all running tasks, shared between the run loops and the status endpoint
+#[derive(Clone, Default)]
+pub struct StatusBoard {
+    tasks: Arc<Mutex<Vec<TaskReport>>>,
+    /// Metrics updated alongside the task reports
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl StatusBoard {
+    /// Create a board that also records every check in the given metrics
+    pub fn with_metrics(metrics: Arc<Metrics>) -> Self {
+        Self {
+            tasks: Arc::default(),
+            metrics: Some(metrics),
+        }
+    }
+    
+    /// Register a task by name and monitor id, returns its slot index
+    pub fn register(&self, name: &str, id: &str) -> usize {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.push(TaskReport {
+            name: name.to_string(),
+            id: id.to_string(),
+            last_check: None,
+            last_status: CheckOutcome::Pending,
+            consecutive_errors: 0,
+            last_response_ms: None,
+        });
+        tasks.len() - 1
+    }
+
+    /// Record the outcome of a check
+    pub fn record(&self, index: usize, outcome: CheckOutcome) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(index) {
+            if let Some(metrics) = &self.metrics {
+                metrics.record(&task.name, &task.id, outcome);
+            }
+            
+            task.last_check = Some(chrono::Local::now().to_rfc3339());
+            task.last_status = outcome;
+            if outcome == CheckOutcome::Error {
+                task.consecutive_errors += 1;
+            } else {
+                task.consecutive_errors = 0;
+            }
+        }
+    }
+
+    /// Record the time the last response of a task took
+    pub fn record_response_time(&self, index: usize, elapsed: Duration) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(index) {
+            if let Some(metrics) = &self.metrics {
+                metrics.record_response_time(&task.name, &task.id, elapsed);
+            }
+            task.last_response_ms = Some(elapsed.as_millis() as u64);
+        }
+    }
+
+    /// Copy of the current task reports
+    pub fn snapshot(&self) -> Vec<TaskReport> {
+        self.tasks.lock().unwrap().clone()
+    }
+}
+
+/// Serve `/healthz` and `/status` on the given port until the process exits
+pub async fn serve(port: u16, board: StatusBoard) -> Result<()> {
+    serve_http(port, "Status endpoint", move |req| route(req, &board)).await
+}
+
+/// Serve requests on all interfaces at the given port with a synchronous handler
+pub async fn serve_http<F>(port: u16, label: &str, handler: F) -> Result<()>
+where
+    F: Fn(&Request<Body>) -> Response<Body> + Send + Sync + 'static,
+{
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let handler = Arc::new(handler);
+
+    let make_service = make_service_fn(move |_| {
+        let handler = handler.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let response = handler(&req);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| anyhow!("Failed to bind {} on {}: {}", label, addr, e))?
+        .serve(make_service);
+
+    info!("{} listening on http://{}", label, addr);
+    server.await.map_err(|e| anyhow!("{} failed: {}", label, e))
+}
+
+/// Plain 404 response
+pub fn not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("not found"))
+        .unwrap_or_default()
+}
+
+/// Build the response for a status endpoint request
+fn route(req: &Request<Body>, board: &StatusBoard) -> Response<Body> {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/healthz") => Response::new(Body::from("ok")),
+        (&Method::GET, "/status") => {
+            let body = serde_json::json!({ "tasks": board.snapshot() });
+            Response::builder()
+                .header(hyper::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap_or_default()
+        }
+        _ => not_found(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    async fn get(board: &StatusBoard, path: &str) -> (StatusCode, String) {
+        let req = Request::get(path).body(Body::empty()).unwrap();
+        let response = route(&req, board);
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn status_reports_each_task_state() {
+        let board = StatusBoard::default();
+        let prices = board.register("Prices", "static-1");
+        let feed = board.register("Feed", "xml-1");
+        board.register("Whale", "hyperliquid-1");
+
+        board.record(prices, CheckOutcome::Changed);
+        board.record_response_time(prices, Duration::from_millis(120));
+        board.record(feed, CheckOutcome::Error);
+        board.record(feed, CheckOutcome::Error);
+
+        let (status, body) = get(&board, "/status").await;
+        assert_eq!(status, StatusCode::OK);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        let tasks = body["tasks"].as_array().unwrap();
+        let states: Vec<(&str, &str, &str, u64)> = tasks
+            .iter()
+            .map(|task| (
+                task["name"].as_str().unwrap(),
+                task["id"].as_str().unwrap(),
+                task["last_status"].as_str().unwrap(),
+                task["consecutive_errors"].as_u64().unwrap(),
+            ))
+            .collect();
+        assert_eq!(states, [
+            ("Prices", "static-1", "changed", 0),
+            ("Feed", "xml-1", "error", 2),
+            ("Whale", "hyperliquid-1", "pending", 0),
+        ]);
+        assert_eq!(tasks[0]["last_response_ms"], 120);
+        assert!(tasks[1]["last_check"].is_string());
+        assert!(tasks[2]["last_check"].is_null());
+
+        assert_eq!(get(&board, "/healthz").await, (StatusCode::OK, "ok".to_string()));
+        assert_eq!(get(&board, "/metrics").await.0, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// What happened in a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A check is about to run
+    CheckStarted,
+    /// A check finished, the detail tells whether it changed, was unchanged or failed
+    CheckCompleted,
+    /// A change was detected
+    ChangeDetected,
+    /// A notification was delivered
+    NotificationSent,
+    /// A notification could not be delivered
+    NotificationFailed,
+    /// A check or stream failed
+    Error,
+}
+
+/// One line of the event log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// UTC time in RFC 3339, with milliseconds
+    pub ts: String,
+    /// Stable monitor id of the task, see `Monitor::id`
+    pub task_id: String,
+    /// Name of the task
+    pub task: String,
+    /// What happened
+    pub event: EventKind,
+    /// Free-form description, empty when the event says it all
+    pub detail: String,
+}
+
+impl Event {
+    /// Event happening now
+    pub fn new(task_id: &str, task: &str, event: EventKind, detail: &str) -> Self {
+        Self {
+            ts: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            task_id: task_id.to_string(),
+            task: task.to_string(),
+            event,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+/// Detail of a `CheckCompleted` event for the result of a check
+pub fn check_outcome<T, E>(result: &Result<Option<T>, E>) -> &'static str {
+    match result {
+        Ok(Some(_)) => "changed",
+        Ok(None) => "unchanged",
+        Err(_) => "failed",
+    }
+}
+
+/// Machine-readable log of task events as JSON lines, for log aggregators; separate from the human log.
+/// Cheap to clone, clones append to the same file
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl EventLog {
+    /// Event log appended to the given path
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Append an event as one line
+    pub fn record(&self, event: &Event) -> Result<()> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{read_from_file, TaskLogger};
+
+    #[test]
+    fn task_events_are_written_as_json_lines() {
+        let path = std::env::temp_dir().join(format!("monitor-events-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut logger = TaskLogger::new("BTC price");
+        logger.event(EventKind::CheckStarted, "");
+        logger.set_events(Some(EventLog::new(&path)), "api-0123456789abcdef");
+        logger.event(EventKind::CheckStarted, "");
+        logger.event(EventKind::ChangeDetected, "BTC: 42 -> 43");
+
+        let events: Vec<Event> = read_from_file(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].event, EventKind::ChangeDetected);
+        assert_eq!(events[1].task_id, "api-0123456789abcdef");
+        assert_eq!(events[1].detail, "BTC: 42 -> 43");
+        assert!(read_from_file(&path).unwrap().contains(r#""event":"check_started""#));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,9 +1,23 @@
-use anyhow::Result;
-use log::error;
+use anyhow::{anyhow, Result};
+use log::{error, Level};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::Path;
-use chrono::TimeZone;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rand::Rng;
+use chrono::{Datelike, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+pub mod events;
+
+use events::{Event, EventKind, EventLog};
+
+/// Timezone `format_timestamp` shows times in, None for the local timezone
+static DISPLAY_TIMEZONE: RwLock<Option<Tz>> = RwLock::new(None);
 
 /// Write data to file
 pub fn write_to_file<P: AsRef<Path>>(path: P, data: &str) -> Result<()> {
@@ -52,13 +66,501 @@ pub fn diff_percentage(old_str: &str, new_str: &str) -> f64 {
     diff * 100.0
 }
 
-/// Format timestamp to readable string
-pub fn format_timestamp(timestamp_ms: u64) -> String {
-    match chrono::Local.timestamp_opt(timestamp_ms as i64 / 1000, 0) {
-        chrono::LocalResult::Single(dt) => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
-        _ => {
-            error!("Invalid timestamp: {}", timestamp_ms);
-            String::from("Time format error")
+/// Parse an IANA timezone name such as `Asia/Shanghai`
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|e| anyhow!("Unknown timezone {}: {}", name.trim(), e))
+}
+
+/// Show formatted timestamps in the given timezone, None for the local timezone
+pub fn set_display_timezone(timezone: Option<Tz>) {
+    *DISPLAY_TIMEZONE.write().unwrap() = timezone;
+}
+
+/// Timestamps below this are in seconds, above in milliseconds (it is 1973 in ms and year 5138 in seconds)
+const MILLISECOND_TIMESTAMP_FLOOR: u64 = 100_000_000_000;
+
+/// Format timestamp (seconds or milliseconds) to readable string in the display timezone
+pub fn format_timestamp(timestamp: u64) -> String {
+    format_timestamp_in(timestamp, *DISPLAY_TIMEZONE.read().unwrap())
+}
+
+/// Unix seconds of a timestamp given in either seconds or milliseconds
+fn timestamp_seconds(timestamp: u64) -> Option<i64> {
+    let seconds = if timestamp < MILLISECOND_TIMESTAMP_FLOOR { timestamp } else { timestamp / 1000 };
+    i64::try_from(seconds).ok()
+}
+
+/// Format timestamp to readable string in a timezone, None for the local timezone
+fn format_timestamp_in(timestamp: u64, timezone: Option<Tz>) -> String {
+    let Some(seconds) = timestamp_seconds(timestamp) else {
+        error!("Invalid timestamp: {}", timestamp);
+        return String::from("Time format error");
+    };
+    let formatted = match timezone {
+        Some(timezone) => timezone.timestamp_opt(seconds, 0).single().map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+        None => chrono::Local.timestamp_opt(seconds, 0).single().map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+    };
+    formatted.unwrap_or_else(|| {
+        error!("Invalid timestamp: {}", timestamp);
+        String::from("Time format error")
+    })
+}
+
+/// Format a USD amount with thousands separators, e.g. `$1,234,567.89` or `-$42.00`
+pub fn format_usd(value: f64) -> String {
+    let cents = (value.abs() * 100.0).round() as u64;
+    let sign = if value < 0.0 && cents > 0 { "-" } else { "" };
+    format!("{}${}.{:02}", sign, group_thousands(cents / 100), cents % 100)
+}
+
+/// Significant figures kept when formatting numbers below 1
+const SIGNIFICANT_FIGURES: i32 = 4;
+
+/// Format a number with at least `min_decimals` decimals, adding more so values below 1 keep four
+/// significant figures, e.g. `64300.00` but `0.00003123` for a low-priced token
+pub fn format_significant(value: f64, min_decimals: usize) -> String {
+    let magnitude = if value == 0.0 || !value.is_finite() { 0 } else { value.abs().log10().floor() as i32 };
+    let decimals = if magnitude < 0 {
+        (SIGNIFICANT_FIGURES - 1 - magnitude).clamp(min_decimals as i32, 12) as usize
+    } else {
+        min_decimals
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// Format a percentage with an explicit sign, e.g. `+12.34%` or `-0.50%`
+pub fn format_pct(value: f64) -> String {
+    let hundredths = (value.abs() * 100.0).round() as u64;
+    let sign = if value < 0.0 && hundredths > 0 { "-" } else { "+" };
+    format!("{}{}.{:02}%", sign, group_thousands(hundredths / 100), hundredths % 100)
+}
+
+/// Digits of a whole number grouped by three with commas
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Mask a secret for display or logs, keeping only a short prefix (e.g. `SCT1****`)
+pub fn mask_secret(secret: &str) -> String {
+    let prefix: String = secret.chars().take(4).collect();
+    if secret.chars().count() <= 8 {
+        "****".to_string()
+    } else {
+        format!("{}****", prefix)
+    }
+}
+
+/// Replace every occurrence of a secret in a text, e.g. a response or error echoing it, with its masked form
+pub fn redact_secret(text: &str, secret: &str) -> String {
+    if secret.is_empty() {
+        return text.to_string();
+    }
+    text.replace(secret, &mask_secret(secret))
+}
+
+/// Quote an argument for a POSIX shell, leaving plain words such as URLs without quotes
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Randomize a duration by up to ±`percent`%, spreading out tasks that share an interval
+pub fn jittered(duration: Duration, percent: u8) -> Duration {
+    if percent == 0 {
+        return duration;
+    }
+    
+    let spread = f64::from(percent.min(100)) / 100.0;
+    let factor = rand::thread_rng().gen_range(1.0 - spread..=1.0 + spread);
+    duration.mul_f64(factor)
+}
+
+/// Time from `now` until the next multiple of `interval` since the Unix epoch, e.g. the next full minute
+fn until_next_boundary(interval: Duration, now: SystemTime) -> Duration {
+    let interval_ms = interval.as_millis().max(1);
+    let now_ms = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    Duration::from_millis((interval_ms - now_ms % interval_ms) as u64)
+}
+
+/// When a task polls again after a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// The interval after each check finishes, randomized by up to this percentage
+    Jittered(u8),
+    /// On wall-clock multiples of the interval, so slow checks don't drift the schedule
+    Aligned,
+}
+
+impl Schedule {
+    /// Aligned polling, or jittered with the given spread
+    pub fn new(aligned: bool, jitter_percent: u8) -> Self {
+        if aligned {
+            Schedule::Aligned
+        } else {
+            Schedule::Jittered(jitter_percent)
+        }
+    }
+    
+    /// How long to sleep before the next check
+    pub fn next_sleep(&self, interval: Duration) -> Duration {
+        match self {
+            Schedule::Jittered(percent) => jittered(interval, *percent),
+            Schedule::Aligned => until_next_boundary(interval, SystemTime::now()),
+        }
+    }
+}
+
+/// Lower-case day names, Monday first
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Days and local times a task polls in, e.g. `mon-fri 09:30-16:00`; outside them it sleeps.
+/// Kept as that text in the configuration, days may be left out for every day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ActiveSchedule {
+    /// Days the window opens on, Monday first
+    days: [bool; 7],
+    /// Opening time
+    start: NaiveTime,
+    /// Closing time, at or before the opening time for a window running past midnight
+    end: NaiveTime,
+}
+
+impl ActiveSchedule {
+    /// Whether the window is open at this local time
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        let day = now.weekday().num_days_from_monday() as usize;
+        let previous = (day + 6) % 7;
+        let time = now.time();
+        if self.start < self.end {
+            self.days[day] && time >= self.start && time < self.end
+        } else {
+            // Runs past midnight, opened either today or yesterday
+            (self.days[day] && time >= self.start) || (self.days[previous] && time < self.end)
+        }
+    }
+    
+    /// Time left until the window next opens, None while it is open
+    pub fn until_active(&self, now: NaiveDateTime) -> Option<Duration> {
+        if self.is_active(now) {
+            return None;
+        }
+        (0..=7)
+            .map(|offset| now.date() + chrono::Duration::days(offset))
+            .filter(|date| self.days[date.weekday().num_days_from_monday() as usize])
+            .map(|date| date.and_time(self.start))
+            .find(|open| *open > now)
+            .and_then(|open| (open - now).to_std().ok())
+    }
+}
+
+impl FromStr for ActiveSchedule {
+    type Err = anyhow::Error;
+    
+    /// Parse `[days] HH:MM-HH:MM`, days being names or ranges separated by commas, e.g. `mon-fri` or `sat,sun`
+    fn from_str(schedule: &str) -> Result<Self> {
+        let parts: Vec<&str> = schedule.split_whitespace().collect();
+        let (days, times) = match parts.as_slice() {
+            [times] => ([true; 7], *times),
+            [days, times] => (parse_days(days)?, *times),
+            _ => return Err(anyhow!("Active schedule {} must look like \"mon-fri 09:30-16:00\"", schedule.trim())),
+        };
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| anyhow!("Active hours {} must look like 09:30-16:00", times))?;
+        let time = |time: &str| NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(|_| anyhow!("Invalid time {}, expected HH:MM", time));
+        Ok(Self { days, start: time(start)?, end: time(end)? })
+    }
+}
+
+/// Parse comma-separated day names or ranges, a range may wrap around the week, e.g. `fri-mon`
+fn parse_days(input: &str) -> Result<[bool; 7]> {
+    let day = |name: &str| {
+        let name = name.trim().to_lowercase();
+        DAY_NAMES
+            .iter()
+            .position(|day| name.len() >= 3 && day.starts_with(&name[..3]))
+            .ok_or_else(|| anyhow!("Unknown day {}, expected mon, tue, wed, thu, fri, sat or sun", name))
+    };
+    let mut days = [false; 7];
+    for item in input.split(',').filter(|item| !item.trim().is_empty()) {
+        let (first, last) = match item.split_once('-') {
+            Some((first, last)) => (day(first)?, day(last)?),
+            None => (day(item)?, day(item)?),
+        };
+        let mut current = first;
+        loop {
+            days[current] = true;
+            if current == last {
+                break;
+            }
+            current = (current + 1) % 7;
+        }
+    }
+    if !days.contains(&true) {
+        return Err(anyhow!("Active schedule needs at least one day"));
+    }
+    Ok(days)
+}
+
+impl fmt::Display for ActiveSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.days.contains(&false) {
+            // Consecutive days as ranges, e.g. mon-fri
+            let mut ranges = Vec::new();
+            let mut day = 0;
+            while day < 7 {
+                if !self.days[day] {
+                    day += 1;
+                    continue;
+                }
+                let first = day;
+                while day + 1 < 7 && self.days[day + 1] {
+                    day += 1;
+                }
+                ranges.push(if first == day {
+                    DAY_NAMES[day].to_string()
+                } else {
+                    format!("{}-{}", DAY_NAMES[first], DAY_NAMES[day])
+                });
+                day += 1;
+            }
+            write!(f, "{} ", ranges.join(","))?;
         }
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
     }
-} 
\ No newline at end of file
+}
+
+impl TryFrom<String> for ActiveSchedule {
+    type Error = anyhow::Error;
+    
+    fn try_from(schedule: String) -> Result<Self> {
+        schedule.parse()
+    }
+}
+
+impl From<ActiveSchedule> for String {
+    fn from(schedule: ActiveSchedule) -> Self {
+        schedule.to_string()
+    }
+}
+
+/// Delays between reconnection attempts, doubling from an initial delay up to a cap and jittered
+/// so tasks that dropped together don't retry in lockstep
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    jitter_percent: u8,
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Start at `initial`, never wait longer than `max`
+    pub fn new(initial: Duration, max: Duration, jitter_percent: u8) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+            jitter_percent,
+            attempts: 0,
+        }
+    }
+    
+    /// Delay before the next attempt, counting the attempt
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.initial
+            .saturating_mul(2u32.saturating_pow(self.attempts))
+            .min(self.max);
+        self.attempts = self.attempts.saturating_add(1);
+        jittered(delay, self.jitter_percent).min(self.max)
+    }
+    
+    /// Attempts made since the last success
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+    
+    /// Start over from the initial delay after a success
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+/// Logger for a single monitoring task
+///
+/// Messages are prefixed with `[name]` and logged under the target `task::<name>`,
+/// so one task can be filtered with e.g. `RUST_LOG=task::btc_price=debug`.
+#[derive(Debug, Clone)]
+pub struct TaskLogger {
+    name: String,
+    target: String,
+    /// Structured event log and the task's monitor id, None when events aren't recorded
+    events: Option<(EventLog, String)>,
+}
+
+impl TaskLogger {
+    /// Create a logger for the task with the given name
+    pub fn new(name: &str) -> Self {
+        let slug: String = name
+            .trim()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        Self {
+            name: name.to_string(),
+            target: format!("task::{}", slug),
+            events: None,
+        }
+    }
+
+    /// Also record events to a structured log, tagged with the task's monitor id; None records nothing
+    pub fn set_events(&mut self, events: Option<EventLog>, task_id: &str) {
+        self.events = events.map(|events| (events, task_id.to_string()));
+    }
+
+    /// Name of the task
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Log a message and return it with the task prefix, for display elsewhere
+    pub fn log(&self, level: Level, message: &str) -> String {
+        let line = format!("[{}] {}", self.name, message);
+        log::log!(target: &self.target, level, "{}", line);
+        line
+    }
+
+    /// Log at debug level
+    pub fn debug(&self, message: &str) {
+        self.log(Level::Debug, message);
+    }
+
+    /// Log at info level
+    pub fn info(&self, message: &str) {
+        self.log(Level::Info, message);
+    }
+
+    /// Log at error level
+    pub fn error(&self, message: &str) {
+        self.log(Level::Error, message);
+    }
+
+    /// Record an event in the structured log, if one is set
+    pub fn event(&self, kind: EventKind, detail: &str) {
+        if let Some((events, task_id)) = &self.events {
+            if let Err(e) = events.record(&Event::new(task_id, &self.name, kind, detail)) {
+                self.log(Level::Warn, &format!("Failed to record event: {}", e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usd_amounts_are_grouped_and_rounded() {
+        assert_eq!(format_usd(1234567.891), "$1,234,567.89");
+        assert_eq!(format_usd(999.995), "$1,000.00");
+        assert_eq!(format_usd(0.0), "$0.00");
+        assert_eq!(format_usd(-42.0), "-$42.00");
+        assert_eq!(format_usd(-0.001), "$0.00");
+    }
+
+    #[test]
+    fn secrets_are_masked_wherever_they_appear() {
+        assert_eq!(mask_secret("SCT123456789"), "SCT1****");
+        assert_eq!(mask_secret("short"), "****");
+        assert_eq!(redact_secret("sendkey SCT123456789 is invalid", "SCT123456789"), "sendkey SCT1**** is invalid");
+        assert_eq!(redact_secret("no key here", ""), "no key here");
+    }
+
+    #[test]
+    fn timestamps_use_the_given_timezone() {
+        let timestamp_ms = 1_700_000_000_000;
+        assert_eq!(format_timestamp_in(timestamp_ms, Some(chrono_tz::UTC)), "2023-11-14 22:13:20");
+        assert_eq!(format_timestamp_in(timestamp_ms, Some(parse_timezone(" Asia/Shanghai ").unwrap())), "2023-11-15 06:13:20");
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn timestamps_in_seconds_and_milliseconds_resolve_to_the_same_date() {
+        assert_eq!(format_timestamp_in(1_718_000_003_000, Some(chrono_tz::UTC)), "2024-06-10 06:13:23");
+        assert_eq!(format_timestamp_in(1_718_000_003, Some(chrono_tz::UTC)), "2024-06-10 06:13:23");
+        assert_eq!(format_timestamp_in(u64::MAX, Some(chrono_tz::UTC)), "Time format error");
+    }
+
+    #[test]
+    fn aligned_sleeps_end_on_interval_boundaries() {
+        let minute = Duration::from_secs(60);
+        let at = |secs: u64, millis: u64| UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_millis(millis);
+        assert_eq!(until_next_boundary(minute, at(1_718_000_010, 250)), Duration::from_millis(29_750));
+        assert_eq!(until_next_boundary(minute, at(1_718_000_040, 0)), minute);
+        assert_eq!(until_next_boundary(Duration::from_secs(300), at(1_718_000_000, 0)), Duration::from_secs(100));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_its_cap_until_reset() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10), 0);
+        let delays: Vec<u64> = (0..6).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+        assert_eq!(backoff.attempts(), 6);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn percentages_always_carry_a_sign() {
+        assert_eq!(format_pct(12.344), "+12.34%");
+        assert_eq!(format_pct(-0.5), "-0.50%");
+        assert_eq!(format_pct(-0.004), "+0.00%");
+        assert_eq!(format_pct(1500.0), "+1,500.00%");
+    }
+
+    #[test]
+    fn active_schedules_open_on_their_days_and_hours() {
+        let at = |date: &str| NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap();
+        let market: ActiveSchedule = "Mon-Fri 09:30-16:00".parse().unwrap();
+        assert_eq!(market.to_string(), "mon-fri 09:30-16:00");
+        // 2024-06-07 is a Friday
+        assert!(market.is_active(at("2024-06-07 09:30")));
+        assert!(!market.is_active(at("2024-06-07 16:00")));
+        assert_eq!(market.until_active(at("2024-06-07 17:00")), Some(Duration::from_secs((2 * 24 + 16) * 3600 + 30 * 60)));
+        assert_eq!(market.until_active(at("2024-06-07 10:00")), None);
+
+        let nights: ActiveSchedule = "fri,sat 22:00-02:00".parse().unwrap();
+        assert!(nights.is_active(at("2024-06-09 01:00")));
+        assert!(!nights.is_active(at("2024-06-10 01:00")));
+        assert_eq!("fri-mon 00:00-00:00".parse::<ActiveSchedule>().unwrap().to_string(), "mon,fri-sun 00:00-00:00");
+        assert!("weekdays 09:00-17:00".parse::<ActiveSchedule>().is_err());
+        assert!("09:00".parse::<ActiveSchedule>().is_err());
+    }
+
+    #[test]
+    fn small_numbers_keep_significant_figures() {
+        assert_eq!(format_significant(64300.0, 2), "64300.00");
+        assert_eq!(format_significant(1.5, 2), "1.50");
+        assert_eq!(format_significant(0.5, 2), "0.5000");
+        assert_eq!(format_significant(0.000031234, 2), "0.00003123");
+        assert_eq!(format_significant(-0.000031234, 2), "-0.00003123");
+        assert_eq!(format_significant(0.115, 4), "0.1150");
+        assert_eq!(format_significant(0.0, 2), "0.00");
+    }
+}
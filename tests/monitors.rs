@@ -0,0 +1,714 @@
+//! Integration tests running each monitor against a local wiremock server
+
+use hyperliquid_monitor::monitors::api_monitor::ApiMonitor;
+use hyperliquid_monitor::monitors::hyperliquid_monitor::HyperliquidMonitor;
+use hyperliquid_monitor::monitors::static_monitor::{CompareMode, StaticMonitor};
+use hyperliquid_monitor::monitors::stream_monitor::{HyperliquidFillStream, StreamMonitor};
+use hyperliquid_monitor::monitors::{ChangeKind, Monitor};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+
+/// Recorded response in `tests/fixtures/<name>.json`
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+    let content = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+    serde_json::from_str(&content).unwrap_or_else(|e| panic!("Invalid fixture {}: {}", path, e))
+}
+
+/// Answer `userFills` with no fills, for contract monitors whose tests are about positions
+async fn serve_no_fills(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "userFills", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(server)
+        .await;
+}
+
+/// Serve the given bodies in order on `GET /page`, each exactly once
+async fn serve_in_order(server: &MockServer, bodies: &[&str]) {
+    for body in bodies {
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(*body))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn static_monitor_fires_only_when_the_body_changes() {
+    let server = MockServer::start().await;
+    serve_in_order(&server, &[
+        "<html><title>v1</title></html>",
+        "<html><title>v1</title></html>",
+        "<html><title>v2</title></html>",
+    ]).await;
+
+    let mut monitor = StaticMonitor::new(&format!("{}/page", server.uri()), "", 60);
+
+    assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Updated);
+    assert!(change.message.contains("'v1' -> 'v2'"), "{}", change.message);
+}
+
+#[tokio::test]
+async fn static_monitor_compare_modes_ignore_markup_changes() {
+    for (mode, selector) in [(CompareMode::NormalizedText, ""), (CompareMode::Selector, "p")] {
+        let server = MockServer::start().await;
+        serve_in_order(&server, &[
+            "<div class=\"a\"><p>Sold   out</p></div>",
+            "<div class=\"b\">\n  <p>Sold   out</p>\n</div>",
+            "<div class=\"b\"><p>In stock</p></div>",
+        ]).await;
+
+        let url = format!("{}/page", server.uri());
+        let mut monitor = StaticMonitor::builder(&url, selector).compare_mode(mode).build().unwrap();
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+        assert!(monitor.check().await.unwrap().is_none(), "{}", mode);
+        assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Updated);
+    }
+}
+
+#[tokio::test]
+async fn static_monitor_decodes_pages_in_their_declared_charset() {
+    let gbk_page: &[u8] = include_bytes!("fixtures/gbk_page.html");
+    let (text, _, _) = encoding_rs::GBK.decode(gbk_page);
+    let renamed = text.replace("比特币", "以太坊");
+    let (renamed, _, _) = encoding_rs::GBK.encode(&renamed);
+
+    let server = MockServer::start().await;
+    let responses = [
+        // Charset from the <meta> tag
+        (gbk_page.to_vec(), "text/html"),
+        // The header wins over a stale <meta> tag, same text so no change
+        (text.as_bytes().to_vec(), "text/html; charset=utf-8"),
+        (renamed.into_owned(), "text/html; charset=GBK"),
+    ];
+    for (body, content_type) in responses {
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, content_type))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = StaticMonitor::new(&format!("{}/page", server.uri()), "", 60);
+
+    assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert!(change.message.contains("'比特币价格' -> '以太坊价格'"), "{}", change.message);
+}
+
+#[tokio::test]
+async fn api_monitor_extracts_and_tracks_a_jsonpath_value() {
+    let server = MockServer::start().await;
+    serve_in_order(&server, &[
+        r#"{"data":{"price":"99.5","volume":1}}"#,
+        r#"{"data":{"price":"99.5","volume":2}}"#,
+        r#"{"data":{"price":"100.5","volume":3}}"#,
+    ]).await;
+
+    let mut monitor = ApiMonitor::new(format!("{}/page", server.uri()), "$.data.price".to_string(), 60);
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Started);
+    assert!(change.details.contains("Initial value: 99.5"), "{}", change.details);
+
+    // Fields outside the selector do not count as changes
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Updated);
+    assert!(change.details.contains("Current value:\n100.5"), "{}", change.details);
+    assert!(change.details.contains("Previous value:\n99.5"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn static_monitor_refuses_pages_over_the_size_limit() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(4096)))
+        .mount(&server)
+        .await;
+
+    let mut monitor = StaticMonitor::new(&format!("{}/page", server.uri()), "", 60);
+    monitor.set_max_body_bytes(Some(2048));
+    let e = monitor.check().await.err().unwrap();
+    assert!(e.to_string().contains("larger than 2 KB"), "{}", e);
+
+    monitor.set_max_body_bytes(Some(8192));
+    assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_parses_positions_and_detects_changes() {
+    let account = fixture("clearinghouse_state");
+    let mut closed_eth = account.clone();
+    closed_eth["assetPositions"].as_array_mut().unwrap().truncate(1);
+
+    let server = MockServer::start().await;
+    serve_no_fills(&server).await;
+    for state in [&account, &account, &closed_eth] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(state))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, false, true);
+    monitor.set_api_url(&server.uri());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Started);
+    assert!(change.details.contains("Asset: BTC\nLeverage: 10x\nType: long"), "{}", change.details);
+    assert!(change.details.contains("Asset: ETH\nLeverage: 5x\nType: short"), "{}", change.details);
+    assert!(change.details.contains("Position value: $6,425.00\nPNL: $20.00 (+3.10%)"), "{}", change.details);
+
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Position);
+    assert!(!change.details.contains("Asset: ETH"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_reports_spot_balance_changes() {
+    let balances = |tokens: &[(&str, &str)]| {
+        let balances: Vec<Value> = tokens
+            .iter()
+            .map(|(coin, total)| json!({ "coin": coin, "token": 0, "hold": "0.0", "total": total, "entryNtl": "0.0" }))
+            .collect();
+        json!({ "balances": balances })
+    };
+
+    let server = MockServer::start().await;
+    let states = [
+        balances(&[("USDC", "100.0"), ("PURR", "5.0")]),
+        balances(&[("USDC", "100.0"), ("PURR", "5.0")]),
+        balances(&[("USDC", "80.0"), ("HYPE", "2.0")]),
+    ];
+    for state in states {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "spotClearinghouseState", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(state))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::builder(ADDRESS)
+        .spot(false)
+        .contract(false)
+        .monitor_spot_balance(true)
+        .api_url(&server.uri())
+        .build()
+        .unwrap();
+
+    assert!(monitor.check().await.unwrap().is_none());
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Balance);
+    assert!(change.message.ends_with("3 spot balances changed"), "{}", change.message);
+    assert!(change.details.contains("HYPE: 0 -> 2 (+2)\nPURR: 5 -> 0 (-5)\nUSDC: 100 -> 80 (-20)"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_reports_vault_deposits_and_withdrawals() {
+    let vault = |address: &str, equity: &str| json!({ "vaultAddress": address, "equity": equity });
+    let server = MockServer::start().await;
+    let states = [
+        json!([vault("0xaaa", "1000.0")]),
+        json!([vault("0xaaa", "1000.001"), vault("0xbbb", "250.0")]),
+        json!([vault("0xbbb", "262.5")]),
+    ];
+    for state in states {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "userVaultEquities", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(state))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::builder(ADDRESS)
+        .notes("LP")
+        .spot(false)
+        .contract(false)
+        .monitor_vaults(true)
+        .api_url(&server.uri())
+        .build()
+        .unwrap();
+
+    assert!(monitor.check().await.unwrap().is_none());
+
+    // The rounding move of the first vault is not reported
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Balance);
+    assert_eq!(change.message, "LP - Vault 0xbbb: deposited, equity $250.00");
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.message, "LP - 2 vaults changed");
+    assert!(change.details.contains("Vault 0xaaa: withdrawn, equity was $1,000.00\nVault 0xbbb: equity $250.00 -> $262.50 (+$12.50)"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_reports_vault_drift_once_it_adds_up() {
+    let vault = |equity: &str| json!([{ "vaultAddress": "0xaaa", "equity": equity }]);
+    let server = MockServer::start().await;
+    for equity in ["1000.0", "1004.0", "1008.0", "1012.0", "1013.0"] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "userVaultEquities", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vault(equity)))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::builder(ADDRESS)
+        .notes("LP")
+        .spot(false)
+        .contract(false)
+        .monitor_vaults(true)
+        .vault_change_threshold(1.0)
+        .api_url(&server.uri())
+        .build()
+        .unwrap();
+
+    assert!(monitor.check().await.unwrap().is_none());
+
+    // PnL drift under 1% per check is not reported until it adds up
+    assert!(monitor.check().await.unwrap().is_none());
+    assert!(monitor.check().await.unwrap().is_none());
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.message, "LP - Vault 0xaaa: equity $1,000.00 -> $1,012.00 (+$12.00)");
+
+    // The reported equity is the new baseline
+    assert!(monitor.check().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn hyperliquid_snapshot_reports_positions_orders_and_account_value() {
+    let account = fixture("clearinghouse_state");
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(account))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "openOrders", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("open_orders")))
+        .mount(&server)
+        .await;
+
+    let monitor = HyperliquidMonitor::builder(ADDRESS).notes("Whale").api_url(&server.uri()).build().unwrap();
+    let snapshot = monitor.snapshot().await.unwrap();
+    assert_eq!(snapshot.kind, ChangeKind::Snapshot);
+    assert_eq!(snapshot.message, "Whale - Account value $10,250.00, 2 positions, 2 open orders");
+    assert!(snapshot.details.contains("Withdrawable: $8,983.40"), "{}", snapshot.details);
+    assert!(snapshot.details.contains("Asset: ETH\nLeverage: 5x"), "{}", snapshot.details);
+    assert!(snapshot.details.contains("Open orders:\nBTC Buy 0.05 @ 60000.0\n@107 Sell 8.0 @ 27.5"), "{}", snapshot.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_skips_position_changes_below_the_threshold() {
+    let account = fixture("clearinghouse_state");
+    let resized = |szi: &str, entry_px: &str| {
+        let mut state = account.clone();
+        state["assetPositions"][0]["position"]["szi"] = json!(szi);
+        state["assetPositions"][0]["position"]["entryPx"] = json!(entry_px);
+        state
+    };
+
+    let server = MockServer::start().await;
+    serve_no_fills(&server).await;
+    // A partial fill moves BTC 4%, a later one 15% from the reported size
+    for state in [account.clone(), resized("0.104", "64300.0"), resized("0.115", "64400.0")] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(state))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, false, true);
+    monitor.set_api_url(&server.uri());
+    monitor.set_position_change_threshold(10.0);
+
+    assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Position);
+    assert!(change.details.contains("Position size: 0.1150"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_alerts_on_pnl_moves_separately_from_position_changes() {
+    let account = fixture("clearinghouse_state");
+    let with_btc_pnl = |pnl: &str| {
+        let mut state = account.clone();
+        state["assetPositions"][0]["position"]["unrealizedPnl"] = json!(pnl);
+        state
+    };
+
+    let server = MockServer::start().await;
+    serve_no_fills(&server).await;
+    for state in [account.clone(), with_btc_pnl("80.0"), with_btc_pnl("150.0"), with_btc_pnl("180.0")] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(state))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, false, true);
+    monitor.set_api_url(&server.uri());
+    monitor.set_pnl_alert_threshold(Some(100.0));
+
+    assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::ThresholdCrossed);
+    assert!(change.message.contains("PnL moved: BTC long $20.00 -> $150.00"), "{}", change.message);
+    assert!(change.details.contains("Mark price: 64250.00"), "{}", change.details);
+
+    // Measured from the reported PnL, not the first one
+    assert!(monitor.check().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_backfills_recent_fills_on_first_check() {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let fill = |tid: &str, time: u64| json!({
+        "tid": tid, "coin": "@107", "side": "B", "px": "24.5", "sz": "10", "time": time,
+    });
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "userFills", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([fill("3", now_ms - 60_000)])))
+        .mount(&server)
+        .await;
+
+    // A full first page, oldest first, then the rest from the last time seen
+    let page_start = now_ms - 25 * 60_000;
+    let full_page: Vec<Value> = (0..2000).map(|i| fill(&format!("p{}", i), page_start + i)).collect();
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "userFillsByTime", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&full_page))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "userFillsByTime", "startTime": page_start + 1999 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([full_page[1999], fill("3", now_ms - 60_000)])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, true, false);
+    monitor.set_api_url(&server.uri());
+    monitor.set_backfill_minutes(30);
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Trade);
+    assert!(change.message.contains("2001 fills in the last 30 minutes"), "{}", change.message);
+    assert!(change.details.contains("(ID 3)") && change.details.contains("(ID p0)"), "{}", change.details);
+
+    assert!(monitor.check().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_backfills_perp_fills_for_contract_tasks() {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let fill = |tid: &str, coin: &str, time: u64| json!({
+        "tid": tid, "coin": coin, "side": "A", "px": "3100.0", "sz": "0.5", "time": time,
+    });
+
+    let server = MockServer::start().await;
+    serve_no_fills(&server).await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("clearinghouse_state_flat")))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "userFillsByTime", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            fill("7", "ETH", now_ms - 120_000),
+            fill("8", "@107", now_ms - 60_000),
+        ])))
+        .mount(&server)
+        .await;
+
+    // No spot tracker and no fill in the latest page, the window is still reported
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, false, true);
+    monitor.set_api_url(&server.uri());
+    monitor.set_backfill_minutes(10);
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Trade);
+    assert!(change.message.contains("1 fills in the last 10 minutes"), "{}", change.message);
+    assert!(change.details.contains("Sell 0.5 ETH @ 3100.0") && !change.details.contains("@107"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_reports_new_funding_payments() {
+    let payment = |time: u64, coin: &str, usdc: &str| json!({
+        "time": time,
+        "delta": { "type": "funding", "coin": coin, "usdc": usdc, "fundingRate": "0.0001" },
+    });
+    let seen = json!([payment(1_000, "BTC", "-1.0")]);
+    let new = json!([payment(2_000, "BTC", "-1.5"), payment(2_000, "ETH", "0.5")]);
+
+    let server = MockServer::start().await;
+    for payments in [&seen, &new, &json!([])] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "userFunding", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(payments))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, false, false);
+    monitor.set_api_url(&server.uri());
+    monitor.set_monitor_funding(true);
+
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Funding);
+    assert!(change.details.contains("BTC -1.5000 USDC (rate 0.0100%)"), "{}", change.details);
+    assert!(change.details.contains("Session funding total: -1.0000 USDC"), "{}", change.details);
+
+    assert!(monitor.check().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_only_reports_spot_fills_to_the_spot_tracker() {
+    let fills = fixture("user_fills_mixed");
+    let with_new = |coin: &str, tid: u64| {
+        let mut newer = fills.clone();
+        let mut fill = newer[0].clone();
+        fill["coin"] = json!(coin);
+        fill["tid"] = json!(tid);
+        newer.as_array_mut().unwrap().insert(0, fill);
+        newer
+    };
+
+    let server = MockServer::start().await;
+    for response in [fills.clone(), with_new("ETH", 1004), with_new("PURR/USDC", 1005)] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "userFills", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, true, false);
+    monitor.set_api_url(&server.uri());
+
+    // The newest fill is a BTC perp, the spot tracker starts from the @107 spot fill
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Started);
+    assert!(change.details.contains("Asset: @107"), "{}", change.details);
+    assert!(change.details.contains("Transaction ID: 1002"), "{}", change.details);
+
+    // A new perp fill is not a spot trade
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Trade);
+    assert!(change.details.contains("Asset: PURR/USDC"), "{}", change.details);
+    assert!(change.details.contains("Transaction ID: 1005"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_reports_perp_fills_to_contract_tasks() {
+    let fills = fixture("user_fills_mixed");
+    let with_new = |coin: &str, tid: u64| {
+        let mut newer = fills.clone();
+        let mut fill = newer[0].clone();
+        fill["coin"] = json!(coin);
+        fill["tid"] = json!(tid);
+        newer.as_array_mut().unwrap().insert(0, fill);
+        newer
+    };
+
+    let server = MockServer::start().await;
+    for response in [fills.clone(), with_new("PURR/USDC", 1004), with_new("ETH", 1005)] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "type": "userFills", "user": ADDRESS })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("clearinghouse_state")))
+        .mount(&server)
+        .await;
+
+    let mut monitor = HyperliquidMonitor::new(ADDRESS, 60, false, true);
+    monitor.set_api_url(&server.uri());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Started);
+    assert!(change.details.contains("Latest perp transaction:\nAsset: BTC"), "{}", change.details);
+
+    // A new spot fill is not a contract trade
+    assert!(monitor.check().await.unwrap().is_none());
+
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Trade);
+    assert!(change.details.contains("Market: perp\nAsset: ETH"), "{}", change.details);
+    assert!(change.details.contains("Transaction ID: 1005\n\nPrevious transaction ID: 1003"), "{}", change.details);
+}
+
+#[tokio::test]
+async fn hyperliquid_monitor_starts_once_with_every_tracker_seeded() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "userFills", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("user_fills_mixed")))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "type": "clearinghouseState", "user": ADDRESS })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("clearinghouse_state")))
+        .mount(&server)
+        .await;
+
+    let mut monitor = HyperliquidMonitor::new_with_notes(ADDRESS, 60, true, true, "Whale");
+    monitor.set_api_url(&server.uri());
+
+    // Spot fills and positions are both recorded by the first check, in a single start
+    let change = monitor.check().await.unwrap().unwrap();
+    assert_eq!(change.kind, ChangeKind::Started);
+    assert_eq!(change.message, "Started monitoring: Whale");
+    assert!(change.details.contains("Transaction ID: 1002"), "{}", change.details);
+    assert!(change.details.contains("Asset: BTC\nLeverage: 10x"), "{}", change.details);
+
+    assert!(monitor.check().await.unwrap().is_none());
+}
+
+/// `userFills` message with one spot fill per timestamp
+fn fills_message(snapshot: bool, times: &[u64]) -> Message {
+    let fills: Vec<Value> = times
+        .iter()
+        .map(|time| json!({ "coin": "@107", "side": "B", "px": "1.5", "sz": "10", "time": time, "tid": time }))
+        .collect();
+    Message::Text(json!({ "channel": "userFills", "data": { "isSnapshot": snapshot, "user": ADDRESS, "fills": fills } }).to_string())
+}
+
+#[tokio::test]
+async fn fill_stream_reports_live_fills_and_catches_up_after_reconnecting() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let sessions = [
+            vec![fills_message(true, &[1_000]), fills_message(false, &[2_000])],
+            vec![fills_message(true, &[1_000, 2_000, 3_000])],
+        ];
+        for messages in sessions {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+            let subscribe: Value = serde_json::from_str(socket.next().await.unwrap().unwrap().to_text().unwrap()).unwrap();
+            assert_eq!(subscribe["subscription"], json!({ "type": "userFills", "user": ADDRESS }));
+            for message in messages {
+                socket.send(message).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+        }
+    });
+
+    let mut stream = HyperliquidFillStream::new(ADDRESS, true, true);
+    stream.set_ws_url(&url);
+
+    assert_eq!(stream.next_change().await.unwrap().unwrap().kind, ChangeKind::Started);
+    let live = stream.next_change().await.unwrap().unwrap();
+    assert_eq!(live.kind, ChangeKind::Trade);
+    assert!(live.details.contains("Buy 10 @107 @ 1.5"), "{}", live.details);
+
+    // The server hung up, the next call reconnects and reports only the fill missed meanwhile
+    assert!(stream.next_change().await.is_err());
+    let missed = stream.next_change().await.unwrap().unwrap();
+    assert!(missed.message.contains("New fill"), "{}", missed.message);
+    assert!(missed.details.contains("(ID 3000)"), "{}", missed.details);
+
+    server.await.unwrap();
+}
+
+/// HTTPS server on a random local port presenting the self-signed certificate in `tests/fixtures`,
+/// answering every request with the same page
+async fn serve_self_signed() -> String {
+    let read = |name: &str| std::fs::read(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap();
+    let identity = native_tls::Identity::from_pkcs8(&read("self_signed_cert.pem"), &read("self_signed_key.pem")).unwrap();
+    let acceptor = tokio_native_tls::TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("https://127.0.0.1:{}/page", listener.local_addr().unwrap().port());
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let Ok(mut stream) = acceptor.accept(stream).await else {
+                continue;
+            };
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).await;
+            let page = "<p>Internal dashboard</p>";
+            let response = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", page.len(), page);
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn static_monitor_names_certificate_and_connection_failures() {
+    let mut monitor = StaticMonitor::new(&serve_self_signed().await, "", 60);
+    let e = monitor.check().await.err().unwrap().to_string();
+    assert!(e.contains("TLS certificate rejected") && e.contains("self-signed certificate"), "{}", e);
+
+    // Nothing listens on a port that was just released
+    let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+    let mut monitor = StaticMonitor::new(&format!("http://127.0.0.1:{}/page", port), "", 60);
+    let e = monitor.check().await.err().unwrap().to_string();
+    assert!(e.contains("connection refused"), "{}", e);
+}
+
+#[tokio::test]
+async fn static_monitor_can_accept_a_self_signed_certificate() {
+    let mut monitor = StaticMonitor::builder(&serve_self_signed().await, "p").accept_invalid_certs(true).build().unwrap();
+    assert_eq!(monitor.check().await.unwrap().unwrap().kind, ChangeKind::Started);
+}